@@ -3,8 +3,11 @@ extern crate inkwell;
 use inkwell::context::Context;
 use rcc_codegen::Emitter;
 use rcc_parser::parse;
+use rcc_preprocessor::Preprocessor;
+use rcc_syntax::span::{SourceMap, Span};
+use rcc_syntax::token::ManagedToken;
 use rcc_syntax::tokens::Tokens;
-use rcc_syntax::Tokenizer;
+use std::path::Path;
 use std::process;
 
 pub fn compile(code: String) {
@@ -12,14 +15,49 @@ pub fn compile(code: String) {
     println!("================================{}", code);
     println!("================================\n");
 
-    // tokenize
-    let mut tokens = Tokens::new(Tokenizer::tokenize(&code));
+    // preprocess and tokenize (quoted includes are searched from the current directory)
+    let mut preprocessor = Preprocessor::new(vec![]);
+    let tokens = match preprocessor.preprocess(&code, Path::new("<input>")) {
+        Ok(tokens) => tokens,
+        Err(err) => panic!(err.to_string()),
+    };
+    print_warnings(&preprocessor);
+    emit(tokens, preprocessor.source_map());
+}
+
+/// compile a source file with a preprocessor which has the include paths (`-I`) and the macros
+/// (`-D` and `-U`) given on the command line
+pub fn compile_file(path: &Path, mut preprocessor: Preprocessor) {
+    let tokens = match preprocessor.preprocess_file(path) {
+        Ok(tokens) => tokens,
+        Err(err) => panic!(err.to_string()),
+    };
+    print_warnings(&preprocessor);
+    emit(tokens, preprocessor.source_map());
+}
+
+fn print_warnings(preprocessor: &Preprocessor) {
+    for warning in preprocessor.warnings() {
+        eprintln!("{}", warning);
+    }
+}
+
+/// prefix an error message with the position (`file:line:column`) of its span
+fn locate(message: String, span: Option<Span>, source_map: &SourceMap) -> String {
+    match span {
+        Some(span) => format!("{}: {}", source_map.locate(span), message),
+        None => message,
+    }
+}
+
+fn emit(tokens: Vec<ManagedToken>, source_map: &SourceMap) {
+    let mut tokens = Tokens::new(tokens);
     println!("{}", tokens);
 
     // parse
     let node = match parse(&mut tokens) {
         Ok(node) => node,
-        Err(err) => panic!(err.to_string()),
+        Err(err) => panic!(locate(err.to_string(), err.span(), source_map)),
     };
     println!("{}", node.to_string());
 
@@ -29,7 +67,7 @@ pub fn compile(code: String) {
     let module = context.create_module("my_module");
     match Emitter::emit(&context, &builder, &module, node) {
         Ok(_) => (),
-        Err(e) => panic!(format!("{}", e)),
+        Err(e) => panic!(locate(e.to_string(), e.span(), source_map)),
     }
 }
 
@@ -176,4 +214,882 @@ mod tests {
         ";
         run_test(code, "3");
     }
+
+    #[test]
+    fn enumeration() {
+        let code = "
+        enum color { RED, GREEN = 5, BLUE };
+        int main() {
+            return RED + GREEN + BLUE;
+        }
+        ";
+        run_test(code, "11");
+    }
+
+    #[test]
+    fn enumeration_in_constant_expression() {
+        let code = "
+        enum { SIZE = 2 * 3, };
+        int main() {
+            int a[SIZE + 1];
+            enum local { DOUBLE = SIZE * 2 };
+            enum local b;
+            b = DOUBLE;
+            return b;
+        }
+        ";
+        run_test(code, "12");
+    }
+
+    #[test]
+    fn enumeration_scope() {
+        let code = "
+        enum { A = 1 };
+        int func() {
+            enum { A = 10 };
+            return A;
+        }
+        int main() {
+            return A + func();
+        }
+        ";
+        run_test(code, "11");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "<input>:2:16: unexpected token value of enumerator A out of range of int"
+    )]
+    fn enumerator_overflow() {
+        let code = "
+        enum { A = 0x7fffffffffffffff, B };
+        int main() {
+            return 0;
+        }
+        ";
+        compile(code.to_owned());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "<input>:2:16: unexpected token value of enumerator A out of range of int"
+    )]
+    fn enumerator_out_of_range() {
+        let code = "
+        enum { A = 0x100000000 };
+        int main() {
+            return 0;
+        }
+        ";
+        compile(code.to_owned());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "<input>:2:32: unexpected token value of enumerator B out of range of int"
+    )]
+    fn implicit_enumerator_out_of_range() {
+        let code = "
+        enum { A = 2147483647, B };
+        int main() {
+            return 0;
+        }
+        ";
+        compile(code.to_owned());
+    }
+
+    #[test]
+    fn typedef_name() {
+        let code = "
+        typedef int number;
+        number twice(number a) {
+            return a + a;
+        }
+        int main() {
+            typedef number counter;
+            counter c;
+            c = 3;
+            return twice(c);
+        }
+        ";
+        run_test(code, "6");
+    }
+
+    #[test]
+    fn typedef_name_as_pointer_declaration() {
+        let code = "
+        typedef int foo;
+        int main() {
+            foo * bar;
+            foo a;
+            a = 5;
+            return a;
+        }
+        ";
+        run_test(code, "5");
+    }
+
+    #[test]
+    fn typedef_name_hidden_by_variable() {
+        let code = "
+        typedef int foo;
+        int main() {
+            int foo;
+            foo = 4;
+            foo * 2;
+            return foo;
+        }
+        ";
+        run_test(code, "4");
+    }
+
+    #[test]
+    fn subtract_divide_remainder() {
+        let code = "
+        int main() {
+            return 50 - 20 - 5 + 17 / 3 % 4;
+        }
+        ";
+        run_test(code, "26");
+    }
+
+    #[test]
+    fn shift() {
+        let code = "
+        int main() {
+            return 1 << 4 + 1 >> 2;
+        }
+        ";
+        run_test(code, "8");
+    }
+
+    #[test]
+    fn signed_and_unsigned_division() {
+        let code = "
+        int main() {
+            int a;
+            unsigned b;
+            a = 0 - 8;
+            b = 0 - 8;
+            return a / 2 + b / 2 / 16777216;
+        }
+        ";
+        // -4 + 0x7ffffffc / 2^24
+        run_test(code, "123");
+    }
+
+    #[test]
+    fn signed_right_shift() {
+        let code = "
+        int main() {
+            long a;
+            a = 0 - 16;
+            return a >> 2;
+        }
+        ";
+        run_test(code, "252");
+    }
+
+    #[test]
+    fn usual_arithmetic_conversion() {
+        let code = "
+        int main() {
+            int a;
+            unsigned int b;
+            a = 0 - 1;
+            b = 1;
+            return a / b >> 28;
+        }
+        ";
+        run_test(code, "15");
+    }
+
+    #[test]
+    fn char_promotion() {
+        let code = "
+        int main() {
+            char c;
+            unsigned char u;
+            c = 200;
+            u = 200;
+            return c / 2 + u / 2;
+        }
+        ";
+        // -28 + 100
+        run_test(code, "72");
+    }
+
+    #[test]
+    fn short_conversion() {
+        let code = "
+        short half(short a) {
+            return a / 2;
+        }
+        int main() {
+            return half(65536 + 10);
+        }
+        ";
+        run_test(code, "5");
+    }
+
+    #[test]
+    fn bool_conversion() {
+        let code = "
+        int main() {
+            _Bool b;
+            b = 256;
+            return b + b;
+        }
+        ";
+        run_test(code, "2");
+    }
+
+    #[test]
+    fn double_arithmetic() {
+        let code = "
+        int main() {
+            double a;
+            a = 1.5;
+            return a * 4.0 + .5 - 2e-1;
+        }
+        ";
+        // 6.3 is truncated toward zero
+        run_test(code, "6");
+    }
+
+    #[test]
+    fn float_constant() {
+        let code = "
+        int main() {
+            float f;
+            f = 2.5f;
+            return f * 2 + 1e1f;
+        }
+        ";
+        run_test(code, "15");
+    }
+
+    #[test]
+    fn integer_and_floating_conversion() {
+        let code = "
+        int main() {
+            int i;
+            double a;
+            double b;
+            i = 7;
+            a = i / 2;
+            b = i / 2.0;
+            return a * 10 + b * 2;
+        }
+        ";
+        run_test(code, "37");
+    }
+
+    #[test]
+    fn double_function() {
+        let code = "
+        double average(double a, double b) {
+            return a / 2 + b / 2;
+        }
+        int main() {
+            return average(3, 6) * 2;
+        }
+        ";
+        run_test(code, "9");
+    }
+
+    #[test]
+    fn floating_to_bool() {
+        let code = "
+        int main() {
+            _Bool b;
+            b = 0.25;
+            return b;
+        }
+        ";
+        run_test(code, "1");
+    }
+
+    #[test]
+    fn declaration_with_initializer() {
+        let code = "
+        int main() {
+            int a = 5, b = a * 2, *c;
+            return a + b;
+        }
+        ";
+        run_test(code, "15");
+    }
+
+    #[test]
+    fn initializer_conversion() {
+        let code = "
+        int main() {
+            char c = 300;
+            double d = 2.5;
+            int i = d * 2;
+            return c + i;
+        }
+        ";
+        run_test(code, "49");
+    }
+
+    #[test]
+    fn initializer_list() {
+        let code = "
+        int main() {
+            int a[2][3] = { { 1, 2 }, 3, 4, };
+            double b[4] = { 1.5 };
+            int x = { 7 };
+            return x;
+        }
+        ";
+        run_test(code, "7");
+    }
+
+    #[test]
+    fn struct_initializer_list() {
+        let code = "
+        struct point { int x; int y; };
+        struct shape { char tag; struct point corner; unsigned flags : 3; double scale; };
+        static struct shape origin = { 1, { 2, 3 }, 5, 1.5 };
+        struct point points[2] = { 4, 5, { 6 } };
+        int main() {
+            struct shape s = { 7, 8 };
+            struct point local[2] = { { 1 }, 2, 3 };
+            return origin.tag + origin.corner.x + origin.corner.y + origin.flags
+                + origin.scale * 2 + s.tag + s.corner.x + s.corner.y + s.flags + s.scale;
+        }
+        ";
+        run_test(code, "29");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "<input>:4:26: unexpected token excess elements in initializer of struct point"
+    )]
+    fn excess_struct_initializers() {
+        let code = "
+        struct point { int x; int y; };
+        int main() {
+            struct point p = { 1, 2, 3 };
+            return p.x;
+        }
+        ";
+        compile(code.to_owned());
+    }
+
+    #[test]
+    fn function_prototype() {
+        let code = "
+        int twice(int);
+        int main() {
+            return twice(21);
+        }
+        int twice(int x) {
+            return x * 2;
+        }
+        ";
+        run_test(code, "42");
+    }
+
+    #[test]
+    fn static_local_variable() {
+        let code = "
+        int counter() {
+            static int count = 10;
+            count = count + 1;
+            return count;
+        }
+        int main() {
+            counter();
+            counter();
+            return counter();
+        }
+        ";
+        run_test(code, "13");
+    }
+
+    #[test]
+    fn file_scope_variable() {
+        let code = "
+        int total;
+        static double scale = 1.5;
+        int add(int x) {
+            total = total + x;
+            return total;
+        }
+        int main() {
+            extern int total;
+            add(4);
+            add(6);
+            return total * scale;
+        }
+        ";
+        run_test(code, "15");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "<input>:3:9: unexpected token static declaration of f follows non-static declaration"
+    )]
+    fn static_after_non_static_declaration() {
+        let code = "
+        int f(void);
+        static int f(void) {
+            return 1;
+        }
+        int main() {
+            return f();
+        }
+        ";
+        compile(code.to_owned());
+    }
+
+    #[test]
+    fn inline_function() {
+        let code = "
+        static inline int square(int x) {
+            return x * x;
+        }
+        inline int cube(int x) {
+            return x * square(x);
+        }
+        extern int cube(int);
+        inline int unused(int x) {
+            return x;
+        }
+        int main() {
+            return cube(3);
+        }
+        ";
+        run_test(code, "27");
+    }
+
+    #[test]
+    fn const_and_volatile() {
+        let code = "
+        volatile int flag = 3;
+        static const double ratio = 2.0;
+        int main() {
+            const int a = 4;
+            volatile int b = a;
+            const int * volatile p;
+            b = b + flag;
+            return b * ratio;
+        }
+        ";
+        run_test(code, "14");
+    }
+
+    #[test]
+    #[should_panic(expected = "<input>:4:13: cannot assign to a which is const-qualified")]
+    fn assignment_to_const() {
+        let code = "
+        int main() {
+            int const a = 1;
+            a = 2;
+            return a;
+        }
+        ";
+        compile(code.to_owned());
+    }
+
+    #[test]
+    fn parenthesized_lvalue() {
+        let code = "
+        struct point { int x; int y; };
+        int main() {
+            struct point s;
+            int a;
+            (s.x) = 1;
+            ((a)) = 2;
+            (s).y = 3;
+            return s.x + a + s.y;
+        }
+        ";
+        run_test(code, "6");
+    }
+
+    #[test]
+    #[should_panic(expected = "<input>:6:13: unexpected token expression is not an lvalue")]
+    fn assignment_to_rvalue() {
+        let code = "
+        int f() {
+            return 1;
+        }
+        int main() {
+            f() = 2;
+            return 0;
+        }
+        ";
+        compile(code.to_owned());
+    }
+
+    #[test]
+    fn volatile_pointer() {
+        let code = "
+        int main() {
+            int x = 1;
+            volatile int *reg = &x;
+            *reg = *reg + 41;
+            return x;
+        }
+        ";
+        run_test(code, "42");
+        let ir = std::fs::read_to_string("compiled.ll").unwrap();
+        assert!(ir.contains("load volatile"));
+        assert!(ir.contains("store volatile"));
+    }
+
+    #[test]
+    fn address_and_indirection() {
+        let code = "
+        struct point { int x; int y; };
+        int main() {
+            struct point p;
+            struct point *pp = &p;
+            int *q = &p.y;
+            int * const *qq = &q;
+            (*pp).x = 3;
+            **qq = 4;
+            return p.x * 10 + *&p.y;
+        }
+        ";
+        run_test(code, "34");
+    }
+
+    #[test]
+    #[should_panic(expected = "<input>:5:14: cannot assign to *p which is const-qualified")]
+    fn assignment_through_pointer_to_const() {
+        let code = "
+        int main() {
+            int a = 1;
+            const int *p = &a;
+            *p = 2;
+            return a;
+        }
+        ";
+        compile(code.to_owned());
+    }
+
+    #[test]
+    fn call_through_function_pointer() {
+        let code = "
+        int add(int a, int b) {
+            return a + b;
+        }
+        int mul(int a, int b) {
+            return a * b;
+        }
+        int apply(int (*op)(int, int), int a, int b) {
+            return op(a, b);
+        }
+        int main() {
+            int (*op)(int, int) = add;
+            int x = apply(op, 2, 3);
+            op = &mul;
+            return x + (*op)(x, 2);
+        }
+        ";
+        run_test(code, "15");
+    }
+
+    #[test]
+    fn callback_table() {
+        let code = "
+        int inc(int x) {
+            return x + 1;
+        }
+        int twice(int x) {
+            return x * 2;
+        }
+        typedef int (*callback)(int);
+        static callback table[2] = { inc, &twice };
+        static callback handler = twice;
+        int main() {
+            callback local[2] = { &inc, twice };
+            return handler(inc(4));
+        }
+        ";
+        run_test(code, "10");
+    }
+
+    #[test]
+    fn integer_constants() {
+        let code = "
+        int main() {
+            unsigned long mask = 0xFFul;
+            long shift = 0b101L;
+            return (mask >> shift) + 017 - 10u;
+        }
+        ";
+        run_test(code, "12");
+    }
+
+    #[test]
+    #[should_panic(expected = "too large")]
+    fn integer_constant_out_of_range() {
+        let code = "
+        int main() {
+            return 18446744073709551616;
+        }
+        ";
+        compile(code.to_owned());
+    }
+
+    #[test]
+    fn comments() {
+        let code = "
+        // returns the sum
+        int main() {
+            int a = 1; /* the first
+                          operand */
+            return a + /* inline */ 2; // 3
+        }
+        ";
+        run_test(code, "3");
+    }
+
+    #[test]
+    fn whitespace_and_line_splicing() {
+        let code = "int main() {\r\n\tint ans\\\r\nwer = 4 +\x0c2;\r\n\treturn answer;\r\n}\r\n";
+        run_test(code, "6");
+    }
+
+    #[test]
+    fn variadic_function() {
+        let code = "
+        double sum(int n, ...) {
+            va_list ap;
+            double total = 0;
+            va_start(ap, n);
+            total = va_arg(ap, int) + va_arg(ap, double);
+            total = total + va_arg(ap, long);
+            va_end(ap);
+            return total;
+        }
+        int main() {
+            float half = 0.5f;
+            double (*f)(int, ...) = sum;
+            return f(3, 4, half, 10l) * 2;
+        }
+        ";
+        run_test(code, "29");
+    }
+
+    #[test]
+    fn struct_bit_fields() {
+        let code = "
+        struct status {
+            unsigned ready : 1, mode : 3;
+            int : 0;
+            int level : 4;
+            char tag;
+        };
+        struct status global;
+        int main() {
+            struct status s;
+            s.ready = 1;
+            s.mode = 13;
+            s.level = -3;
+            s.tag = 7;
+            global.mode = s.mode;
+            return s.ready + s.mode + s.level + s.tag + global.mode;
+        }
+        ";
+        run_test(code, "15");
+    }
+
+    #[test]
+    fn sizeof_and_static_assert() {
+        let code = "
+        _Static_assert(sizeof(long) / 8, \"long is 8 bytes\");
+        enum { SIZE = sizeof(int) * 2 - -1 };
+        struct pair { char tag; long value; };
+        int main() {
+            struct pair p;
+            _Static_assert(sizeof p - 16 + 1, \"pair is \" \"padded\");
+            return SIZE + sizeof(p.tag) + +sizeof(char *) - -3;
+        }
+        ";
+        run_test(code, "21");
+    }
+
+    #[test]
+    fn include_header() {
+        let directory = std::env::temp_dir().join("rcc_include_header");
+        std::fs::create_dir_all(directory.join("include")).unwrap();
+        std::fs::write(
+            directory.join("main.c"),
+            "#include \"add.h\"\n#include <twice.h>\nint main() {\n    return twice(add(1, 2));\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            directory.join("add.h"),
+            "int add(int a, int b);\nint add(int a, int b) {\n    return a + b;\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            directory.join("include/twice.h"),
+            "int twice(int a) {\n    return a * 2;\n}\n",
+        )
+        .unwrap();
+        let preprocessor = Preprocessor::new(vec![directory.join("include")]);
+        compile_file(&directory.join("main.c"), preprocessor);
+        assert_eq!(run(), "exit code: 6");
+    }
+
+    #[test]
+    fn alignment() {
+        let code = "
+        struct line { _Alignas(64) char bytes[8]; int count; };
+        _Alignas(64) char buffer[100];
+        _Static_assert(sizeof(struct line) / 64, \"line is padded to its alignment\");
+        int main() {
+            _Alignas(struct line) static int counter;
+            _Alignas(16) _Alignas(0) long local;
+            struct line l;
+            l.count = _Alignof(struct line) + _Alignof(long);
+            local = sizeof(struct line);
+            return l.count - local - _Alignof(char);
+        }
+        ";
+        run_test(code, "7");
+    }
+
+    #[test]
+    #[should_panic(expected = "<input>:3:33: unexpected token alignment 2 of value less strict")]
+    fn weaker_alignment() {
+        let code = "
+        int main() {
+            _Alignas(short) int value;
+            return 0;
+        }
+        ";
+        compile(code.to_owned());
+    }
+
+    #[test]
+    #[should_panic(expected = "<input>:2:9: static assertion failed: int is 8 bytes")]
+    fn failed_static_assert() {
+        let code = "
+        _Static_assert(sizeof(int) / 8, \"int is 8 bytes\");
+        int main() {
+            return 0;
+        }
+        ";
+        compile(code.to_owned());
+    }
+
+    #[test]
+    fn macros() {
+        let code = "
+        #define SQUARE(x) ((x) * (x))
+        #define CALL(f, ...) f(__VA_ARGS__)
+        #define DECLARE(type, name) type name ## _value
+        #define ASSERT(e) _Static_assert(e, #e)
+        #define FIVE 5
+        ASSERT(sizeof(long) / 8);
+        int add(int a, int b) {
+            return a + b;
+        }
+        int main() {
+            DECLARE(int, answer) = CALL(add, SQUARE(2 + 1), 1);
+            return answer_value + FIVE;
+        }
+        ";
+        run_test(code, "15");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "at line 3 location 18 (in expansion of macro ASSIGN defined at line 1 location 16)"
+    )]
+    fn error_in_macro() {
+        let code = "
+        #define ASSIGN = =
+        int main() {
+            int a ASSIGN 1;
+            return a;
+        }
+        ";
+        compile(code.to_owned());
+    }
+
+    #[test]
+    fn conditional_compilation() {
+        let code = "
+        #define VERSION 3
+        #if VERSION >= 2 && !defined(LEGACY)
+        int feature() {
+            return VERSION;
+        }
+        #else
+        int feature() {
+            return 0;
+        }
+        #endif
+        int main() {
+        #ifdef LEGACY
+            return 1;
+        #elif VERSION == 3
+            return feature() * 2;
+        #endif
+        }
+        ";
+        run_test(code, "6");
+    }
+
+    #[test]
+    fn character_constants() {
+        let code = "
+        #if 'a' == 97 && -1 > 0u
+        int main() {
+            return 'a' - 'A' + '\\n';
+        }
+        #endif
+        ";
+        run_test(code, "42");
+    }
+
+    #[test]
+    fn predefined_macros() {
+        let code = "
+        #if defined(__x86_64__) && __SIZEOF_LONG__ == 8 && __STDC_VERSION__ >= 201112L
+        int main() {
+            return __LINE__ + __STDC__;
+        }
+        #endif
+        ";
+        run_test(code, "5");
+    }
+
+    #[test]
+    #[should_panic(expected = "#error pointers are not 4 bytes at line 3 of <input>")]
+    fn error_directive() {
+        let code = "
+        #if __SIZEOF_POINTER__ != 4
+        #error pointers are not 4 bytes
+        #endif
+        int main() {
+            return 0;
+        }
+        ";
+        compile(code.to_owned());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "unexpected char '@' at line 2 location 22, unexpected char '$' at line 3 location 21"
+    )]
+    fn lexical_errors() {
+        let code = "
+        int main() {
+            int a = 1 @ 2;
+            return a $ 1;
+        }
+        ";
+        compile(code.to_owned());
+    }
 }
@@ -1,7 +1,89 @@
 extern crate rcc;
+extern crate rcc_preprocessor;
 
+use rcc_preprocessor::output::write_preprocessed;
+use rcc_preprocessor::Preprocessor;
+use std::env;
+use std::path::PathBuf;
+use std::process;
+
+/// macro given on the command line, which are applied in order
+enum MacroOption {
+    Define(String),   // -D name or -D name=replacement
+    Undefine(String), // -U name
+}
+
+/// rcc [-E] [-I directory]... [-D name[=replacement]]... [-U name]... [-Wunknown-pragmas] [file]
+///
+/// compiles the demo code unless a file is given, and `-E` prints the preprocessed file instead
 fn main() {
-    let code = "
+    let mut include_paths = vec![];
+    let mut macros = vec![];
+    let mut warns_unknown_pragmas = false;
+    let mut preprocesses_only = false;
+    let mut file = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        // options are followed by their values with or without a space
+        let mut value = |option: &str| match arg.strip_prefix(option) {
+            Some("") => Some(args.next().unwrap_or_else(|| usage())),
+            Some(value) => Some(value.to_owned()),
+            None => None,
+        };
+        if let Some(directory) = value("-I") {
+            include_paths.push(PathBuf::from(directory));
+        } else if let Some(definition) = value("-D") {
+            macros.push(MacroOption::Define(definition));
+        } else if let Some(name) = value("-U") {
+            macros.push(MacroOption::Undefine(name));
+        } else if arg == "-E" {
+            preprocesses_only = true;
+        } else if arg == "-Wunknown-pragmas" {
+            warns_unknown_pragmas = true;
+        } else if file.is_none() && !arg.starts_with('-') {
+            file = Some(PathBuf::from(arg));
+        } else {
+            usage();
+        }
+    }
+
+    match file {
+        Some(file) => {
+            let mut preprocessor = Preprocessor::new(include_paths);
+            preprocessor.warn_unknown_pragmas(warns_unknown_pragmas);
+            for option in macros {
+                let result = match option {
+                    MacroOption::Define(definition) => preprocessor.define(&definition),
+                    MacroOption::Undefine(name) => preprocessor.undefine(&name),
+                };
+                if let Err(err) = result {
+                    eprintln!("{}", err);
+                    process::exit(1);
+                }
+            }
+            if preprocesses_only {
+                match preprocessor.preprocess_file(&file) {
+                    Ok(tokens) => {
+                        print!(
+                            "{}",
+                            write_preprocessed(&tokens, preprocessor.line_markers())
+                        );
+                        for warning in preprocessor.warnings() {
+                            eprintln!("{}", warning);
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        process::exit(1);
+                    }
+                }
+                return;
+            }
+            rcc::compile_file(&file, preprocessor)
+        }
+        None if preprocesses_only => usage(),
+        None => {
+            let code = "
 int func(int a, int b) {
     return a + b;
 }
@@ -9,7 +91,17 @@ int main() {
     int a[3];
     return func(1, 2);
 }"
-    .to_owned();
-    rcc::compile(code);
+            .to_owned();
+            rcc::compile(code);
+        }
+    }
     println!("{:?}", rcc::run());
 }
+
+fn usage() -> ! {
+    eprintln!(
+        "usage: rcc [-E] [-I directory]... [-D name[=replacement]]... [-U name]... \
+         [-Wunknown-pragmas] [file]"
+    );
+    process::exit(1);
+}
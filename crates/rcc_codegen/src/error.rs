@@ -1,27 +1,102 @@
 use inkwell::support::LLVMString;
+use rcc_parser::error::ParseError;
+use rcc_syntax::span::Span;
 use rcc_syntax::token::TokenError;
 use std::error::Error;
 use std::fmt;
 
+/// error found while emitting a program, with the span of the source code where it is found
+///
+/// an error found where no span is at hand (e.g. in resolving a type) has an empty span, which is
+/// filled by `or_span` with the span of the enclosing expression, statement or declaration
 #[derive(Debug)]
 pub enum CompileError {
-    Emit(TokenError),
+    Emit(TokenError, Span),
     LLVM(LLVMString),
-    Undeclared(String),
-    Unexpect(String),
-    NotFound(String),
+    Parse(ParseError, Span),
+    Undeclared(String, Span),
+    NotAssignable(String, Span),
+    ConstAssignment(String, Span),
+    Unexpect(String, Span),
+    NotFound(String, Span),
+    StaticAssertion(String, Span), // (message, _Static_assert declaration)
+}
+
+impl CompileError {
+    /// error of an unexpected construct whose span is filled by `or_span`
+    pub fn unexpect(cause: String) -> CompileError {
+        CompileError::Unexpect(cause, Span::default())
+    }
+
+    /// span of the source code where the error is found, which is shown with the source map
+    pub fn span(&self) -> Option<Span> {
+        let span = match self {
+            CompileError::LLVM(_) => return None,
+            CompileError::Parse(err, span) => return err.span().or_else(|| located(*span)),
+            CompileError::Emit(_, span)
+            | CompileError::Undeclared(_, span)
+            | CompileError::NotAssignable(_, span)
+            | CompileError::ConstAssignment(_, span)
+            | CompileError::Unexpect(_, span)
+            | CompileError::NotFound(_, span)
+            | CompileError::StaticAssertion(_, span) => *span,
+        };
+        located(span)
+    }
+
+    /// locate an error at a span (of the construct containing it) unless it already has one
+    pub fn or_span(mut self, outer: Span) -> CompileError {
+        match &mut self {
+            CompileError::LLVM(_) => (),
+            CompileError::Emit(_, span)
+            | CompileError::Parse(_, span)
+            | CompileError::Undeclared(_, span)
+            | CompileError::NotAssignable(_, span)
+            | CompileError::ConstAssignment(_, span)
+            | CompileError::Unexpect(_, span)
+            | CompileError::NotFound(_, span)
+            | CompileError::StaticAssertion(_, span) => {
+                if span.is_empty() {
+                    *span = outer;
+                }
+            }
+        }
+        self
+    }
+}
+
+fn located(span: Span) -> Option<Span> {
+    if span.is_empty() {
+        None
+    } else {
+        Some(span)
+    }
 }
 
 impl fmt::Display for CompileError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            CompileError::Emit(err) => write!(f, "{}", err.to_string()),
+            CompileError::Emit(err, _) => write!(f, "{}", err.to_string()),
             CompileError::LLVM(err) => write!(f, "{}", err.to_string()),
-            CompileError::Undeclared(identifier) => {
+            CompileError::Parse(err, _) => write!(f, "{}", err.to_string()),
+            CompileError::Undeclared(identifier, _) => {
                 write!(f, "undeclared identifier {}", identifier)
             }
-            CompileError::Unexpect(token) => write!(f, "unexpected token {}", token),
-            CompileError::NotFound(cause) => write!(f, "{} not found", cause),
+            CompileError::NotAssignable(identifier, _) => {
+                write!(f, "{} is not assignable", identifier)
+            }
+            CompileError::ConstAssignment(identifier, _) => {
+                write!(
+                    f,
+                    "cannot assign to {} which is const-qualified",
+                    identifier
+                )
+            }
+            CompileError::Unexpect(token, _) => write!(f, "unexpected token {}", token),
+            CompileError::NotFound(cause, _) => write!(f, "{} not found", cause),
+            CompileError::StaticAssertion(message, _) => {
+                write!(f, "static assertion failed: {}", message)
+            }
         }
     }
 }
@@ -29,18 +104,28 @@ impl fmt::Display for CompileError {
 impl Error for CompileError {
     fn description(&self) -> &str {
         match *self {
-            CompileError::Emit(_) => "compile error",
+            CompileError::Emit(..) => "compile error",
             CompileError::LLVM(_) => "llvm error",
-            CompileError::Undeclared(_) => "undeclared",
-            CompileError::Unexpect(_) => "unexpected",
-            CompileError::NotFound(_) => "notfound",
+            CompileError::Parse(..) => "parse error",
+            CompileError::Undeclared(..) => "undeclared",
+            CompileError::NotAssignable(..) => "not assignable",
+            CompileError::ConstAssignment(..) => "assignment to const",
+            CompileError::Unexpect(..) => "unexpected",
+            CompileError::NotFound(..) => "notfound",
+            CompileError::StaticAssertion(..) => "static assertion failed",
         }
     }
 }
 
 impl From<TokenError> for CompileError {
     fn from(err: TokenError) -> CompileError {
-        CompileError::Emit(err)
+        CompileError::Emit(err, Span::default())
+    }
+}
+
+impl From<ParseError> for CompileError {
+    fn from(err: ParseError) -> CompileError {
+        CompileError::Parse(err, Span::default())
     }
 }
 
@@ -3,34 +3,368 @@ mod error;
 use error::CompileError;
 use inkwell::builder::Builder;
 use inkwell::context::Context;
-use inkwell::module::Module;
-use inkwell::types::BasicTypeEnum;
-use inkwell::values::{BasicValueEnum, IntValue, PointerValue};
-use rcc_parser::expression::{Exp12, Exp13, Exp16, Exp2, Expression};
-use rcc_parser::function::Function;
-use rcc_parser::program::Program;
-use rcc_parser::statement::{DeclareStatement, ExpressionStatement, ReturnStatement, Statement};
+use inkwell::module::{Linkage, Module};
+use inkwell::types::{
+    ArrayType, BasicType, BasicTypeEnum, FloatType, FunctionType, IntType, StructType,
+};
+use inkwell::values::{
+    BasicValueEnum, CallableValue, FunctionValue, GlobalValue, IntValue, PointerValue,
+};
+use inkwell::{AddressSpace, FloatPredicate, IntPredicate};
+use rcc_parser::constant::{evaluate, ConstantScope};
+use rcc_parser::enumeration::EnumDeclaration;
+use rcc_parser::expression::{Exp11, Exp12, Exp13, Exp15, Exp16, Exp2, Expression};
+use rcc_parser::function::{Function, FunctionDeclaration};
+use rcc_parser::program::{ExternalDeclaration, Program};
+use rcc_parser::statement::{
+    Declarator, DeclareStatement, ExpressionStatement, Initializer, ReturnStatement, Statement,
+    TypeStruct,
+};
+use rcc_parser::static_assert::StaticAssertDeclaration;
+use rcc_parser::storage::{Alignment, StorageClass};
+use rcc_parser::structure::StructDeclaration;
+use rcc_parser::types::{FloatingType, IntegerKind, IntegerType, Qualifiers, Type};
+use rcc_syntax::span::Span;
+use rcc_syntax::symbol::Symbol;
 use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::iter::Peekable;
 use std::path;
+use std::vec;
 
 type Result<T> = std::result::Result<T, CompileError>;
 
+/// what an ordinary identifier denotes
+#[derive(Debug, Clone)]
+enum Entity<'ctx> {
+    Variable(PointerValue<'ctx>, Type, Qualifiers),
+    Constant(i64),
+    Function(FunctionValue<'ctx>, Type, Vec<Type>, bool), // (function, return type, parameter types, is variadic)
+}
+
+/// layout of a member in its structure
+#[derive(Debug, Clone)]
+struct MemberLayout {
+    ty: Type,
+    qualifiers: Qualifiers,
+    offset: u64,                   // in bytes (of the storage unit for a bit-field)
+    bit_field: Option<(u32, u32)>, // (bit offset in the storage unit, width)
+}
+
+/// layout of a structure in x86-64 System V ABI
+///
+/// members are in declaration order, which is the order of initializers
+#[derive(Debug, Clone)]
+struct StructLayout {
+    members: Vec<(Symbol, MemberLayout)>,
+    size: u64,
+    align: u64,
+}
+impl StructLayout {
+    fn member(&self, identifier: Symbol) -> Option<&MemberLayout> {
+        self.members
+            .iter()
+            .find(|(member, _)| *member == identifier)
+            .map(|(_, layout)| layout)
+    }
+}
+
+/// object designated by an lvalue
+#[derive(Debug, Clone)]
+enum Location<'ctx> {
+    Object(PointerValue<'ctx>),
+    BitField(PointerValue<'ctx>, u32, u32), // (pointer to the storage unit, bit offset, width)
+}
+
+/// typed rvalue of an expression (of an arithmetic type or a pointer type)
+#[derive(Debug, Clone)]
+struct Value<'ctx> {
+    value: BasicValueEnum<'ctx>,
+    ty: Type,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Arithmetic {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+/// scoped symbol table (the first scope is the file scope)
+///
+/// struct tags are in their own name space apart from ordinary identifiers
 #[derive(Debug)]
 struct Environment<'ctx> {
-    variables: HashMap<String, PointerValue<'ctx>>,
+    scopes: Vec<HashMap<Symbol, Entity<'ctx>>>,
+    tags: Vec<HashMap<Symbol, StructLayout>>,
 }
 impl<'ctx> Environment<'ctx> {
     fn new() -> Environment<'ctx> {
         Environment {
-            variables: HashMap::new(),
+            scopes: vec![HashMap::new()],
+            tags: vec![HashMap::new()],
+        }
+    }
+    fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+        self.tags.push(HashMap::new());
+    }
+    fn pop_scope(&mut self) {
+        self.scopes.pop();
+        self.tags.pop();
+    }
+    fn insert(
+        &mut self,
+        identifier: Symbol,
+        pointer: PointerValue<'ctx>,
+        ty: Type,
+        qualifiers: Qualifiers,
+    ) {
+        self.insert_entity(identifier, Entity::Variable(pointer, ty, qualifiers));
+    }
+    fn insert_constant(&mut self, identifier: Symbol, value: i64) {
+        self.insert_entity(identifier, Entity::Constant(value));
+    }
+    fn insert_function(
+        &mut self,
+        identifier: Symbol,
+        function: FunctionValue<'ctx>,
+        return_type: Type,
+        parameter_types: Vec<Type>,
+        is_variadic: bool,
+    ) {
+        self.insert_entity(
+            identifier,
+            Entity::Function(function, return_type, parameter_types, is_variadic),
+        );
+    }
+    fn insert_entity(&mut self, identifier: Symbol, entity: Entity<'ctx>) {
+        self.scopes
+            .last_mut()
+            .expect("environment has no scope")
+            .insert(identifier, entity);
+    }
+    fn get(&self, identifier: Symbol) -> Option<&Entity<'ctx>> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&identifier))
+    }
+    fn insert_struct(&mut self, tag: Symbol, layout: StructLayout) {
+        self.tags
+            .last_mut()
+            .expect("environment has no scope")
+            .insert(tag, layout);
+    }
+    fn get_struct(&self, tag: Symbol) -> Option<&StructLayout> {
+        self.tags.iter().rev().find_map(|scope| scope.get(&tag))
+    }
+    /// get the layout of a structure type which is complete
+    fn layout_of(&self, ty: &Type) -> Result<&StructLayout> {
+        match ty {
+            Type::Struct(tag) => self
+                .get_struct(*tag)
+                .ok_or_else(|| CompileError::unexpect(format!("layout of incomplete type {}", ty))),
+            _ => unreachable!("layout of {}", ty),
+        }
+    }
+
+    /// resolve array sizes and get the declared type
+    fn resolve_type(&self, type_struct: &TypeStruct) -> Result<Type> {
+        let mut ty = type_struct.get_unsized_type();
+        for size in type_struct.get_post().iter().rev() {
+            let size = evaluate(size, self)?;
+            if size <= 0 {
+                return Err(CompileError::unexpect(format!(
+                    "array size {} of {}",
+                    size,
+                    type_struct.get_identifier()
+                )));
+            }
+            ty = Type::Array(Box::new(ty), size as u64);
+        }
+        Ok(ty)
+    }
+
+    /// get the size and the alignment in bytes of an object type on x86-64
+    fn size_align(&self, ty: &Type) -> Result<(u64, u64)> {
+        Ok(match ty {
+            Type::Integer(integer_type) => (integer_type.size() as u64, integer_type.size() as u64),
+            Type::Floating(floating_type) => {
+                (floating_type.size() as u64, floating_type.size() as u64)
+            }
+            Type::Pointer(..) => (8, 8),
+            Type::Array(ty, size) => {
+                let (element_size, align) = self.size_align(ty)?;
+                (element_size * size, align)
+            }
+            Type::VaList => (24, 8),
+            Type::Struct(tag) => match self.get_struct(*tag) {
+                Some(layout) => (layout.size, layout.align),
+                None => {
+                    return Err(CompileError::unexpect(format!(
+                        "size of incomplete type {}",
+                        ty
+                    )))
+                }
+            },
+            Type::Function(..) => {
+                return Err(CompileError::unexpect(format!(
+                    "size of function type {}",
+                    ty
+                )))
+            }
+        })
+    }
+
+    /// get the alignment of an object from its type and alignment specifiers
+    ///
+    /// the strictest alignment applies and `_Alignas(0)` has no effect. an alignment specifier
+    /// cannot make the alignment less strict than the one of the type.
+    fn resolve_alignment(
+        &self,
+        ty: &Type,
+        alignments: &[Alignment],
+        identifier: Symbol,
+    ) -> Result<u64> {
+        let (_, natural) = self.size_align(ty)?;
+        let mut align = natural;
+        for alignment in alignments {
+            let value = match alignment {
+                Alignment::Type(type_name) => self.size_align(&self.resolve_type(type_name)?)?.1,
+                Alignment::Expression(expression) => {
+                    let value = evaluate(expression, self)?;
+                    if value < 0 || value & (value - 1) != 0 || value > u32::MAX as i64 {
+                        return Err(CompileError::unexpect(format!(
+                            "alignment {} of {}",
+                            value, identifier
+                        )));
+                    }
+                    value as u64
+                }
+            };
+            if value != 0 && value < natural {
+                return Err(CompileError::unexpect(format!(
+                    "alignment {} of {} less strict than {} of type {}",
+                    value, identifier, natural, ty
+                )));
+            }
+            align = align.max(value);
+        }
+        Ok(align)
+    }
+
+    /// get the type of an expression without evaluating it
+    ///
+    /// a function designator and an array are not converted to pointers, as the operand of
+    /// sizeof. None if the expression is invalid or has no type.
+    fn type_of(&self, node: &Exp2) -> Option<Type> {
+        match node {
+            Exp2::Single(exp) => self.type_of_exp11(exp),
+            Exp2::Eq(operands) => self.type_of_exp11(operands.first()?),
+        }
+    }
+    fn type_of_exp11(&self, node: &Exp11) -> Option<Type> {
+        match node {
+            Exp11::Single(exp) => self.type_of_exp12(exp),
+            Exp11::Shl(lhs, _) | Exp11::Shr(lhs, _) => match self.type_of_exp11(lhs)? {
+                Type::Integer(integer_type) => Some(Type::Integer(integer_type.promote())),
+                _ => None,
+            },
+        }
+    }
+    fn type_of_exp12(&self, node: &Exp12) -> Option<Type> {
+        match node {
+            Exp12::Single(exp) => self.type_of_exp13(exp),
+            Exp12::Add(lhs, rhs) | Exp12::Sub(lhs, rhs) => {
+                Type::common(&self.type_of_exp12(lhs)?, &self.type_of_exp13(rhs)?)
+            }
         }
     }
-    fn insert(&mut self, identifier: String, pointer: PointerValue<'ctx>) {
-        self.variables.insert(identifier, pointer);
+    fn type_of_exp13(&self, node: &Exp13) -> Option<Type> {
+        match node {
+            Exp13::Single(exp) => self.type_of_exp15(exp),
+            Exp13::Mul(lhs, rhs) | Exp13::Div(lhs, rhs) | Exp13::Rem(lhs, rhs) => {
+                Type::common(&self.type_of_exp13(lhs)?, &self.type_of_exp15(rhs)?)
+            }
+        }
     }
-    fn get(&self, identifier: &String) -> Option<PointerValue> {
-        let mut cloned_variables = self.variables.clone();
-        cloned_variables.remove(identifier)
+    fn type_of_exp15(&self, node: &Exp15) -> Option<Type> {
+        match node {
+            Exp15::Single(exp) => self.type_of_exp16(exp),
+            // the qualifiers of the operand change neither the size nor the alignment of the pointer
+            Exp15::Address(exp) => Some(Type::Pointer(
+                Box::new(self.type_of_exp15(exp)?),
+                Qualifiers::default(),
+            )),
+            Exp15::Indirection(exp) => match self.type_of_exp15(exp)? {
+                Type::Pointer(ty, _) => Some(*ty),
+                ty @ Type::Function(..) => Some(ty),
+                _ => None,
+            },
+            Exp15::Plus(exp) | Exp15::Minus(exp) => match self.type_of_exp15(exp)? {
+                Type::Integer(integer_type) => Some(Type::Integer(integer_type.promote())),
+                ty @ Type::Floating(_) => Some(ty),
+                _ => None,
+            },
+            Exp15::Sizeof(_) | Exp15::SizeofType(_) | Exp15::AlignofType(_) => {
+                Some(Type::Integer(size_type()))
+            }
+        }
+    }
+    fn type_of_exp16(&self, node: &Exp16) -> Option<Type> {
+        match node {
+            Exp16::Number(_, ty) => Some(Type::Integer(*ty)),
+            Exp16::Floating(_, ty) => Some(Type::Floating(*ty)),
+            Exp16::Identifier(identifier, _) => match self.get(*identifier)? {
+                Entity::Variable(_, ty, _) => Some(ty.clone()),
+                Entity::Constant(_) => Some(Type::int()),
+                Entity::Function(_, return_type, parameter_types, is_variadic) => {
+                    Some(Type::Function(
+                        Box::new(return_type.clone()),
+                        parameter_types.clone(),
+                        *is_variadic,
+                    ))
+                }
+            },
+            Exp16::Parenthesis(expression) => self.type_of(&expression.expression),
+            Exp16::FunctionCall(function, _) => match self.type_of_exp16(function)? {
+                Type::Function(return_type, ..) => Some(*return_type),
+                Type::Pointer(ty, _) => match *ty {
+                    Type::Function(return_type, ..) => Some(*return_type),
+                    _ => None,
+                },
+                _ => None,
+            },
+            Exp16::Member(exp, member) => match self.type_of_exp16(exp)? {
+                Type::Struct(tag) => Some(self.get_struct(tag)?.member(*member)?.ty.clone()),
+                _ => None,
+            },
+            Exp16::VaArg(_, type_name) => self.resolve_type(type_name).ok(),
+            Exp16::VaStart(_) | Exp16::VaEnd(_) => None,
+        }
+    }
+}
+impl<'ctx> ConstantScope for Environment<'ctx> {
+    fn get_constant(&self, identifier: Symbol) -> Option<i64> {
+        match self.get(identifier) {
+            Some(Entity::Constant(value)) => Some(*value),
+            _ => None,
+        }
+    }
+    fn size_of_type(&self, type_name: &TypeStruct) -> Option<u64> {
+        let ty = self.resolve_type(type_name).ok()?;
+        self.size_align(&ty).ok().map(|(size, _)| size)
+    }
+    fn size_of_expression(&self, exp: &Exp15) -> Option<u64> {
+        let ty = self.type_of_exp15(exp)?;
+        self.size_align(&ty).ok().map(|(size, _)| size)
+    }
+    fn align_of_type(&self, type_name: &TypeStruct) -> Option<u64> {
+        let ty = self.resolve_type(type_name).ok()?;
+        self.size_align(&ty).ok().map(|(_, align)| align)
     }
 }
 
@@ -56,212 +390,2160 @@ impl<'a, 'ctx> Emitter<'a, 'ctx> {
     }
 
     fn emit_program(&self, program: Program) -> Result<()> {
-        for function in program.functions {
-            self.emit_function(function)?;
+        let mut environment = Environment::new();
+        let linkages = Emitter::function_linkages(&program)?;
+        for declaration in program.declarations {
+            let span = declaration.span();
+            self.emit_external_declaration(declaration, &linkages, &mut environment)
+                .map_err(|err| err.or_span(span))?;
         }
         return Ok(());
     }
 
-    fn emit_function(&self, function: Function) -> Result<()> {
-        let identifier = function.identifier.get_token().get_identifier()?;
-        let _return_type = function.return_type;
-        let arguments = function.arguments;
+    fn emit_external_declaration(
+        &self,
+        declaration: ExternalDeclaration,
+        linkages: &HashMap<Symbol, Linkage>,
+        environment: &mut Environment<'ctx>,
+    ) -> Result<()> {
+        match declaration {
+            ExternalDeclaration::Function(function) => {
+                let linkage = linkages[&function
+                    .declaration
+                    .identifier
+                    .get_token()
+                    .get_identifier()?];
+                self.emit_function(function, linkage, environment)
+            }
+            ExternalDeclaration::FunctionDeclaration(declaration) => {
+                self.declare_function(&declaration, environment)?;
+                Ok(())
+            }
+            ExternalDeclaration::Declare(statement) => {
+                self.emit_external_declare_statement(statement, environment)
+            }
+            ExternalDeclaration::Enum(enum_declaration) => {
+                self.emit_enum_declaration(enum_declaration, environment)
+            }
+            ExternalDeclaration::Struct(struct_declaration) => {
+                self.emit_struct_declaration(struct_declaration, environment)
+            }
+            // typedef names are resolved by the parser
+            ExternalDeclaration::Typedef(_) => Ok(()),
+            ExternalDeclaration::StaticAssert(static_assert) => {
+                self.emit_static_assert(static_assert, environment)
+            }
+        }
+    }
 
-        let i64_type = self.context.i64_type();
+    /// get the linkage of each function from its file scope declarations
+    ///
+    /// a function first declared static has internal linkage. a definition whose declarations
+    /// are all inline without extern is an inline definition (C99 semantics), which is emitted as
+    /// available_externally since the external definition is in another translation unit. a
+    /// function declared static after a declaration without static is an error.
+    fn function_linkages(program: &Program) -> Result<HashMap<Symbol, Linkage>> {
+        let mut declarations: HashMap<Symbol, Vec<&FunctionDeclaration>> = HashMap::new();
+        for declaration in program.declarations.iter() {
+            let declaration = match declaration {
+                ExternalDeclaration::Function(function) => &function.declaration,
+                ExternalDeclaration::FunctionDeclaration(declaration) => declaration,
+                _ => continue,
+            };
+            if let Ok(identifier) = declaration.identifier.get_token().get_identifier() {
+                declarations
+                    .entry(identifier)
+                    .or_default()
+                    .push(declaration);
+            }
+        }
+        let is_static = |declaration: &FunctionDeclaration| {
+            declaration.storage_class == Some(StorageClass::Static)
+        };
+        let mut linkages = HashMap::new();
+        for (identifier, declarations) in declarations {
+            let linkage = if is_static(declarations[0]) {
+                Linkage::Internal
+            } else if let Some(declaration) = declarations
+                .iter()
+                .find(|declaration| is_static(declaration))
+            {
+                return Err(CompileError::Unexpect(
+                    format!(
+                        "static declaration of {} follows non-static declaration",
+                        identifier
+                    ),
+                    declaration.span,
+                ));
+            } else if declarations
+                .iter()
+                .all(|declaration| declaration.is_inline && declaration.storage_class.is_none())
+            {
+                Linkage::AvailableExternally
+            } else {
+                Linkage::External
+            };
+            linkages.insert(identifier, linkage);
+        }
+        Ok(linkages)
+    }
 
-        // create param_types
-        let mut param_types: Vec<BasicTypeEnum> = vec![];
-        for _ in 0..(arguments.len()) {
-            param_types.push(i64_type.into());
+    /// add a function to the module unless it is already declared and insert it to the environment
+    ///
+    /// returns the function with its return type and parameter types
+    fn declare_function(
+        &self,
+        declaration: &FunctionDeclaration,
+        environment: &mut Environment<'ctx>,
+    ) -> Result<(FunctionValue<'ctx>, Type, Vec<Type>)> {
+        let identifier = declaration.identifier.get_token().get_identifier()?;
+        let return_type = environment.resolve_type(&declaration.return_type)?;
+        // structures are not passed by value for now
+        if let Type::Struct(_) = return_type {
+            return Err(CompileError::Unexpect(
+                format!("function {} returning {}", identifier, return_type),
+                declaration.span,
+            ));
+        }
+
+        // array and function parameters are adjusted to pointers
+        let mut argument_types: Vec<Type> = vec![];
+        for argument in declaration.arguments.iter() {
+            let ty = environment
+                .resolve_type(argument)?
+                .adjust_parameter(argument.get_qualifiers());
+            if ty == Type::VaList || matches!(ty, Type::Struct(_)) {
+                return Err(CompileError::Unexpect(
+                    format!(
+                        "{} parameter {} of function {}",
+                        ty,
+                        argument.get_identifier(),
+                        identifier
+                    ),
+                    declaration.span,
+                ));
+            }
+            argument_types.push(ty);
         }
 
-        let function_value =
-            self.module
-                .add_function(&identifier, i64_type.fn_type(&param_types, false), None);
+        let function_value = match self.module.get_function(identifier.as_str()) {
+            Some(function_value) => function_value,
+            None => self.module.add_function(
+                identifier.as_str(),
+                self.function_type(&return_type, &argument_types, declaration.is_variadic),
+                None,
+            ),
+        };
+        environment.insert_function(
+            identifier,
+            function_value,
+            return_type.clone(),
+            argument_types.clone(),
+            declaration.is_variadic,
+        );
+        Ok((function_value, return_type, argument_types))
+    }
+
+    fn emit_function(
+        &self,
+        function: Function,
+        linkage: Linkage,
+        environment: &mut Environment<'ctx>,
+    ) -> Result<()> {
+        let (function_value, return_type, argument_types) =
+            self.declare_function(&function.declaration, environment)?;
+        let arguments = function.declaration.arguments;
+        if function_value.count_basic_blocks() != 0 {
+            return Err(CompileError::Unexpect(
+                format!(
+                    "redefinition of function {}",
+                    function.declaration.identifier
+                ),
+                function.declaration.span,
+            ));
+        }
+        function_value.set_linkage(linkage);
 
         let basic_block = self.context.append_basic_block(function_value, "entry");
         self.builder.position_at_end(basic_block);
 
-        let mut environment = Environment::new();
-        for (i, (argument_identifier, argument_type)) in arguments.into_iter().enumerate() {
-            let identifier = argument_identifier.get_token().get_identifier()?;
-            let _ty = argument_type.get_token().get_type()?;
-            let arg_value = function_value
-                .get_nth_param(i as u32)
-                .unwrap()
-                .into_int_value();
-            let pointer_value = self.builder.build_alloca(i64_type, "arg");
-            self.builder.build_store(pointer_value, arg_value);
-            environment.insert(identifier, pointer_value);
+        environment.push_scope();
+        for (i, (argument, ty)) in arguments.into_iter().zip(argument_types).enumerate() {
+            let identifier = argument.get_identifier();
+            // qualifiers of an array parameter are of the elements pointed by the adjusted pointer
+            let qualifiers = if argument.get_post().is_empty() {
+                argument.get_qualifiers()
+            } else {
+                Qualifiers::default()
+            };
+            let arg_value = function_value.get_nth_param(i as u32).unwrap();
+            let pointer_value = self.builder.build_alloca(self.llvm_type(&ty), "arg");
+            self.emit_store(pointer_value, arg_value, qualifiers);
+            environment.insert(identifier, pointer_value, ty, qualifiers);
         }
 
         for statement in function.block.into_iter() {
-            match statement {
-                Statement::Declare(statement) => {
-                    self.emit_declare_statement(statement, &mut environment)?
-                }
-                Statement::Expression(statement) => {
-                    self.emit_expression_statement(statement, &environment)?;
-                }
-            }
+            let span = statement.span();
+            self.emit_statement(statement, environment)
+                .map_err(|err| err.or_span(span))?;
         }
-        self.emit_return_statement(function.return_statement, &environment)?;
+        let span = function.return_statement.span;
+        self.emit_return_statement(function.return_statement, &return_type, environment)
+            .map_err(|err| err.or_span(span))?;
+        environment.pop_scope();
 
         self.module
             .print_to_file(path::Path::new("compiled.ll"))
             .map_err(|err| From::from(err))
     }
 
+    fn emit_statement(
+        &self,
+        statement: Statement,
+        environment: &mut Environment<'ctx>,
+    ) -> Result<()> {
+        match statement {
+            Statement::Declare(statement) => self.emit_declare_statement(statement, environment),
+            Statement::Enum(statement) => self.emit_enum_declaration(statement, environment),
+            Statement::Struct(statement) => self.emit_struct_declaration(statement, environment),
+            Statement::Typedef(_) => Ok(()),
+            Statement::StaticAssert(statement) => self.emit_static_assert(statement, environment),
+            Statement::Expression(statement) => {
+                self.emit_expression_statement(statement, environment)
+            }
+        }
+    }
+
+    /// resolve the type of a declared object (which cannot be a function or an incomplete type)
+    fn resolve_object_type(
+        &self,
+        type_struct: &TypeStruct,
+        environment: &Environment,
+    ) -> Result<Type> {
+        let ty = environment.resolve_type(type_struct)?;
+        if ty.is_function() {
+            return Err(CompileError::unexpect(format!(
+                "{} of function type {}",
+                type_struct.get_identifier(),
+                ty
+            )));
+        }
+        if environment.size_align(&ty).is_err() {
+            return Err(CompileError::unexpect(format!(
+                "{} of incomplete type {}",
+                type_struct.get_identifier(),
+                ty
+            )));
+        }
+        Ok(ty)
+    }
+
+    fn llvm_type(&self, ty: &Type) -> BasicTypeEnum<'ctx> {
+        match ty {
+            Type::Integer(integer_type) => self.int_type(*integer_type).into(),
+            Type::Floating(floating_type) => self.float_type(*floating_type).into(),
+            Type::Pointer(ty, _) => match ty.as_ref() {
+                Type::Function(return_type, parameter_types, is_variadic) => self
+                    .function_type(return_type, parameter_types, *is_variadic)
+                    .ptr_type(AddressSpace::Generic)
+                    .into(),
+                ty => self.llvm_type(ty).ptr_type(AddressSpace::Generic).into(),
+            },
+            Type::Array(ty, size) => self.llvm_type(ty).array_type(*size as u32).into(),
+            Type::Function(..) => unreachable!("function type {} is not an object type", ty),
+            Type::VaList => self.va_list_type().into(),
+            Type::Struct(tag) => self.struct_type(*tag).into(),
+        }
+    }
+
+    /// get the named type of a structure (which is opaque until the structure is declared)
+    fn struct_type(&self, tag: Symbol) -> StructType<'ctx> {
+        let name = format!("struct.{}", tag);
+        match self.module.get_struct_type(&name) {
+            Some(struct_type) => struct_type,
+            None => self.context.opaque_struct_type(&name),
+        }
+    }
+
+    fn function_type(
+        &self,
+        return_type: &Type,
+        parameter_types: &[Type],
+        is_variadic: bool,
+    ) -> FunctionType<'ctx> {
+        let parameter_types: Vec<BasicTypeEnum> = parameter_types
+            .iter()
+            .map(|ty| self.llvm_type(ty))
+            .collect();
+        self.llvm_type(return_type)
+            .fn_type(&parameter_types, is_variadic)
+    }
+
+    /// va_list of x86-64 System V ABI (an array of one `__va_list_tag`)
+    fn va_list_type(&self) -> ArrayType<'ctx> {
+        let i8_pointer = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        let va_list_tag = self.context.struct_type(
+            &[
+                self.context.i32_type().into(), // gp_offset
+                self.context.i32_type().into(), // fp_offset
+                i8_pointer.into(),              // overflow_arg_area
+                i8_pointer.into(),              // reg_save_area
+            ],
+            false,
+        );
+        va_list_tag.array_type(1)
+    }
+
+    fn int_type(&self, integer_type: IntegerType) -> IntType<'ctx> {
+        match integer_type.size() {
+            1 => self.context.i8_type(),
+            2 => self.context.i16_type(),
+            4 => self.context.i32_type(),
+            _ => self.context.i64_type(),
+        }
+    }
+
+    fn float_type(&self, floating_type: FloatingType) -> FloatType<'ctx> {
+        match floating_type {
+            FloatingType::Float => self.context.f32_type(),
+            FloatingType::Double => self.context.f64_type(),
+        }
+    }
+
+    /// convert an arithmetic value to another arithmetic type
+    ///
+    /// pointers are only converted to the same pointer type, whose pointed-to type can have more
+    /// qualifiers (e.g. `int *` to `volatile int *`)
+    fn convert(&self, value: Value<'ctx>, ty: &Type) -> Result<BasicValueEnum<'ctx>> {
+        match (&value.ty, ty) {
+            (Type::Pointer(from, from_qualifiers), Type::Pointer(to, to_qualifiers))
+                if from == to && from_qualifiers.merge(*to_qualifiers) == *to_qualifiers =>
+            {
+                Ok(value.value)
+            }
+            (Type::Integer(from), Type::Integer(to)) => Ok(self
+                .convert_integer(value.value.into_int_value(), *from, *to)
+                .into()),
+            (Type::Integer(from), Type::Floating(to)) => {
+                let (value, float_type) = (value.value.into_int_value(), self.float_type(*to));
+                Ok(if from.signed {
+                    self.builder
+                        .build_signed_int_to_float(value, float_type, "sitofp")
+                } else {
+                    self.builder
+                        .build_unsigned_int_to_float(value, float_type, "uitofp")
+                }
+                .into())
+            }
+            (Type::Floating(_), Type::Integer(to)) if to.kind == IntegerKind::Bool => {
+                // _Bool holds 1 for every value which does not compare equal to 0 (including NaN)
+                let value = value.value.into_float_value();
+                let zero = value.get_type().const_zero();
+                let is_nonzero =
+                    self.builder
+                        .build_float_compare(FloatPredicate::UNE, value, zero, "to_bool");
+                Ok(self
+                    .builder
+                    .build_int_z_extend(is_nonzero, self.int_type(*to), "bool")
+                    .into())
+            }
+            (Type::Floating(_), Type::Integer(to)) => {
+                let (value, int_type) = (value.value.into_float_value(), self.int_type(*to));
+                Ok(if to.signed {
+                    self.builder
+                        .build_float_to_signed_int(value, int_type, "fptosi")
+                } else {
+                    self.builder
+                        .build_float_to_unsigned_int(value, int_type, "fptoui")
+                }
+                .into())
+            }
+            (Type::Floating(from), Type::Floating(to)) => {
+                let (value, float_type) = (value.value.into_float_value(), self.float_type(*to));
+                Ok(if from < to {
+                    self.builder.build_float_ext(value, float_type, "fpext")
+                } else if from > to {
+                    self.builder.build_float_trunc(value, float_type, "fptrunc")
+                } else {
+                    value
+                }
+                .into())
+            }
+            _ => Err(CompileError::unexpect(format!(
+                "conversion from {} to {}",
+                value.ty, ty
+            ))),
+        }
+    }
+
+    /// convert an integer value to another integer type
+    fn convert_integer(
+        &self,
+        value: IntValue<'ctx>,
+        from: IntegerType,
+        to: IntegerType,
+    ) -> IntValue<'ctx> {
+        if to.kind == IntegerKind::Bool {
+            // _Bool holds 1 for every nonzero value
+            let zero = value.get_type().const_zero();
+            let is_nonzero =
+                self.builder
+                    .build_int_compare(IntPredicate::NE, value, zero, "to_bool");
+            return self
+                .builder
+                .build_int_z_extend(is_nonzero, self.int_type(to), "bool");
+        }
+        if from.bit_width() < to.bit_width() && from.signed {
+            self.builder
+                .build_int_s_extend(value, self.int_type(to), "sext")
+        } else if from.bit_width() < to.bit_width() {
+            self.builder
+                .build_int_z_extend(value, self.int_type(to), "zext")
+        } else if from.bit_width() > to.bit_width() {
+            self.builder
+                .build_int_truncate(value, self.int_type(to), "trunc")
+        } else {
+            value
+        }
+    }
+
+    /// emit a binary arithmetic operation after the usual arithmetic conversions
+    fn emit_arithmetic(
+        &self,
+        operator: Arithmetic,
+        lhs: Value<'ctx>,
+        rhs: Value<'ctx>,
+    ) -> Result<Value<'ctx>> {
+        let ty = Type::common(&lhs.ty, &rhs.ty).ok_or_else(|| {
+            CompileError::unexpect(format!("operands of type {} and {}", lhs.ty, rhs.ty))
+        })?;
+        let (lhs, rhs) = (self.convert(lhs, &ty)?, self.convert(rhs, &ty)?);
+        let value: BasicValueEnum = match &ty {
+            Type::Floating(_) => {
+                let (lhs, rhs) = (lhs.into_float_value(), rhs.into_float_value());
+                match operator {
+                    Arithmetic::Add => self.builder.build_float_add(lhs, rhs, "fadd"),
+                    Arithmetic::Sub => self.builder.build_float_sub(lhs, rhs, "fsub"),
+                    Arithmetic::Mul => self.builder.build_float_mul(lhs, rhs, "fmul"),
+                    Arithmetic::Div => self.builder.build_float_div(lhs, rhs, "fdiv"),
+                    Arithmetic::Rem => {
+                        return Err(CompileError::unexpect(format!("{} operand of %", ty)))
+                    }
+                }
+                .into()
+            }
+            Type::Integer(integer_type) => {
+                let (lhs, rhs) = (lhs.into_int_value(), rhs.into_int_value());
+                match operator {
+                    Arithmetic::Add => self.builder.build_int_add(lhs, rhs, "sum"),
+                    Arithmetic::Sub => self.builder.build_int_sub(lhs, rhs, "sub"),
+                    Arithmetic::Mul => self.builder.build_int_mul(lhs, rhs, "mul"),
+                    Arithmetic::Div if integer_type.signed => {
+                        self.builder.build_int_signed_div(lhs, rhs, "sdiv")
+                    }
+                    Arithmetic::Div => self.builder.build_int_unsigned_div(lhs, rhs, "udiv"),
+                    Arithmetic::Rem if integer_type.signed => {
+                        self.builder.build_int_signed_rem(lhs, rhs, "srem")
+                    }
+                    Arithmetic::Rem => self.builder.build_int_unsigned_rem(lhs, rhs, "urem"),
+                }
+                .into()
+            }
+            _ => unreachable!("usual arithmetic conversion gives an arithmetic type"),
+        };
+        Ok(Value { value, ty })
+    }
+
+    /// declare objects at file scope, which have static storage duration
+    fn emit_external_declare_statement(
+        &self,
+        declare_statement: DeclareStatement,
+        environment: &mut Environment<'ctx>,
+    ) -> Result<()> {
+        let storage_class = declare_statement.storage_class;
+        let alignments = declare_statement.alignments;
+        for declarator in declare_statement.declarators {
+            let span = declarator.span;
+            self.emit_external_declarator(declarator, storage_class, &alignments, environment)
+                .map_err(|err| err.or_span(span))?;
+        }
+        Ok(())
+    }
+
+    fn emit_external_declarator(
+        &self,
+        declarator: Declarator,
+        storage_class: Option<StorageClass>,
+        alignments: &[Alignment],
+        environment: &mut Environment<'ctx>,
+    ) -> Result<()> {
+        let identifier = declarator.type_struct.get_identifier();
+        let ty = self.resolve_object_type(&declarator.type_struct, environment)?;
+        let align = environment.resolve_alignment(&ty, alignments, identifier)?;
+        let global = match self.module.get_global(identifier.as_str()) {
+            Some(global) => global,
+            None => self
+                .module
+                .add_global(self.llvm_type(&ty), None, identifier.as_str()),
+        };
+        // an alignment specifier on any declaration of the object applies
+        global.set_alignment(global.get_alignment().max(align as u32));
+        let qualifiers = declarator.type_struct.get_qualifiers();
+        if storage_class == Some(StorageClass::Static) {
+            global.set_linkage(Linkage::Internal);
+        }
+        global.set_constant(qualifiers.is_const && !qualifiers.is_volatile);
+        match declarator.initializer {
+            Some(initializer) => {
+                let value = self.emit_constant_initializer(&ty, initializer, environment)?;
+                global.set_initializer(&value);
+            }
+            // a tentative definition is zero unless the object is defined by another declaration
+            None if storage_class != Some(StorageClass::Extern)
+                && global.get_initializer().is_none() =>
+            {
+                global.set_initializer(&self.const_zero(&ty));
+            }
+            None => (),
+        }
+        environment.insert(identifier, global.as_pointer_value(), ty, qualifiers);
+        Ok(())
+    }
+
     fn emit_declare_statement(
         &self,
         declare_statement: DeclareStatement,
         environment: &mut Environment<'ctx>,
     ) -> Result<()> {
-        let type_struct = declare_statement.type_struct;
+        match declare_statement.storage_class {
+            Some(StorageClass::Static) => {
+                return self.emit_static_declare_statement(declare_statement, environment)
+            }
+            // refers to an object defined at file scope (or in another translation unit)
+            Some(StorageClass::Extern) => {
+                return self.emit_external_declare_statement(declare_statement, environment)
+            }
+            None => (),
+        }
+        for declarator in declare_statement.declarators {
+            let span = declarator.span;
+            self.emit_declarator(declarator, &declare_statement.alignments, environment)
+                .map_err(|err| err.or_span(span))?;
+        }
+        Ok(())
+    }
+
+    fn emit_declarator(
+        &self,
+        declarator: Declarator,
+        alignments: &[Alignment],
+        environment: &mut Environment<'ctx>,
+    ) -> Result<()> {
+        let type_struct = declarator.type_struct;
         let identifier = type_struct.get_identifier();
-        let i64_type = self.context.i64_type();
-        let pointer_value = self.builder.build_alloca(i64_type, "variable");
-        environment.insert(identifier, pointer_value);
+        let qualifiers = type_struct.get_qualifiers();
+        let ty = self.resolve_object_type(&type_struct, environment)?;
+        let align = environment.resolve_alignment(&ty, alignments, identifier)?;
+        let pointer_value = self.builder.build_alloca(self.llvm_type(&ty), "variable");
+        pointer_value
+            .as_instruction()
+            .and_then(|instruction| instruction.set_alignment(align as u32).ok())
+            .expect("alloca has an alignment");
+        environment.insert(identifier, pointer_value, ty.clone(), qualifiers);
+        if let Some(initializer) = declarator.initializer {
+            self.emit_initializer(pointer_value, &ty, qualifiers, initializer, environment)?;
+        }
+        Ok(())
+    }
+
+    /// declare static objects in a block, which are emitted as internal globals named after the function
+    fn emit_static_declare_statement(
+        &self,
+        declare_statement: DeclareStatement,
+        environment: &mut Environment<'ctx>,
+    ) -> Result<()> {
+        let function_value = self
+            .builder
+            .get_insert_block()
+            .and_then(|basic_block| basic_block.get_parent())
+            .expect("static declaration outside of a function");
+        for declarator in declare_statement.declarators {
+            let span = declarator.span;
+            self.emit_static_declarator(
+                declarator,
+                &declare_statement.alignments,
+                function_value,
+                environment,
+            )
+            .map_err(|err| err.or_span(span))?;
+        }
+        Ok(())
+    }
+
+    fn emit_static_declarator(
+        &self,
+        declarator: Declarator,
+        alignments: &[Alignment],
+        function_value: FunctionValue<'ctx>,
+        environment: &mut Environment<'ctx>,
+    ) -> Result<()> {
+        let identifier = declarator.type_struct.get_identifier();
+        let ty = self.resolve_object_type(&declarator.type_struct, environment)?;
+        let name = format!(
+            "{}.{}",
+            function_value.get_name().to_string_lossy(),
+            identifier
+        );
+        let qualifiers = declarator.type_struct.get_qualifiers();
+        let align = environment.resolve_alignment(&ty, alignments, identifier)?;
+        let global: GlobalValue = self.module.add_global(self.llvm_type(&ty), None, &name);
+        global.set_alignment(align as u32);
+        global.set_linkage(Linkage::Internal);
+        global.set_constant(qualifiers.is_const && !qualifiers.is_volatile);
+        let value = match declarator.initializer {
+            Some(initializer) => self.emit_constant_initializer(&ty, initializer, environment)?,
+            None => self.const_zero(&ty),
+        };
+        global.set_initializer(&value);
+        environment.insert(identifier, global.as_pointer_value(), ty, qualifiers);
+        Ok(())
+    }
+
+    /// get the constant initial value of an object with static storage duration
+    ///
+    /// a floating initializer has to be a floating constant or an integer constant expression
+    fn emit_constant_initializer(
+        &self,
+        ty: &Type,
+        initializer: Initializer,
+        environment: &Environment<'ctx>,
+    ) -> Result<BasicValueEnum<'ctx>> {
+        match (ty, initializer) {
+            (Type::Array(..), Initializer::List(initializers)) => {
+                let mut initializers = initializers.into_iter().peekable();
+                let value =
+                    self.emit_constant_array_initializers(ty, &mut initializers, environment)?;
+                match initializers.next() {
+                    Some(_) => Err(CompileError::unexpect(format!(
+                        "excess elements in initializer of {}",
+                        ty
+                    ))),
+                    None => Ok(value),
+                }
+            }
+            (Type::Struct(_), Initializer::List(initializers)) => {
+                let mut initializers = initializers.into_iter().peekable();
+                let value =
+                    self.emit_constant_struct_initializers(ty, &mut initializers, environment)?;
+                match initializers.next() {
+                    Some(_) => Err(CompileError::unexpect(format!(
+                        "excess elements in initializer of {}",
+                        ty
+                    ))),
+                    None => Ok(value),
+                }
+            }
+            (Type::Integer(integer_type), Initializer::Expression(expression)) => {
+                let value = match floating_constant(&expression) {
+                    Some(value) => value as i64,
+                    None => evaluate(&expression, environment)?,
+                };
+                let value = if integer_type.kind == IntegerKind::Bool {
+                    (value != 0) as u64
+                } else {
+                    value as u64
+                };
+                Ok(self.int_type(*integer_type).const_int(value, true).into())
+            }
+            (Type::Floating(floating_type), Initializer::Expression(expression)) => {
+                let value = match floating_constant(&expression) {
+                    Some(value) => value,
+                    None => evaluate(&expression, environment)? as f64,
+                };
+                Ok(self.float_type(*floating_type).const_float(value).into())
+            }
+            (Type::Pointer(pointee, _), Initializer::Expression(expression))
+                if pointee.is_function() =>
+            {
+                // a function pointer is initialized with the address of a function
+                let function = function_designator(&expression)
+                    .and_then(|identifier| environment.get(identifier));
+                match function {
+                    Some(Entity::Function(
+                        function_value,
+                        return_type,
+                        parameter_types,
+                        is_variadic,
+                    )) if **pointee
+                        == Type::Function(
+                            Box::new(return_type.clone()),
+                            parameter_types.clone(),
+                            *is_variadic,
+                        ) =>
+                    {
+                        Ok(function_value.as_global_value().as_pointer_value().into())
+                    }
+                    _ => Err(CompileError::unexpect(format!(
+                        "constant initializer of {}",
+                        ty
+                    ))),
+                }
+            }
+            (_, Initializer::Expression(_)) => Err(CompileError::unexpect(format!(
+                "constant initializer of {}",
+                ty
+            ))),
+            (_, Initializer::List(initializers)) => {
+                // the initializer of a scalar can be enclosed in braces
+                let mut initializers = initializers.into_iter();
+                match (initializers.next(), initializers.next()) {
+                    (Some(initializer @ Initializer::Expression(_)), None) => {
+                        self.emit_constant_initializer(ty, initializer, environment)
+                    }
+                    _ => Err(CompileError::unexpect(format!(
+                        "initializer list of {}",
+                        ty
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// get a constant array from initializers in order (see emit_array_initializers)
+    fn emit_constant_array_initializers(
+        &self,
+        ty: &Type,
+        initializers: &mut Peekable<vec::IntoIter<Initializer>>,
+        environment: &Environment<'ctx>,
+    ) -> Result<BasicValueEnum<'ctx>> {
+        let (element_type, size) = match ty {
+            Type::Array(element_type, size) => (element_type, *size),
+            _ => unreachable!("array initializers of {}", ty),
+        };
+        let mut elements = vec![];
+        for _ in 0..size {
+            elements.push(match initializers.peek() {
+                Some(Initializer::Expression(_)) if element_type.is_array() => {
+                    self.emit_constant_array_initializers(element_type, initializers, environment)?
+                }
+                Some(Initializer::Expression(_)) if element_type.is_aggregate() => {
+                    self.emit_constant_struct_initializers(element_type, initializers, environment)?
+                }
+                Some(_) => {
+                    let initializer = initializers.next().unwrap();
+                    self.emit_constant_initializer(element_type, initializer, environment)?
+                }
+                // elements without initializer are zero
+                None => self.const_zero(element_type),
+            });
+        }
+        Ok(match self.llvm_type(element_type) {
+            BasicTypeEnum::IntType(int_type) => {
+                let elements: Vec<_> = elements.into_iter().map(|e| e.into_int_value()).collect();
+                int_type.const_array(&elements)
+            }
+            BasicTypeEnum::FloatType(float_type) => {
+                let elements: Vec<_> = elements.into_iter().map(|e| e.into_float_value()).collect();
+                float_type.const_array(&elements)
+            }
+            BasicTypeEnum::PointerType(pointer_type) => {
+                let elements: Vec<_> = elements
+                    .into_iter()
+                    .map(|e| e.into_pointer_value())
+                    .collect();
+                pointer_type.const_array(&elements)
+            }
+            BasicTypeEnum::ArrayType(array_type) => {
+                let elements: Vec<_> = elements.into_iter().map(|e| e.into_array_value()).collect();
+                array_type.const_array(&elements)
+            }
+            BasicTypeEnum::StructType(struct_type) => {
+                let elements: Vec<_> = elements
+                    .into_iter()
+                    .map(|e| e.into_struct_value())
+                    .collect();
+                struct_type.const_array(&elements)
+            }
+            _ => unreachable!("array of {}", element_type),
+        }
+        .into())
+    }
+
+    /// get a constant structure from initializers in declaration order (see
+    /// emit_struct_initializers)
+    ///
+    /// the structure is built from the bytes of its members, so a pointer member can only be
+    /// initialized by a null pointer constant
+    fn emit_constant_struct_initializers(
+        &self,
+        ty: &Type,
+        initializers: &mut Peekable<vec::IntoIter<Initializer>>,
+        environment: &Environment<'ctx>,
+    ) -> Result<BasicValueEnum<'ctx>> {
+        let layout = environment.layout_of(ty)?;
+        let mut bytes = vec![0; layout.size as usize];
+        self.write_constant_aggregate(&mut bytes, ty, initializers, environment)?;
+        // the body of a structure is an array of its alignment unit in little endian
+        let unit_type = self.context.custom_width_int_type(layout.align as u32 * 8);
+        let units: Vec<_> = bytes
+            .chunks(layout.align as usize)
+            .map(|unit| {
+                let words: Vec<u64> = unit
+                    .chunks(8)
+                    .map(|word| word.iter().rev().fold(0, |w, byte| w << 8 | *byte as u64))
+                    .collect();
+                unit_type.const_int_arbitrary_precision(&words)
+            })
+            .collect();
+        let body = unit_type.const_array(&units);
+        Ok(self
+            .llvm_type(ty)
+            .into_struct_type()
+            .const_named_struct(&[body.into()])
+            .into())
+    }
+
+    /// write the constant initial value of an object to its bytes, which are zero without
+    /// initializer
+    fn write_constant_initializer(
+        &self,
+        bytes: &mut [u8],
+        ty: &Type,
+        initializer: Initializer,
+        environment: &Environment<'ctx>,
+    ) -> Result<()> {
+        match (ty, initializer) {
+            (_, Initializer::List(initializers)) if ty.is_aggregate() => {
+                let mut initializers = initializers.into_iter().peekable();
+                self.write_constant_aggregate(bytes, ty, &mut initializers, environment)?;
+                match initializers.next() {
+                    Some(_) => Err(CompileError::unexpect(format!(
+                        "excess elements in initializer of {}",
+                        ty
+                    ))),
+                    None => Ok(()),
+                }
+            }
+            (_, Initializer::Expression(_)) if ty.is_aggregate() => Err(CompileError::unexpect(
+                format!("{} initialized by an expression", ty),
+            )),
+            (_, initializer) => match scalar_initializer(initializer) {
+                Some(expression) => {
+                    let value = self.constant_scalar(ty, &expression, environment)?;
+                    let size = bytes.len();
+                    bytes.copy_from_slice(&value.to_le_bytes()[..size]);
+                    Ok(())
+                }
+                None => Err(CompileError::unexpect(format!(
+                    "initializer list of {}",
+                    ty
+                ))),
+            },
+        }
+    }
+
+    /// write elements of an array or members of a structure from initializers in order (see
+    /// emit_array_initializers)
+    fn write_constant_aggregate(
+        &self,
+        bytes: &mut [u8],
+        ty: &Type,
+        initializers: &mut Peekable<vec::IntoIter<Initializer>>,
+        environment: &Environment<'ctx>,
+    ) -> Result<()> {
+        // elements of an array are laid out like members
+        let objects: Vec<MemberLayout> = match ty {
+            Type::Array(element_type, size) => {
+                let (element_size, _) = environment.size_align(element_type)?;
+                (0..*size)
+                    .map(|index| MemberLayout {
+                        ty: *element_type.clone(),
+                        qualifiers: Qualifiers::default(),
+                        offset: index * element_size,
+                        bit_field: None,
+                    })
+                    .collect()
+            }
+            _ => environment
+                .layout_of(ty)?
+                .members
+                .iter()
+                .map(|(_, member)| member.clone())
+                .collect(),
+        };
+        for MemberLayout {
+            ty,
+            offset,
+            bit_field,
+            ..
+        } in objects
+        {
+            let is_elided = match initializers.peek() {
+                Some(Initializer::Expression(_)) => ty.is_aggregate(),
+                Some(Initializer::List(_)) => false,
+                None => break,
+            };
+            let (size, _) = environment.size_align(&ty)?;
+            let bytes = &mut bytes[offset as usize..(offset + size) as usize];
+            match bit_field {
+                _ if is_elided => {
+                    self.write_constant_aggregate(bytes, &ty, initializers, environment)?
+                }
+                None => {
+                    let initializer = initializers.next().unwrap();
+                    self.write_constant_initializer(bytes, &ty, initializer, environment)?
+                }
+                Some((bit_offset, width)) => {
+                    let expression = match scalar_initializer(initializers.next().unwrap()) {
+                        Some(expression) => expression,
+                        None => {
+                            return Err(CompileError::unexpect(format!(
+                                "initializer list of {}",
+                                ty
+                            )))
+                        }
+                    };
+                    let value = self.constant_scalar(&ty, &expression, environment)?;
+                    let mask = (u64::MAX >> (64 - width)) << bit_offset;
+                    let mut unit = [0; 8];
+                    unit[..bytes.len()].copy_from_slice(bytes);
+                    let unit = u64::from_le_bytes(unit) & !mask | value << bit_offset & mask;
+                    bytes.copy_from_slice(&unit.to_le_bytes()[..size as usize]);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// get the bits of the constant value of a scalar object
+    fn constant_scalar(
+        &self,
+        ty: &Type,
+        expression: &Expression,
+        environment: &Environment<'ctx>,
+    ) -> Result<u64> {
+        match ty {
+            Type::Integer(integer_type) => {
+                let value = match floating_constant(expression) {
+                    Some(value) => value as i64,
+                    None => evaluate(expression, environment)?,
+                };
+                Ok(if integer_type.kind == IntegerKind::Bool {
+                    (value != 0) as u64
+                } else {
+                    value as u64
+                })
+            }
+            Type::Floating(floating_type) => {
+                let value = match floating_constant(expression) {
+                    Some(value) => value,
+                    None => evaluate(expression, environment)? as f64,
+                };
+                Ok(match floating_type {
+                    FloatingType::Float => (value as f32).to_bits() as u64,
+                    FloatingType::Double => value.to_bits(),
+                })
+            }
+            // a null pointer constant
+            Type::Pointer(..) if evaluate(expression, environment).ok() == Some(0) => Ok(0),
+            _ => Err(CompileError::unexpect(format!(
+                "constant initializer of {}",
+                ty
+            ))),
+        }
+    }
+
+    fn const_zero(&self, ty: &Type) -> BasicValueEnum<'ctx> {
+        match self.llvm_type(ty) {
+            BasicTypeEnum::IntType(int_type) => int_type.const_zero().into(),
+            BasicTypeEnum::FloatType(float_type) => float_type.const_zero().into(),
+            BasicTypeEnum::PointerType(pointer_type) => pointer_type.const_null().into(),
+            BasicTypeEnum::ArrayType(array_type) => array_type.const_zero().into(),
+            BasicTypeEnum::StructType(struct_type) => struct_type.const_zero().into(),
+            _ => unreachable!("zero of {}", ty),
+        }
+    }
+
+    /// load the value of an object (volatile accesses are kept as they are by optimizations)
+    fn emit_load(
+        &self,
+        pointer: PointerValue<'ctx>,
+        qualifiers: Qualifiers,
+    ) -> BasicValueEnum<'ctx> {
+        let value = self.builder.build_load(pointer, "variable_load");
+        if qualifiers.is_volatile {
+            let instruction = value
+                .as_instruction_value()
+                .expect("load is an instruction");
+            instruction
+                .set_volatile(true)
+                .expect("load can be volatile");
+        }
+        value
+    }
+
+    fn emit_store(
+        &self,
+        pointer: PointerValue<'ctx>,
+        value: BasicValueEnum<'ctx>,
+        qualifiers: Qualifiers,
+    ) {
+        let instruction = self.builder.build_store(pointer, value);
+        if qualifiers.is_volatile {
+            instruction
+                .set_volatile(true)
+                .expect("store can be volatile");
+        }
+    }
+
+    /// store the initial value of an object
+    fn emit_initializer(
+        &self,
+        pointer: PointerValue<'ctx>,
+        ty: &Type,
+        qualifiers: Qualifiers,
+        initializer: Initializer,
+        environment: &Environment<'ctx>,
+    ) -> Result<()> {
+        match (ty, initializer) {
+            (Type::Array(..), Initializer::List(initializers)) => {
+                // elements without initializer are zero
+                let zero = self.llvm_type(ty).into_array_type().const_zero();
+                self.emit_store(pointer, zero.into(), qualifiers);
+                let mut initializers = initializers.into_iter().peekable();
+                self.emit_array_initializers(
+                    pointer,
+                    ty,
+                    qualifiers,
+                    &mut initializers,
+                    environment,
+                )?;
+                match initializers.next() {
+                    Some(_) => Err(CompileError::unexpect(format!(
+                        "excess elements in initializer of {}",
+                        ty
+                    ))),
+                    None => Ok(()),
+                }
+            }
+            (Type::Struct(_), Initializer::List(initializers)) => {
+                // members without initializer are zero
+                self.emit_store(pointer, self.const_zero(ty), qualifiers);
+                let mut initializers = initializers.into_iter().peekable();
+                self.emit_struct_initializers(
+                    pointer,
+                    ty,
+                    qualifiers,
+                    &mut initializers,
+                    environment,
+                )?;
+                match initializers.next() {
+                    Some(_) => Err(CompileError::unexpect(format!(
+                        "excess elements in initializer of {}",
+                        ty
+                    ))),
+                    None => Ok(()),
+                }
+            }
+            (Type::Array(..), Initializer::Expression(_)) => Err(CompileError::unexpect(format!(
+                "{} initialized by an expression",
+                ty
+            ))),
+            (_, Initializer::Expression(expression)) => {
+                let value = self.emit_expression(expression, environment)?;
+                self.emit_store(pointer, self.convert(value, ty)?, qualifiers);
+                Ok(())
+            }
+            (_, Initializer::List(initializers)) => {
+                // the initializer of a scalar can be enclosed in braces
+                let mut initializers = initializers.into_iter();
+                match (initializers.next(), initializers.next()) {
+                    (Some(initializer @ Initializer::Expression(_)), None) => {
+                        self.emit_initializer(pointer, ty, qualifiers, initializer, environment)
+                    }
+                    _ => Err(CompileError::unexpect(format!(
+                        "initializer list of {}",
+                        ty
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// initialize array elements in order
+    ///
+    /// an element which is an array or a structure itself takes as many initializers as it needs
+    /// when its initializer is not enclosed in braces (e.g. `int a[2][2] = { 1, 2, 3 };`)
+    fn emit_array_initializers(
+        &self,
+        pointer: PointerValue<'ctx>,
+        ty: &Type,
+        qualifiers: Qualifiers,
+        initializers: &mut Peekable<vec::IntoIter<Initializer>>,
+        environment: &Environment<'ctx>,
+    ) -> Result<()> {
+        let (element_type, size) = match ty {
+            Type::Array(element_type, size) => (element_type, *size),
+            _ => unreachable!("array initializers of {}", ty),
+        };
+        let index_type = self.context.i64_type();
+        for index in 0..size {
+            let is_elided = match initializers.peek() {
+                Some(Initializer::Expression(_)) => element_type.is_aggregate(),
+                Some(Initializer::List(_)) => false,
+                None => break,
+            };
+            let element_pointer = unsafe {
+                self.builder.build_in_bounds_gep(
+                    pointer,
+                    &[index_type.const_zero(), index_type.const_int(index, false)],
+                    "element",
+                )
+            };
+            if is_elided {
+                self.emit_elided_initializers(
+                    element_pointer,
+                    element_type,
+                    qualifiers,
+                    initializers,
+                    environment,
+                )?;
+            } else {
+                let initializer = initializers.next().unwrap();
+                self.emit_initializer(
+                    element_pointer,
+                    element_type,
+                    qualifiers,
+                    initializer,
+                    environment,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// initialize members of a structure in declaration order (see emit_array_initializers)
+    ///
+    /// unnamed bit-fields have no initializers
+    fn emit_struct_initializers(
+        &self,
+        pointer: PointerValue<'ctx>,
+        ty: &Type,
+        qualifiers: Qualifiers,
+        initializers: &mut Peekable<vec::IntoIter<Initializer>>,
+        environment: &Environment<'ctx>,
+    ) -> Result<()> {
+        let layout = environment.layout_of(ty)?;
+        for (_, member_layout) in layout.members.iter() {
+            let is_elided = match initializers.peek() {
+                Some(Initializer::Expression(_)) => member_layout.ty.is_aggregate(),
+                Some(Initializer::List(_)) => false,
+                None => break,
+            };
+            let member_qualifiers = qualifiers.merge(member_layout.qualifiers);
+            match self.member_location(pointer, member_layout) {
+                Location::Object(member_pointer) if is_elided => self.emit_elided_initializers(
+                    member_pointer,
+                    &member_layout.ty,
+                    member_qualifiers,
+                    initializers,
+                    environment,
+                )?,
+                Location::Object(member_pointer) => {
+                    let initializer = initializers.next().unwrap();
+                    self.emit_initializer(
+                        member_pointer,
+                        &member_layout.ty,
+                        member_qualifiers,
+                        initializer,
+                        environment,
+                    )?
+                }
+                location @ Location::BitField(..) => {
+                    let expression = match scalar_initializer(initializers.next().unwrap()) {
+                        Some(expression) => expression,
+                        None => {
+                            return Err(CompileError::unexpect(format!(
+                                "initializer list of {}",
+                                member_layout.ty
+                            )))
+                        }
+                    };
+                    let value = self.emit_expression(expression, environment)?;
+                    self.emit_store_location(
+                        &location,
+                        &member_layout.ty,
+                        value,
+                        member_qualifiers,
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// initialize an array or a structure whose initializer is not enclosed in braces
+    fn emit_elided_initializers(
+        &self,
+        pointer: PointerValue<'ctx>,
+        ty: &Type,
+        qualifiers: Qualifiers,
+        initializers: &mut Peekable<vec::IntoIter<Initializer>>,
+        environment: &Environment<'ctx>,
+    ) -> Result<()> {
+        match ty {
+            Type::Array(..) => {
+                self.emit_array_initializers(pointer, ty, qualifiers, initializers, environment)
+            }
+            _ => self.emit_struct_initializers(pointer, ty, qualifiers, initializers, environment),
+        }
+    }
+
+    fn emit_enum_declaration(
+        &self,
+        enum_declaration: EnumDeclaration,
+        environment: &mut Environment<'ctx>,
+    ) -> Result<()> {
+        // the value following the previous enumerator, which is None when it overflows
+        let mut next: Option<i64> = Some(0);
+        for enumerator in enum_declaration.enumerators {
+            let value = match enumerator.value {
+                Some(expression) => Some(evaluate(&expression, environment)?),
+                None => next,
+            };
+            // enumeration constants have type int
+            let value = match value {
+                Some(value) if i32::try_from(value).is_ok() => value,
+                _ => {
+                    return Err(CompileError::Unexpect(
+                        format!(
+                            "value of enumerator {} out of range of int",
+                            enumerator.identifier
+                        ),
+                        enumerator.span,
+                    ))
+                }
+            };
+            environment.insert_constant(enumerator.identifier, value);
+            next = value.checked_add(1);
+        }
+        Ok(())
+    }
+
+    /// check a static assertion, which emits nothing
+    fn emit_static_assert(
+        &self,
+        static_assert: StaticAssertDeclaration,
+        environment: &Environment<'ctx>,
+    ) -> Result<()> {
+        if evaluate(&static_assert.expression, environment)? == 0 {
+            return Err(CompileError::StaticAssertion(
+                static_assert.message,
+                static_assert.span,
+            ));
+        }
+        Ok(())
+    }
+
+    /// lay out the members of a structure in x86-64 System V ABI
+    ///
+    /// a bit-field is packed into the storage unit of its declared type right after the previous
+    /// member unless it would straddle the boundary of the unit. a zero-width bit-field starts the
+    /// next unit, and unnamed bit-fields do not affect the alignment of the structure. an
+    /// alignment specifier of a member raises the alignment of the structure as well.
+    fn emit_struct_declaration(
+        &self,
+        struct_declaration: StructDeclaration,
+        environment: &mut Environment<'ctx>,
+    ) -> Result<()> {
+        let tag = struct_declaration.tag;
+        let mut members: Vec<(Symbol, MemberLayout)> = vec![];
+        // offset is in bits to pack bit-fields
+        let (mut offset, mut align) = (0, 1);
+        for member in struct_declaration.members {
+            let identifier = member.type_struct.get_identifier();
+            let ty = self.resolve_object_type(&member.type_struct, environment)?;
+            let (size, _) = environment.size_align(&ty)?;
+            let member_align =
+                environment.resolve_alignment(&ty, &member.alignments, identifier)?;
+            if member.width.is_some() && !member.alignments.is_empty() {
+                return Err(CompileError::unexpect(format!(
+                    "alignment of bit-field {}",
+                    identifier
+                )));
+            }
+            let (member_offset, bit_field) = match member.width {
+                None => {
+                    offset = round_up(offset, member_align * 8);
+                    let member_offset = offset / 8;
+                    offset += size * 8;
+                    (member_offset, None)
+                }
+                Some(width) => {
+                    let integer_type = match &ty {
+                        Type::Integer(integer_type) => *integer_type,
+                        _ => {
+                            return Err(CompileError::unexpect(format!(
+                                "bit-field {} of type {}",
+                                identifier, ty
+                            )))
+                        }
+                    };
+                    let width = evaluate(&width, environment)?;
+                    let max_width = match integer_type.kind {
+                        IntegerKind::Bool => 1,
+                        _ => integer_type.bit_width() as i64,
+                    };
+                    if width < 0 || width > max_width || width == 0 && !identifier.is_empty() {
+                        return Err(CompileError::unexpect(format!(
+                            "width {} of bit-field {}",
+                            width, identifier
+                        )));
+                    }
+                    let (width, unit) = (width as u64, size * 8);
+                    if width == 0 || offset / unit != (offset + width - 1) / unit {
+                        offset = round_up(offset, unit);
+                    }
+                    if identifier.is_empty() {
+                        offset += width;
+                        continue;
+                    }
+                    let member_offset = offset / unit * size;
+                    let bit_offset = (offset % unit) as u32;
+                    offset += width;
+                    (member_offset, Some((bit_offset, width as u32)))
+                }
+            };
+            align = align.max(member_align);
+            let layout = MemberLayout {
+                ty,
+                qualifiers: member.type_struct.get_qualifiers(),
+                offset: member_offset,
+                bit_field,
+            };
+            if members.iter().any(|(member, _)| *member == identifier) {
+                return Err(CompileError::unexpect(format!(
+                    "duplicate member {} of struct {}",
+                    identifier, tag
+                )));
+            }
+            members.push((identifier, layout));
+        }
+        let size = round_up(round_up(offset, 8) / 8, align);
+
+        // tags share the names of llvm types, so that a tag cannot be declared again in a block
+        let struct_type = self.struct_type(tag);
+        if !struct_type.is_opaque() {
+            return Err(CompileError::unexpect(format!(
+                "redefinition of struct {}",
+                tag
+            )));
+        }
+        // members are accessed by their offsets in the array of the alignment unit
+        let unit_type = self.context.custom_width_int_type(align as u32 * 8);
+        struct_type.set_body(&[unit_type.array_type((size / align) as u32).into()], false);
+        environment.insert_struct(
+            tag,
+            StructLayout {
+                members,
+                size,
+                align,
+            },
+        );
         Ok(())
     }
 
     fn emit_expression_statement(
         &self,
         expression_statement: ExpressionStatement,
-        environment: &'a Environment,
-    ) -> Result<IntValue> {
-        self.emit_expression(expression_statement.expression, environment)
+        environment: &Environment<'ctx>,
+    ) -> Result<()> {
+        let expression = expression_statement.expression;
+        // va_start and va_end have no value
+        match single_exp16(&expression) {
+            Some(Exp16::VaStart(list)) => {
+                let function = self.builder.get_insert_block().and_then(|b| b.get_parent());
+                if !matches!(function, Some(function) if function.get_type().is_var_arg()) {
+                    return Err(CompileError::Unexpect(
+                        "va_start in a function without variable arguments".to_owned(),
+                        expression.span,
+                    ));
+                }
+                self.emit_va_intrinsic("llvm.va_start", list, environment)
+            }
+            Some(Exp16::VaEnd(list)) => self.emit_va_intrinsic("llvm.va_end", list, environment),
+            _ => {
+                self.emit_expression(expression, environment)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// call the intrinsic of va_start or va_end with a va_list
+    fn emit_va_intrinsic(
+        &self,
+        name: &str,
+        list: &Expression,
+        environment: &Environment<'ctx>,
+    ) -> Result<()> {
+        let list = self.emit_va_list(list.clone(), environment)?;
+        let intrinsic = match self.module.get_function(name) {
+            Some(intrinsic) => intrinsic,
+            None => {
+                let function_type = self
+                    .context
+                    .void_type()
+                    .fn_type(&[list.get_type().into()], false);
+                self.module.add_function(name, function_type, None)
+            }
+        };
+        self.builder.build_call(intrinsic, &[list.into()], "");
+        Ok(())
+    }
+
+    /// get a va_list object as the `i8*` which the va intrinsics and va_arg take
+    fn emit_va_list(
+        &self,
+        list: Expression,
+        environment: &Environment<'ctx>,
+    ) -> Result<PointerValue<'ctx>> {
+        let span = list.span;
+        let (location, ty, _) = match list.expression {
+            Exp2::Single(exp) => self
+                .emit_expression_node_as_lhs(exp, environment)
+                .map_err(|err| err.or_span(span))?,
+            _ => {
+                return Err(CompileError::Unexpect(
+                    "assignment as va_list".to_owned(),
+                    span,
+                ))
+            }
+        };
+        let pointer = match (location, &ty) {
+            (Location::Object(pointer), Type::VaList) => pointer,
+            _ => return Err(CompileError::Unexpect(format!("{} as va_list", ty), span)),
+        };
+        let i8_pointer = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        Ok(self
+            .builder
+            .build_pointer_cast(pointer, i8_pointer, "va_list"))
     }
 
     fn emit_return_statement(
         &self,
         return_statement: ReturnStatement,
-        environment: &Environment,
+        return_type: &Type,
+        environment: &Environment<'ctx>,
     ) -> Result<()> {
         let return_expression_node = return_statement.expression;
         let ret_value = self.emit_expression(return_expression_node, environment)?;
+        let ret_value = self.convert(ret_value, return_type)?;
         self.builder.build_return(Some(&ret_value));
         Ok(())
     }
 
+    fn expect_integer(&self, ty: &Type) -> Result<IntegerType> {
+        match ty {
+            Type::Integer(integer_type) => Ok(*integer_type),
+            _ => Err(CompileError::unexpect(format!(
+                "{} operand where an integer is expected",
+                ty
+            ))),
+        }
+    }
+
     fn emit_expression(
         &self,
         node: Expression,
-        environment: &'a Environment,
-    ) -> Result<IntValue<'a>> {
+        environment: &Environment<'ctx>,
+    ) -> Result<Value<'ctx>> {
+        let span = node.span;
         self.emit_exp2(node.expression, environment)
+            .map_err(|err| err.or_span(span))
     }
-    fn emit_exp2(&self, node: Exp2, environment: &'a Environment) -> Result<IntValue<'a>> {
+    fn emit_exp2(&self, node: Exp2, environment: &Environment<'ctx>) -> Result<Value<'ctx>> {
         match node {
-            Exp2::Single(exp) => Ok(self.emit_exp12(exp, environment)?),
+            Exp2::Single(exp) => Ok(self.emit_exp11(exp, environment)?),
             Exp2::Eq(operands) => {
                 if operands.len() == 1 {
                     let mut operand_itr = operands.into_iter();
-                    self.emit_exp12(operand_itr.next().unwrap(), environment)
+                    self.emit_exp11(operand_itr.next().unwrap(), environment)
                 } else {
                     // todo emit multiple term (eg. a = b = c)
                     let mut operand_itr = operands.into_iter();
-                    let lhs: PointerValue =
+                    let (lhs, ty, qualifiers) =
                         self.emit_expression_node_as_lhs(operand_itr.next().unwrap(), environment)?;
-                    let rhs: IntValue =
-                        self.emit_exp12(operand_itr.next().unwrap(), environment)?;
-                    self.builder.build_store(lhs, rhs);
-                    Ok(rhs)
+                    let rhs = self.emit_exp11(operand_itr.next().unwrap(), environment)?;
+                    self.emit_store_location(&lhs, &ty, rhs, qualifiers)
                 }
             }
         }
     }
-    fn emit_exp12(&self, node: Exp12, environment: &'a Environment) -> Result<IntValue<'a>> {
+    fn emit_exp11(&self, node: Exp11, environment: &Environment<'ctx>) -> Result<Value<'ctx>> {
         match node {
-            Exp12::Single(exp) => Ok(self.emit_exp13(exp, environment)?),
-            Exp12::Add(operands) => {
-                let mut const_nums: Vec<IntValue> = Vec::new();
-                for operand in operands.into_iter() {
-                    const_nums.push(self.emit_exp13(operand, environment)?);
-                }
-                let mut reduced = self.context.i64_type().const_int(0, false);
-                for const_num in const_nums.into_iter() {
-                    reduced = self.builder.build_int_add(reduced, const_num, "sum");
-                }
-                Ok(reduced)
+            Exp11::Single(exp) => self.emit_exp12(exp, environment),
+            Exp11::Shl(lhs, rhs) => {
+                let (lhs, rhs, ty) = self.emit_shift_operands(*lhs, rhs, environment)?;
+                let value = self.builder.build_left_shift(lhs, rhs, "shl").into();
+                Ok(Value {
+                    value,
+                    ty: Type::Integer(ty),
+                })
+            }
+            Exp11::Shr(lhs, rhs) => {
+                let (lhs, rhs, ty) = self.emit_shift_operands(*lhs, rhs, environment)?;
+                let value = self
+                    .builder
+                    .build_right_shift(lhs, rhs, ty.signed, "shr")
+                    .into();
+                Ok(Value {
+                    value,
+                    ty: Type::Integer(ty),
+                })
             }
         }
     }
-    fn emit_exp13(&self, node: Exp13, environment: &'a Environment) -> Result<IntValue<'a>> {
+    /// both operands of a shift are promoted separately and the result has the type of lhs
+    fn emit_shift_operands(
+        &self,
+        lhs: Exp11,
+        rhs: Exp12,
+        environment: &Environment<'ctx>,
+    ) -> Result<(IntValue<'ctx>, IntValue<'ctx>, IntegerType)> {
+        let lhs = self.emit_exp11(lhs, environment)?;
+        let rhs = self.emit_exp12(rhs, environment)?;
+        let (lhs_type, rhs_type) = (self.expect_integer(&lhs.ty)?, self.expect_integer(&rhs.ty)?);
+        let ty = lhs_type.promote();
+        Ok((
+            self.convert_integer(lhs.value.into_int_value(), lhs_type, ty),
+            self.convert_integer(rhs.value.into_int_value(), rhs_type, ty),
+            ty,
+        ))
+    }
+    fn emit_exp12(&self, node: Exp12, environment: &Environment<'ctx>) -> Result<Value<'ctx>> {
+        let (operator, lhs, rhs) = match node {
+            Exp12::Single(exp) => return self.emit_exp13(exp, environment),
+            Exp12::Add(lhs, rhs) => (Arithmetic::Add, lhs, rhs),
+            Exp12::Sub(lhs, rhs) => (Arithmetic::Sub, lhs, rhs),
+        };
+        let lhs = self.emit_exp12(*lhs, environment)?;
+        let rhs = self.emit_exp13(rhs, environment)?;
+        self.emit_arithmetic(operator, lhs, rhs)
+    }
+    fn emit_exp13(&self, node: Exp13, environment: &Environment<'ctx>) -> Result<Value<'ctx>> {
+        let (operator, lhs, rhs) = match node {
+            Exp13::Single(exp) => return self.emit_exp15(exp, environment),
+            Exp13::Mul(lhs, rhs) => (Arithmetic::Mul, lhs, rhs),
+            Exp13::Div(lhs, rhs) => (Arithmetic::Div, lhs, rhs),
+            Exp13::Rem(lhs, rhs) => (Arithmetic::Rem, lhs, rhs),
+        };
+        let lhs = self.emit_exp13(*lhs, environment)?;
+        let rhs = self.emit_exp15(rhs, environment)?;
+        self.emit_arithmetic(operator, lhs, rhs)
+    }
+    fn emit_exp15(&self, node: Exp15, environment: &Environment<'ctx>) -> Result<Value<'ctx>> {
         match node {
-            Exp13::Single(exp) => Ok(self.emit_exp16(exp, environment)?),
-            Exp13::Mul(operands) => {
-                let mut const_nums: Vec<IntValue> = Vec::new();
-                for operand in operands.into_iter() {
-                    const_nums.push(self.emit_exp16(operand, environment)?);
+            Exp15::Single(exp) => self.emit_exp16(exp, environment),
+            Exp15::Address(exp) => match *exp {
+                // & of a function designator gives the same pointer as the function designator
+                Exp15::Single(Exp16::Identifier(identifier, span))
+                    if matches!(environment.get(identifier), Some(Entity::Function(..))) =>
+                {
+                    self.emit_exp16(Exp16::Identifier(identifier, span), environment)
                 }
-                let mut reduced = self.context.i64_type().const_int(1, false);
-                for const_num in const_nums.into_iter() {
-                    reduced = self.builder.build_int_mul(reduced, const_num, "mul");
+                // &*p is p
+                Exp15::Indirection(exp) => {
+                    let value = self.emit_exp15(*exp, environment)?;
+                    match &value.ty {
+                        Type::Pointer(..) => Ok(value),
+                        ty => Err(CompileError::unexpect(format!("indirection of {}", ty))),
+                    }
+                }
+                exp => match self.emit_unary_lvalue(&exp, environment)? {
+                    (Location::Object(pointer), ty, qualifiers) => Ok(Value {
+                        value: pointer.into(),
+                        ty: Type::Pointer(Box::new(ty), qualifiers),
+                    }),
+                    (Location::BitField(..), ..) => Err(CompileError::unexpect(format!(
+                        "address of bit-field {}",
+                        unary_lvalue_name(&exp)
+                    ))),
+                },
+            },
+            Exp15::Plus(exp) => self.emit_promoted_operand(*exp, "+", environment),
+            Exp15::Minus(exp) => {
+                let operand = self.emit_promoted_operand(*exp, "-", environment)?;
+                let value = match operand.value {
+                    BasicValueEnum::IntValue(value) => {
+                        self.builder.build_int_neg(value, "neg").into()
+                    }
+                    BasicValueEnum::FloatValue(value) => {
+                        self.builder.build_float_neg(value, "fneg").into()
+                    }
+                    _ => unreachable!("promoted operand is arithmetic"),
+                };
+                Ok(Value {
+                    value,
+                    ty: operand.ty,
+                })
+            }
+            // sizeof and _Alignof are integer constants of type size_t and the operand is not evaluated
+            exp @ Exp15::Sizeof(_) | exp @ Exp15::SizeofType(_) | exp @ Exp15::AlignofType(_) => {
+                let expression = Expression {
+                    expression: Exp2::Single(Exp11::Single(Exp12::Single(Exp13::Single(exp)))),
+                    span: Span::default(), // located at the enclosing expression
+                };
+                let size = evaluate(&expression, environment)?;
+                let value = self
+                    .int_type(size_type())
+                    .const_int(size as u64, false)
+                    .into();
+                Ok(Value {
+                    value,
+                    ty: Type::Integer(size_type()),
+                })
+            }
+            Exp15::Indirection(exp) => {
+                let value = self.emit_exp15(*exp, environment)?;
+                let (ty, qualifiers) = match &value.ty {
+                    // the function designator is converted back to the function pointer
+                    Type::Pointer(ty, _) if ty.is_function() => return Ok(value),
+                    Type::Pointer(ty, qualifiers) => (ty.as_ref().clone(), *qualifiers),
+                    ty => return Err(CompileError::unexpect(format!("indirection of {}", ty))),
+                };
+                if !ty.is_scalar() {
+                    return Err(CompileError::unexpect(format!(
+                        "object of type {} used as a value",
+                        ty
+                    )));
                 }
-                Ok(reduced)
+                let value = self.emit_load(value.value.into_pointer_value(), qualifiers);
+                Ok(Value { value, ty })
             }
         }
     }
-    fn emit_exp16(&self, node: Exp16, environment: &'a Environment) -> Result<IntValue<'a>> {
+    /// emit the arithmetic operand of unary + or - after the integer promotions
+    fn emit_promoted_operand(
+        &self,
+        node: Exp15,
+        operator: &str,
+        environment: &Environment<'ctx>,
+    ) -> Result<Value<'ctx>> {
+        let value = self.emit_exp15(node, environment)?;
+        let ty = match &value.ty {
+            Type::Integer(integer_type) => Type::Integer(integer_type.promote()),
+            Type::Floating(_) => value.ty.clone(),
+            ty => {
+                return Err(CompileError::unexpect(format!(
+                    "{} operand of unary {}",
+                    ty, operator
+                )))
+            }
+        };
+        let value = self.convert(value, &ty)?;
+        Ok(Value { value, ty })
+    }
+    fn emit_exp16(&self, node: Exp16, environment: &Environment<'ctx>) -> Result<Value<'ctx>> {
         match node {
-            Exp16::Number(number) => Ok(self.context.i64_type().const_int(number, false)),
-            Exp16::Identifier(identifier) => {
-                let variable_pointer = environment.get(&identifier);
-                if let Some(variable_pointer) = variable_pointer {
+            Exp16::Number(number, ty) => {
+                let value = self.int_type(ty).const_int(number, false).into();
+                Ok(Value {
+                    value,
+                    ty: Type::Integer(ty),
+                })
+            }
+            Exp16::Floating(number, ty) => {
+                let value = self.float_type(ty).const_float(number).into();
+                Ok(Value {
+                    value,
+                    ty: Type::Floating(ty),
+                })
+            }
+            Exp16::Identifier(identifier, span) => match environment.get(identifier) {
+                Some(Entity::Variable(variable_pointer, ty, qualifiers)) if ty.is_scalar() => {
+                    let value = self.emit_load(*variable_pointer, *qualifiers);
+                    Ok(Value {
+                        value,
+                        ty: ty.clone(),
+                    })
+                }
+                Some(Entity::Variable(_, ty, _)) => Err(CompileError::Unexpect(
+                    format!("{} of type {} used as a value", identifier, ty),
+                    span,
+                )),
+                Some(Entity::Constant(value)) => {
+                    // enumeration constants have type int
                     let value = self
-                        .builder
-                        .build_load(variable_pointer, "variable_load")
-                        .into_int_value();
-                    Ok(value)
-                } else {
-                    Err(CompileError::Undeclared(identifier))
+                        .int_type(IntegerType::int())
+                        .const_int(*value as u64, true)
+                        .into();
+                    Ok(Value {
+                        value,
+                        ty: Type::int(),
+                    })
+                }
+                Some(Entity::Function(
+                    function_value,
+                    return_type,
+                    parameter_types,
+                    is_variadic,
+                )) => {
+                    // a function designator is converted to a pointer to the function
+                    let value = function_value.as_global_value().as_pointer_value().into();
+                    let ty = Type::Function(
+                        Box::new(return_type.clone()),
+                        parameter_types.clone(),
+                        *is_variadic,
+                    );
+                    Ok(Value {
+                        value,
+                        ty: Type::Pointer(Box::new(ty), Qualifiers::default()),
+                    })
+                }
+                None => Err(CompileError::Undeclared(identifier.to_string(), span)),
+            },
+            Exp16::Parenthesis(expression) => self.emit_expression(*expression, environment),
+            exp @ Exp16::Member(..) => {
+                let (location, ty, qualifiers) = self.emit_lvalue(&exp, environment)?;
+                if !ty.is_scalar() {
+                    return Err(CompileError::unexpect(format!(
+                        "{} of type {} used as a value",
+                        lvalue_name(&exp),
+                        ty
+                    )));
                 }
+                self.emit_load_location(&location, &ty, qualifiers)
             }
-            Exp16::FunctionCall(identifier, parameter_expressions) => {
-                if let Some(fn_value) = self.module.get_function(&identifier) {
-                    let mut parameters: Vec<BasicValueEnum> = Vec::new();
-                    for parameter in parameter_expressions {
-                        parameters.push(self.emit_expression(parameter, environment)?.into());
-                    }
-                    let func_calls_site =
-                        self.builder.build_call(fn_value, &parameters, "func_call");
-                    Ok(func_calls_site
-                        .try_as_basic_value()
-                        .left()
-                        .unwrap()
-                        .into_int_value())
-                } else {
-                    Err(CompileError::NotFound("function".to_owned()))
+            Exp16::FunctionCall(function, parameter_expressions) => {
+                // functions are called directly and any other callee is called through its pointer
+                let (callee, name) = match *function {
+                    Exp16::Identifier(identifier, span) => match environment.get(identifier) {
+                        Some(Entity::Function(
+                            function_value,
+                            return_type,
+                            parameter_types,
+                            is_variadic,
+                        )) => (
+                            (
+                                CallableValue::from(*function_value),
+                                return_type.clone(),
+                                parameter_types.clone(),
+                                *is_variadic,
+                            ),
+                            identifier.as_str(),
+                        ),
+                        _ => (
+                            self.emit_callee(Exp16::Identifier(identifier, span), environment)?,
+                            identifier.as_str(),
+                        ),
+                    },
+                    function => (self.emit_callee(function, environment)?, "pointer"),
+                };
+                let (callable, return_type, parameter_types, is_variadic) = callee;
+                let (count, minimum) = (parameter_expressions.len(), parameter_types.len());
+                if count < minimum || count > minimum && !is_variadic {
+                    return Err(CompileError::unexpect(format!(
+                        "{} arguments to function {} taking {}{}",
+                        count,
+                        name,
+                        if is_variadic { "at least " } else { "" },
+                        minimum
+                    )));
+                }
+                let mut parameter_expressions = parameter_expressions.into_iter();
+                let mut parameters: Vec<BasicValueEnum> = Vec::new();
+                for (parameter, ty) in parameter_expressions.by_ref().zip(&parameter_types) {
+                    let value = self.emit_expression(parameter, environment)?;
+                    parameters.push(self.convert(value, ty)?);
+                }
+                // the variable arguments have the default argument promotions
+                for parameter in parameter_expressions {
+                    let value = self.emit_expression(parameter, environment)?;
+                    let ty = match &value.ty {
+                        Type::Integer(integer_type) => Type::Integer(integer_type.promote()),
+                        Type::Floating(_) => Type::Floating(FloatingType::Double),
+                        ty => ty.clone(),
+                    };
+                    parameters.push(self.convert(value, &ty)?);
                 }
+                let func_calls_site = self.builder.build_call(callable, &parameters, "func_call");
+                let value = func_calls_site.try_as_basic_value().left().unwrap();
+                Ok(Value {
+                    value,
+                    ty: return_type,
+                })
             }
+            Exp16::VaArg(list, type_name) => {
+                let list = self.emit_va_list(*list, environment)?;
+                let ty = self.resolve_object_type(&type_name, environment)?;
+                if !ty.is_scalar() {
+                    return Err(CompileError::unexpect(format!("va_arg of {}", ty)));
+                }
+                let value = self
+                    .builder
+                    .build_va_arg(list, self.llvm_type(&ty), "va_arg");
+                Ok(Value { value, ty })
+            }
+            Exp16::VaStart(_) | Exp16::VaEnd(_) => Err(CompileError::unexpect(
+                "va_start or va_end used as a value".to_owned(),
+            )),
+        }
+    }
+
+    /// get the function pointer of a callee which is not a function designator
+    ///
+    /// returns the callable with its return type, parameter types and whether it is variadic
+    fn emit_callee(
+        &self,
+        node: Exp16,
+        environment: &Environment<'ctx>,
+    ) -> Result<(CallableValue<'ctx>, Type, Vec<Type>, bool)> {
+        let value = self.emit_exp16(node, environment)?;
+        match value.ty {
+            Type::Pointer(ty, _) => match *ty {
+                Type::Function(return_type, parameter_types, is_variadic) => {
+                    let callable = CallableValue::try_from(value.value.into_pointer_value())
+                        .expect("pointer to a function is callable");
+                    Ok((callable, *return_type, parameter_types, is_variadic))
+                }
+                ty => Err(CompileError::unexpect(format!("call through {}*", ty))),
+            },
+            ty => Err(CompileError::unexpect(format!("call to {}", ty))),
         }
     }
 
     fn emit_expression_node_as_lhs(
         &self,
-        node: Exp12,
-        environment: &'a Environment,
-    ) -> Result<PointerValue<'a>> {
+        node: Exp11,
+        environment: &Environment<'ctx>,
+    ) -> Result<(Location<'ctx>, Type, Qualifiers)> {
         match node {
-            Exp12::Single(exp) => match exp {
-                Exp13::Single(exp) => match exp {
-                    Exp16::Identifier(identifier) => {
-                        if let Some(pointer_value) = environment.get(&identifier) {
-                            Ok(pointer_value)
-                        } else {
-                            Err(CompileError::Undeclared(identifier))
-                        }
-                    }
-                    _ => unimplemented!(),
-                },
-                _ => Err(CompileError::Unexpect(
-                    "Expect declared variable identifier".to_owned(),
-                )),
-            },
-            _ => Err(CompileError::Unexpect(
+            // every other primary expression is rejected by emit_lvalue
+            Exp11::Single(Exp12::Single(Exp13::Single(exp))) => {
+                let (location, ty, qualifiers) = self.emit_unary_lvalue(&exp, environment)?;
+                if qualifiers.is_const {
+                    let span = unary_lvalue_span(&exp).unwrap_or_default();
+                    return Err(CompileError::ConstAssignment(unary_lvalue_name(&exp), span));
+                }
+                Ok((location, ty, qualifiers))
+            }
+            _ => Err(CompileError::unexpect(
                 "Expect declared variable identifier".to_owned(),
             )),
         }
     }
+
+    /// get the object designated by a variable or a member of a structure
+    ///
+    /// qualifiers of a structure apply to its members
+    fn emit_lvalue(
+        &self,
+        node: &Exp16,
+        environment: &Environment<'ctx>,
+    ) -> Result<(Location<'ctx>, Type, Qualifiers)> {
+        match node {
+            Exp16::Identifier(identifier, span) => match environment.get(*identifier) {
+                Some(Entity::Variable(pointer_value, ty, qualifiers)) => {
+                    Ok((Location::Object(*pointer_value), ty.clone(), *qualifiers))
+                }
+                Some(_) => Err(CompileError::NotAssignable(identifier.to_string(), *span)),
+                None => Err(CompileError::Undeclared(identifier.to_string(), *span)),
+            },
+            Exp16::Member(exp, member) => {
+                let (location, ty, qualifiers) = self.emit_lvalue(exp, environment)?;
+                let (pointer, layout) = match (location, &ty) {
+                    (Location::Object(pointer), Type::Struct(tag)) => {
+                        (pointer, environment.get_struct(*tag))
+                    }
+                    _ => {
+                        return Err(CompileError::unexpect(format!(
+                            "member {} of {}",
+                            member, ty
+                        )))
+                    }
+                };
+                let member_layout = match layout.and_then(|layout| layout.member(*member)) {
+                    Some(member_layout) => member_layout,
+                    None => {
+                        return Err(CompileError::unexpect(format!(
+                            "no member {} in {}",
+                            member, ty
+                        )))
+                    }
+                };
+                Ok((
+                    self.member_location(pointer, member_layout),
+                    member_layout.ty.clone(),
+                    qualifiers.merge(member_layout.qualifiers),
+                ))
+            }
+            Exp16::Parenthesis(expression) => match single_exp15(expression) {
+                Some(exp) => self.emit_unary_lvalue(exp, environment),
+                None => Err(CompileError::unexpect(format!(
+                    "{} is not an lvalue",
+                    lvalue_name(node)
+                ))),
+            },
+            _ => Err(CompileError::unexpect(format!(
+                "{} is not an lvalue",
+                lvalue_name(node)
+            ))),
+        }
+    }
+
+    /// get the object designated by an lvalue which can be an indirection (e.g. `*p`)
+    ///
+    /// the object which a pointer points to has the qualifiers of the pointed-to type
+    fn emit_unary_lvalue(
+        &self,
+        node: &Exp15,
+        environment: &Environment<'ctx>,
+    ) -> Result<(Location<'ctx>, Type, Qualifiers)> {
+        match node {
+            Exp15::Single(exp) => self.emit_lvalue(exp, environment),
+            Exp15::Indirection(exp) => {
+                let value = self.emit_exp15(exp.as_ref().clone(), environment)?;
+                match value.ty {
+                    Type::Pointer(ty, qualifiers) if !ty.is_function() => Ok((
+                        Location::Object(value.value.into_pointer_value()),
+                        *ty,
+                        qualifiers,
+                    )),
+                    ty => Err(CompileError::unexpect(format!("indirection of {}", ty))),
+                }
+            }
+            _ => Err(CompileError::unexpect(format!(
+                "{} is not an lvalue",
+                unary_lvalue_name(node)
+            ))),
+        }
+    }
+
+    /// get the object designated by a member of the structure which a pointer points to
+    fn member_location(
+        &self,
+        pointer: PointerValue<'ctx>,
+        member_layout: &MemberLayout,
+    ) -> Location<'ctx> {
+        // members are addressed by their offsets in bytes
+        let i8_pointer = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        let pointer = self
+            .builder
+            .build_pointer_cast(pointer, i8_pointer, "struct");
+        let offset = self
+            .context
+            .i64_type()
+            .const_int(member_layout.offset, false);
+        let pointer = unsafe {
+            self.builder
+                .build_in_bounds_gep(pointer, &[offset], "member")
+        };
+        let member_pointer = self
+            .llvm_type(&member_layout.ty)
+            .ptr_type(AddressSpace::Generic);
+        let pointer = self
+            .builder
+            .build_pointer_cast(pointer, member_pointer, "member");
+        match member_layout.bit_field {
+            Some((bit_offset, width)) => Location::BitField(pointer, bit_offset, width),
+            None => Location::Object(pointer),
+        }
+    }
+
+    /// load the value of an object or a bit-field
+    fn emit_load_location(
+        &self,
+        location: &Location<'ctx>,
+        ty: &Type,
+        qualifiers: Qualifiers,
+    ) -> Result<Value<'ctx>> {
+        match location {
+            Location::Object(pointer) => Ok(Value {
+                value: self.emit_load(*pointer, qualifiers),
+                ty: ty.clone(),
+            }),
+            Location::BitField(pointer, bit_offset, width) => {
+                let unit = self.emit_load(*pointer, qualifiers).into_int_value();
+                let integer_type = self.expect_integer(ty)?;
+                Ok(self.extract_bit_field(unit, integer_type, *bit_offset, *width))
+            }
+        }
+    }
+
+    /// convert a value to the type of an object or a bit-field and store it
+    ///
+    /// a bit-field is stored by read-modify-write of its storage unit.
+    /// returns the value of the object after the store
+    fn emit_store_location(
+        &self,
+        location: &Location<'ctx>,
+        ty: &Type,
+        value: Value<'ctx>,
+        qualifiers: Qualifiers,
+    ) -> Result<Value<'ctx>> {
+        let value = self.convert(value, ty)?;
+        match location {
+            Location::Object(pointer) => {
+                self.emit_store(*pointer, value, qualifiers);
+                Ok(Value {
+                    value,
+                    ty: ty.clone(),
+                })
+            }
+            Location::BitField(pointer, bit_offset, width) => {
+                let integer_type = self.expect_integer(ty)?;
+                let unit = self.emit_load(*pointer, qualifiers).into_int_value();
+                let unit_type = unit.get_type();
+                let mask = (u64::MAX >> (64 - width)) << bit_offset;
+                let bits = self.builder.build_left_shift(
+                    value.into_int_value(),
+                    unit_type.const_int(*bit_offset as u64, false),
+                    "bit_field",
+                );
+                let bits =
+                    self.builder
+                        .build_and(bits, unit_type.const_int(mask, false), "bit_field");
+                let rest = self
+                    .builder
+                    .build_and(unit, unit_type.const_int(!mask, false), "unit");
+                let unit = self.builder.build_or(rest, bits, "unit");
+                self.emit_store(*pointer, unit.into(), qualifiers);
+                Ok(self.extract_bit_field(unit, integer_type, *bit_offset, *width))
+            }
+        }
+    }
+
+    /// get the value of a bit-field in its storage unit
+    fn extract_bit_field(
+        &self,
+        unit: IntValue<'ctx>,
+        integer_type: IntegerType,
+        bit_offset: u32,
+        width: u32,
+    ) -> Value<'ctx> {
+        // shift the bit-field to the most significant bits and back to extend its sign
+        let unit_type = unit.get_type();
+        let bit_width = integer_type.bit_width();
+        let left = unit_type.const_int((bit_width - bit_offset - width) as u64, false);
+        let value = self.builder.build_left_shift(unit, left, "bit_field");
+        let right = unit_type.const_int((bit_width - width) as u64, false);
+        let value = self
+            .builder
+            .build_right_shift(value, right, integer_type.signed, "bit_field");
+        // a bit-field narrower than int is promoted to int which can represent all of its values
+        let int = IntegerType::int();
+        if integer_type.kind <= IntegerKind::Int && width < int.bit_width() {
+            Value {
+                value: self.convert_integer(value, integer_type, int).into(),
+                ty: Type::Integer(int),
+            }
+        } else {
+            Value {
+                value: value.into(),
+                ty: Type::Integer(integer_type),
+            }
+        }
+    }
+}
+
+/// size_t is unsigned long on x86-64
+fn size_type() -> IntegerType {
+    IntegerType::new(IntegerKind::Long, false)
+}
+
+/// round up an offset to a multiple of the alignment
+fn round_up(offset: u64, align: u64) -> u64 {
+    offset + (align - offset % align) % align
+}
+
+/// get the name of an lvalue for diagnostics (e.g. `s.flags`)
+fn lvalue_name(exp: &Exp16) -> String {
+    match exp {
+        Exp16::Identifier(identifier, _) => identifier.to_string(),
+        Exp16::Member(exp, member) => format!("{}.{}", lvalue_name(exp), member),
+        Exp16::Parenthesis(expression) => match single_exp15(expression) {
+            Some(Exp15::Single(exp)) => lvalue_name(exp),
+            Some(exp) => format!("({})", unary_lvalue_name(exp)),
+            None => "expression".to_owned(),
+        },
+        _ => "expression".to_owned(),
+    }
+}
+
+/// get the name of an lvalue which can be an indirection (e.g. `*p`)
+fn unary_lvalue_name(exp: &Exp15) -> String {
+    match exp {
+        Exp15::Single(exp) => lvalue_name(exp),
+        Exp15::Indirection(exp) => format!("*{}", unary_lvalue_name(exp)),
+        _ => "expression".to_owned(),
+    }
+}
+
+/// get the span of the variable which an lvalue is in (e.g. `s` of `s.flags`)
+fn lvalue_span(exp: &Exp16) -> Option<Span> {
+    match exp {
+        Exp16::Identifier(_, span) => Some(*span),
+        Exp16::Member(exp, _) => lvalue_span(exp),
+        Exp16::Parenthesis(expression) => single_exp15(expression).and_then(unary_lvalue_span),
+        _ => None,
+    }
+}
+
+/// get the span of the variable which an lvalue is in (e.g. `p` of `*p`)
+fn unary_lvalue_span(exp: &Exp15) -> Option<Span> {
+    match exp {
+        Exp15::Single(exp) => lvalue_span(exp),
+        Exp15::Indirection(exp) => unary_lvalue_span(exp),
+        _ => None,
+    }
+}
+
+/// get the value of an expression which is a single floating constant
+fn floating_constant(expression: &Expression) -> Option<f64> {
+    match single_exp16(expression) {
+        Some(Exp16::Floating(value, _)) => Some(*value),
+        _ => None,
+    }
+}
+
+/// get the expression of a scalar initializer, which can be enclosed in braces
+fn scalar_initializer(initializer: Initializer) -> Option<Expression> {
+    match initializer {
+        Initializer::Expression(expression) => Some(expression),
+        Initializer::List(initializers) => {
+            let mut initializers = initializers.into_iter();
+            match (initializers.next(), initializers.next()) {
+                (Some(initializer), None) => scalar_initializer(initializer),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// get the unary expression of an expression without binary operators
+fn single_exp15(expression: &Expression) -> Option<&Exp15> {
+    match &expression.expression {
+        Exp2::Single(Exp11::Single(Exp12::Single(Exp13::Single(exp)))) => Some(exp),
+        _ => None,
+    }
+}
+
+/// get the primary expression of an expression without operators
+fn single_exp16(expression: &Expression) -> Option<&Exp16> {
+    match single_exp15(expression) {
+        Some(Exp15::Single(exp)) => Some(exp),
+        _ => None,
+    }
+}
+
+/// get the function named by an expression which is a function designator or its address
+fn function_designator(expression: &Expression) -> Option<Symbol> {
+    match single_exp15(expression)? {
+        Exp15::Single(Exp16::Identifier(identifier, _)) => Some(*identifier),
+        Exp15::Address(exp) => match exp.as_ref() {
+            Exp15::Single(Exp16::Identifier(identifier, _)) => Some(*identifier),
+            _ => None,
+        },
+        _ => None,
+    }
 }
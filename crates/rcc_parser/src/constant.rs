@@ -0,0 +1,301 @@
+use super::error::ParseError;
+use super::expression::{Exp11, Exp12, Exp13, Exp15, Exp16, Exp2, Expression};
+use super::statement::TypeStruct;
+use rcc_syntax::symbol::Symbol;
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+/// identifiers which can appear in a constant expression (e.g. enumerators) and sizes of types
+pub trait ConstantScope {
+    fn get_constant(&self, identifier: Symbol) -> Option<i64>;
+    /// size in bytes of the type named by a type name (None unless it is a complete object type)
+    fn size_of_type(&self, type_name: &TypeStruct) -> Option<u64>;
+    /// size in bytes of the type of an expression, which is not evaluated
+    fn size_of_expression(&self, exp: &Exp15) -> Option<u64>;
+    /// alignment in bytes of the type named by a type name (None unless it is a complete object type)
+    fn align_of_type(&self, type_name: &TypeStruct) -> Option<u64>;
+}
+
+/// evaluate an integer constant expression
+pub fn evaluate(expression: &Expression, scope: &dyn ConstantScope) -> Result<i64> {
+    evaluate_exp2(&expression.expression, scope)
+}
+
+fn evaluate_exp2(node: &Exp2, scope: &dyn ConstantScope) -> Result<i64> {
+    match node {
+        Exp2::Single(exp) => evaluate_exp11(exp, scope),
+        Exp2::Eq(_) => Err(ParseError::NotConstant("assignment".to_owned())),
+    }
+}
+
+fn overflow(operation: &str) -> ParseError {
+    ParseError::NotConstant(format!("overflowing {}", operation))
+}
+
+fn evaluate_exp11(node: &Exp11, scope: &dyn ConstantScope) -> Result<i64> {
+    match node {
+        Exp11::Single(exp) => evaluate_exp12(exp, scope),
+        Exp11::Shl(lhs, rhs) => {
+            let (lhs, rhs) = (evaluate_exp11(lhs, scope)?, evaluate_exp12(rhs, scope)?);
+            if !(0..64).contains(&rhs) {
+                return Err(overflow("shift"));
+            }
+            Ok(lhs << rhs)
+        }
+        Exp11::Shr(lhs, rhs) => {
+            let (lhs, rhs) = (evaluate_exp11(lhs, scope)?, evaluate_exp12(rhs, scope)?);
+            if !(0..64).contains(&rhs) {
+                return Err(overflow("shift"));
+            }
+            Ok(lhs >> rhs)
+        }
+    }
+}
+
+fn evaluate_exp12(node: &Exp12, scope: &dyn ConstantScope) -> Result<i64> {
+    match node {
+        Exp12::Single(exp) => evaluate_exp13(exp, scope),
+        Exp12::Add(lhs, rhs) => evaluate_exp12(lhs, scope)?
+            .checked_add(evaluate_exp13(rhs, scope)?)
+            .ok_or_else(|| overflow("addition")),
+        Exp12::Sub(lhs, rhs) => evaluate_exp12(lhs, scope)?
+            .checked_sub(evaluate_exp13(rhs, scope)?)
+            .ok_or_else(|| overflow("subtraction")),
+    }
+}
+
+fn evaluate_exp13(node: &Exp13, scope: &dyn ConstantScope) -> Result<i64> {
+    match node {
+        Exp13::Single(exp) => evaluate_exp15(exp, scope),
+        Exp13::Mul(lhs, rhs) => evaluate_exp13(lhs, scope)?
+            .checked_mul(evaluate_exp15(rhs, scope)?)
+            .ok_or_else(|| overflow("multiplication")),
+        Exp13::Div(lhs, rhs) => evaluate_exp13(lhs, scope)?
+            .checked_div(evaluate_exp15(rhs, scope)?)
+            .ok_or_else(|| ParseError::NotConstant("division by zero".to_owned())),
+        Exp13::Rem(lhs, rhs) => evaluate_exp13(lhs, scope)?
+            .checked_rem(evaluate_exp15(rhs, scope)?)
+            .ok_or_else(|| ParseError::NotConstant("division by zero".to_owned())),
+    }
+}
+
+fn evaluate_exp15(node: &Exp15, scope: &dyn ConstantScope) -> Result<i64> {
+    match node {
+        Exp15::Single(exp) => evaluate_exp16(exp, scope),
+        Exp15::Address(_) => Err(ParseError::NotConstant("address".to_owned())),
+        Exp15::Indirection(_) => Err(ParseError::NotConstant("indirection".to_owned())),
+        Exp15::Plus(exp) => evaluate_exp15(exp, scope),
+        Exp15::Minus(exp) => evaluate_exp15(exp, scope)?
+            .checked_neg()
+            .ok_or_else(|| overflow("negation")),
+        Exp15::Sizeof(exp) => match scope.size_of_expression(exp) {
+            Some(size) => Ok(size as i64),
+            None => Err(ParseError::NotConstant(
+                "sizeof of an expression without complete type".to_owned(),
+            )),
+        },
+        Exp15::SizeofType(type_name) => match scope.size_of_type(type_name) {
+            Some(size) => Ok(size as i64),
+            None => Err(ParseError::NotConstant(format!(
+                "sizeof {}",
+                type_name.get_base()
+            ))),
+        },
+        Exp15::AlignofType(type_name) => match scope.align_of_type(type_name) {
+            Some(align) => Ok(align as i64),
+            None => Err(ParseError::NotConstant(format!(
+                "_Alignof {}",
+                type_name.get_base()
+            ))),
+        },
+    }
+}
+
+fn evaluate_exp16(node: &Exp16, scope: &dyn ConstantScope) -> Result<i64> {
+    match node {
+        Exp16::Number(num, _) => Ok(*num as i64),
+        Exp16::Floating(num, _) => Err(ParseError::NotConstant(format!("floating {}", num))),
+        Exp16::Identifier(identifier, _) => scope
+            .get_constant(*identifier)
+            .ok_or_else(|| ParseError::NotConstant(identifier.to_string())),
+        Exp16::Parenthesis(expression) => evaluate(expression, scope),
+        Exp16::FunctionCall(..) => Err(ParseError::NotConstant("function call".to_owned())),
+        Exp16::Member(..) => Err(ParseError::NotConstant("member access".to_owned())),
+        Exp16::VaStart(_) | Exp16::VaArg(..) | Exp16::VaEnd(_) => {
+            Err(ParseError::NotConstant("variable argument".to_owned()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::testutil::*;
+    use super::super::typedef::TypedefTable;
+    use super::super::types::Type;
+    use super::*;
+    use rcc_syntax::token::{Keyword, Punct, Token};
+    use rcc_syntax::tokens::Tokens;
+    use std::collections::HashMap;
+
+    impl ConstantScope for HashMap<String, i64> {
+        fn get_constant(&self, identifier: Symbol) -> Option<i64> {
+            self.get(identifier.as_str()).copied()
+        }
+        fn size_of_type(&self, type_name: &TypeStruct) -> Option<u64> {
+            match type_name.get_base() {
+                _ if type_name.get_pointer() > 0 => Some(8),
+                Type::Integer(integer_type) => Some(integer_type.size() as u64),
+                _ => None,
+            }
+        }
+        fn size_of_expression(&self, _exp: &Exp15) -> Option<u64> {
+            None
+        }
+        fn align_of_type(&self, type_name: &TypeStruct) -> Option<u64> {
+            self.size_of_type(type_name)
+        }
+    }
+
+    fn run(tokens: Vec<Token>, scope: &HashMap<String, i64>) -> Result<i64> {
+        let mut tokens = Tokens::new(tokens.into_iter().map(mtoken).collect());
+        let expression = Expression::parse_constant(&mut tokens, &TypedefTable::new())?;
+        evaluate(&expression, scope)
+    }
+
+    #[test]
+    fn add_mul() {
+        // 1 + 2 * 3
+        let actual = run(
+            vec![
+                number_token(1),
+                Token::Punct(Punct::Plus),
+                number_token(2),
+                Token::Punct(Punct::Star),
+                number_token(3),
+            ],
+            &HashMap::new(),
+        );
+        assert_eq!(actual, Ok(7));
+    }
+
+    #[test]
+    fn sub_div_rem_shift() {
+        // 20 - 7 / 2 % 2 << 1 >> 2
+        let actual = run(
+            vec![
+                number_token(20),
+                Token::Punct(Punct::Minus),
+                number_token(7),
+                Token::Punct(Punct::Slash),
+                number_token(2),
+                Token::Punct(Punct::Percent),
+                number_token(2),
+                Token::Punct(Punct::LeftShift),
+                number_token(1),
+                Token::Punct(Punct::RightShift),
+                number_token(2),
+            ],
+            &HashMap::new(),
+        );
+        assert_eq!(actual, Ok(9));
+    }
+
+    #[test]
+    fn division_by_zero() {
+        let actual = run(
+            vec![number_token(1), Token::Punct(Punct::Slash), number_token(0)],
+            &HashMap::new(),
+        );
+        assert_eq!(
+            actual,
+            Err(ParseError::NotConstant("division by zero".to_owned()))
+        );
+    }
+
+    #[test]
+    fn with_constant_identifier() {
+        // RED * 4
+        let mut scope = HashMap::new();
+        scope.insert("RED".to_owned(), 2);
+        let actual = run(
+            vec![
+                Token::Identifier(Symbol::intern("RED")),
+                Token::Punct(Punct::Star),
+                number_token(4),
+            ],
+            &scope,
+        );
+        assert_eq!(actual, Ok(8));
+    }
+
+    #[test]
+    fn sizeof_and_negation() {
+        // -sizeof(long) * 2 + -(-3) - sizeof(char *)
+        let punct = |punct: &str| Token::Punct(Punct::from_spelling(punct).unwrap());
+        let actual = run(
+            vec![
+                punct("-"),
+                Token::Keyword(Keyword::Sizeof),
+                punct("("),
+                Token::Keyword(Keyword::Long),
+                punct(")"),
+                punct("*"),
+                number_token(2),
+                punct("+"),
+                punct("-"),
+                punct("("),
+                punct("-"),
+                number_token(3),
+                punct(")"),
+                punct("-"),
+                Token::Keyword(Keyword::Sizeof),
+                punct("("),
+                Token::Keyword(Keyword::Char),
+                punct("*"),
+                punct(")"),
+            ],
+            &HashMap::new(),
+        );
+        assert_eq!(actual, Ok(-21));
+    }
+
+    #[test]
+    fn alignof() {
+        // _Alignof(short) << 3
+        let actual = run(
+            vec![
+                Token::Keyword(Keyword::Alignof),
+                Token::Punct(Punct::LeftParen),
+                Token::Keyword(Keyword::Short),
+                Token::Punct(Punct::RightParen),
+                Token::Punct(Punct::LeftShift),
+                number_token(3),
+            ],
+            &HashMap::new(),
+        );
+        assert_eq!(actual, Ok(16));
+    }
+
+    #[test]
+    fn sizeof_without_complete_type() {
+        // sizeof a
+        let actual = run(
+            vec![
+                Token::Keyword(Keyword::Sizeof),
+                Token::Identifier(Symbol::intern("a")),
+            ],
+            &HashMap::new(),
+        );
+        assert!(matches!(actual, Err(ParseError::NotConstant(_))));
+    }
+
+    #[test]
+    fn with_unknown_identifier() {
+        let actual = run(
+            vec![Token::Identifier(Symbol::intern("a"))],
+            &HashMap::new(),
+        );
+        assert_eq!(actual, Err(ParseError::NotConstant("a".to_owned())));
+    }
+}
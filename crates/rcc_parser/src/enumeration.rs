@@ -0,0 +1,194 @@
+use super::error::ParseError;
+use super::expression::Expression;
+use super::typedef::TypedefTable;
+use super::util::get_space;
+use rcc_syntax::span::Span;
+use rcc_syntax::symbol::Symbol;
+use rcc_syntax::token::{Keyword, Punct, Token};
+use rcc_syntax::tokens::Tokens;
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Enumerator {
+    pub identifier: Symbol,
+    pub value: Option<Expression>,
+    pub span: Span,
+}
+impl Enumerator {
+    /// enumerator := Token::Identifier (Token::Punct(Punct::Assign) constant_expression)?
+    fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<Enumerator> {
+        let checkpoint = tokens.checkpoint();
+        let identifier = tokens.consume_identifier()?.get_token().get_identifier()?;
+        let mut value = None;
+        if let Some(_token) = tokens.check_next_punct(Punct::Assign) {
+            tokens.next(); // consume "="
+            value = Some(Expression::parse_constant(tokens, typedefs)?);
+        }
+        Ok(Enumerator {
+            identifier,
+            value,
+            span: tokens.span_since(checkpoint),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnumDeclaration {
+    pub tag: Option<Symbol>,
+    pub enumerators: Vec<Enumerator>,
+    pub span: Span,
+}
+impl EnumDeclaration {
+    /// check whether the next tokens start an enum declaration (not `enum tag identifier`)
+    pub fn is_next(tokens: &Tokens) -> bool {
+        let is_next = |n, token: &Token| match tokens.peek_nth(n) {
+            Some(next) => next.get_token() == token,
+            None => false,
+        };
+        if !is_next(0, &Token::Keyword(Keyword::Enum)) {
+            return false;
+        }
+        // the tag is optional
+        let brace = match tokens.peek_nth(1).map(|token| token.get_token()) {
+            Some(Token::Identifier(_)) => 2,
+            _ => 1,
+        };
+        is_next(brace, &Token::Punct(Punct::LeftBrace))
+    }
+
+    /// parse and get enum_declaration
+    ///
+    /// enum_declaration :=
+    /// Token::Keyword(Keyword::Enum) Token::Identifier?
+    ///     Token::Punct(Punct::LeftBrace)
+    ///         enumerator (Token::Punct(Punct::Comma) enumerator)* Token::Punct(Punct::Comma)?
+    ///     Token::Punct(Punct::RightBrace)
+    /// Token::Punct(Punct::Semicolon)
+    pub fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<EnumDeclaration> {
+        let checkpoint = tokens.checkpoint();
+        tokens.consume_keyword(Keyword::Enum)?;
+        let tag = match tokens.peek() {
+            Some(token) => match token.get_token() {
+                Token::Identifier(_) => Some(tokens.next().unwrap().get_token().get_identifier()?),
+                _ => None,
+            },
+            None => None,
+        };
+        tokens.consume_punct(Punct::LeftBrace)?;
+
+        let mut enumerators = vec![Enumerator::parse(tokens, typedefs)?];
+        loop {
+            if let Some(token) = tokens.peek() {
+                if let Token::Punct(Punct::Comma) = token.get_token() {
+                    tokens.next(); // consume ,
+                    if let Some(token) = tokens.peek() {
+                        if let Token::Identifier(_) = token.get_token() {
+                            enumerators.push(Enumerator::parse(tokens, typedefs)?);
+                            continue;
+                        }
+                    }
+                }
+            }
+            break;
+        }
+
+        tokens.consume_punct(Punct::RightBrace)?;
+        tokens.consume_punct(Punct::Semicolon)?;
+        Ok(EnumDeclaration {
+            tag,
+            enumerators,
+            span: tokens.span_since(checkpoint),
+        })
+    }
+
+    pub fn to_string(&self, tab_level: u32) -> String {
+        let mut s = format!(
+            "{}enum_declaration -> tag {:?}\n",
+            get_space(tab_level),
+            self.tag.map(Symbol::as_str)
+        );
+        for enumerator in self.enumerators.iter() {
+            s += &format!("{}{}\n", get_space(tab_level + 1), enumerator.identifier);
+            if let Some(value) = &enumerator.value {
+                s += &format!("{}\n", value.to_string(tab_level + 2));
+            }
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::expression::{Exp11, Exp12, Exp13, Exp15, Exp16, Exp2};
+    use super::super::testutil::*;
+    use super::*;
+    use crate::types::IntegerType;
+
+    fn constant(num: u64) -> Expression {
+        Expression {
+            expression: Exp2::Single(Exp11::Single(Exp12::Single(Exp13::Single(Exp15::Single(
+                Exp16::Number(num, IntegerType::int()),
+            ))))),
+            span: Span::default(),
+        }
+    }
+
+    #[test]
+    fn implicit_and_explicit_values() {
+        // enum color { RED, GREEN = 5, BLUE, };
+        let mut tokens = Tokens::new(vec![
+            mtoken(Token::Keyword(Keyword::Enum)),
+            mtoken(Token::Identifier(Symbol::intern("color"))),
+            mtoken(Token::Punct(Punct::LeftBrace)),
+            mtoken(Token::Identifier(Symbol::intern("RED"))),
+            mtoken(Token::Punct(Punct::Comma)),
+            mtoken(Token::Identifier(Symbol::intern("GREEN"))),
+            mtoken(Token::Punct(Punct::Assign)),
+            mtoken(number_token(5)),
+            mtoken(Token::Punct(Punct::Comma)),
+            mtoken(Token::Identifier(Symbol::intern("BLUE"))),
+            mtoken(Token::Punct(Punct::Comma)),
+            mtoken(Token::Punct(Punct::RightBrace)),
+            mtoken(Token::Punct(Punct::Semicolon)),
+        ]);
+        assert!(EnumDeclaration::is_next(&tokens));
+        let actual = EnumDeclaration::parse(&mut tokens, &TypedefTable::new()).unwrap();
+        let expect = EnumDeclaration {
+            tag: Some(Symbol::intern("color")),
+            enumerators: vec![
+                Enumerator {
+                    identifier: Symbol::intern("RED"),
+                    value: None,
+                    span: Span::default(),
+                },
+                Enumerator {
+                    identifier: Symbol::intern("GREEN"),
+                    value: Some(constant(5)),
+                    span: Span::default(),
+                },
+                Enumerator {
+                    identifier: Symbol::intern("BLUE"),
+                    value: None,
+                    span: Span::default(),
+                },
+            ],
+            span: Span::default(),
+        };
+        assert_eq!(actual, expect);
+        assert_eq!(tokens.len(), 0);
+    }
+
+    #[test]
+    fn enum_typed_variable_is_not_a_declaration() {
+        // enum color c;
+        let tokens = Tokens::new(vec![
+            mtoken(Token::Keyword(Keyword::Enum)),
+            mtoken(Token::Identifier(Symbol::intern("color"))),
+            mtoken(Token::Identifier(Symbol::intern("c"))),
+            mtoken(Token::Punct(Punct::Semicolon)),
+        ]);
+        assert!(!EnumDeclaration::is_next(&tokens));
+    }
+}
@@ -1,3 +1,4 @@
+use rcc_syntax::span::Span;
 use rcc_syntax::token::{ManagedToken, TokenError};
 use rcc_syntax::tokens::ConsumeError;
 use std::error::Error;
@@ -8,26 +9,73 @@ pub enum ParseError {
     Consume(ConsumeError),
     Token(TokenError),
     Unexpect(Option<ManagedToken>),
+    NotConstant(String),
+    InvalidInteger(ManagedToken, String),
+}
+
+impl ParseError {
+    /// span of the token where the error is found
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ParseError::Consume(ConsumeError::Consume(Some(token)))
+            | ParseError::Unexpect(Some(token))
+            | ParseError::InvalidInteger(token, _) => Some(token.get_span()),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            ParseError::Consume(err) => write!(f, "parse error: {}", err.to_string()),
+            ParseError::Consume(err) => {
+                write!(f, "parse error: {}", err.to_string())?;
+                match err {
+                    ConsumeError::Consume(Some(token)) => write_expansion(f, token),
+                    ConsumeError::Consume(None) => Ok(()),
+                }
+            }
             ParseError::Token(err) => write!(f, "token error: {}", err.to_string()),
             ParseError::Unexpect(optional_token) => {
-                write!(f, "parse error: unexpected token {:?}", optional_token)
+                write!(f, "parse error: unexpected token {:?}", optional_token)?;
+                match optional_token {
+                    Some(token) => write_expansion(f, token),
+                    None => Ok(()),
+                }
+            }
+            ParseError::NotConstant(cause) => {
+                write!(f, "parse error: {} is not a constant expression", cause)
+            }
+            ParseError::InvalidInteger(token, cause) => {
+                write!(f, "parse error: integer constant {:?} {}", token, cause)?;
+                write_expansion(f, token)
             }
         }
     }
 }
 
+/// note where a token from a macro expansion is expanded and which macros it comes from
+fn write_expansion(f: &mut fmt::Formatter, token: &ManagedToken) -> fmt::Result {
+    match token.get_expansion() {
+        Some(expansion) => write!(
+            f,
+            " at line {} location {} ({})",
+            token.get_line(),
+            token.get_location(),
+            expansion
+        ),
+        None => Ok(()),
+    }
+}
+
 impl Error for ParseError {
     fn description(&self) -> &str {
         match *self {
             ParseError::Consume(_) => "parse error: consuming tokens",
             ParseError::Token(_) => "parse error: consuming tokens",
             ParseError::Unexpect(_) => "parse error: unexpected token",
+            ParseError::NotConstant(_) => "parse error: not a constant expression",
+            ParseError::InvalidInteger(..) => "parse error: invalid integer constant",
         }
     }
 }
@@ -1,8 +1,14 @@
 use super::error::ParseError;
+use super::statement::TypeStruct;
+use super::typedef::TypedefTable;
+use super::types::{FloatingType, IntegerType, Qualifiers};
 use super::util::get_space;
-use rcc_syntax::token::Token;
+use rcc_syntax::span::Span;
+use rcc_syntax::symbol::Symbol;
+use rcc_syntax::token::{character_value, Keyword, ManagedToken, Punct, Token};
 use rcc_syntax::tokens::Tokens;
 use std::fmt;
+use std::num::IntErrorKind;
 
 type Result<T> = std::result::Result<T, ParseError>;
 
@@ -31,11 +37,26 @@ impl fmt::Display for Operator {
 #[derive(Clone, Debug, PartialEq)]
 pub struct Expression {
     pub expression: Exp2,
+    pub span: Span, // tokens of the expression
 }
 impl Expression {
-    pub fn parse(tokens: &mut Tokens) -> Result<Expression> {
+    pub fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<Expression> {
+        let checkpoint = tokens.checkpoint();
+        let expression = Exp2::parse(tokens, typedefs)?;
         Ok(Expression {
-            expression: Exp2::parse(tokens)?,
+            expression,
+            span: tokens.span_since(checkpoint),
+        })
+    }
+    /// parse and get constant_expression
+    ///
+    /// constant_expression := exp11
+    pub fn parse_constant(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<Expression> {
+        let checkpoint = tokens.checkpoint();
+        let expression = Exp2::Single(Exp11::parse(tokens, typedefs)?);
+        Ok(Expression {
+            expression,
+            span: tokens.span_since(checkpoint),
         })
     }
     pub fn to_string(&self, space_num: u32) -> String {
@@ -43,17 +64,27 @@ impl Expression {
     }
 }
 
+fn binary_to_string(space_num: u32, operator: &str, lhs: String, rhs: String) -> String {
+    format!(
+        "{}operator: {}\n{}\n{}\n",
+        get_space(space_num),
+        operator,
+        lhs,
+        rhs
+    )
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Exp2 {
-    Single(Exp12),
-    Eq(Vec<Exp12>),
+    Single(Exp11),
+    Eq(Vec<Exp11>),
 }
 impl Exp2 {
-    fn parse(tokens: &mut Tokens) -> Result<Exp2> {
-        let mut operand = vec![Exp12::parse(tokens)?];
-        while let Some(_token) = tokens.check_next_operator("=") {
+    fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<Exp2> {
+        let mut operand = vec![Exp11::parse(tokens, typedefs)?];
+        while let Some(_token) = tokens.check_next_punct(Punct::Assign) {
             tokens.next(); // consume "="
-            operand.push(Exp12::parse(tokens)?);
+            operand.push(Exp11::parse(tokens, typedefs)?);
         }
         if operand.len() == 1 {
             Ok(Exp2::Single(operand.remove(0)))
@@ -75,126 +106,462 @@ impl Exp2 {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum Exp11 {
+    Single(Exp12),
+    Shl(Box<Exp11>, Exp12),
+    Shr(Box<Exp11>, Exp12),
+}
+impl Exp11 {
+    /// exp11 := exp12 ((Token::Punct(Punct::LeftShift) | Token::Punct(Punct::RightShift)) exp12)*
+    fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<Exp11> {
+        let mut exp = Exp11::Single(Exp12::parse(tokens, typedefs)?);
+        loop {
+            if let Some(_token) = tokens.check_next_punct(Punct::LeftShift) {
+                tokens.next(); // consume "<<"
+                exp = Exp11::Shl(Box::new(exp), Exp12::parse(tokens, typedefs)?);
+            } else if let Some(_token) = tokens.check_next_punct(Punct::RightShift) {
+                tokens.next(); // consume ">>"
+                exp = Exp11::Shr(Box::new(exp), Exp12::parse(tokens, typedefs)?);
+            } else {
+                return Ok(exp);
+            }
+        }
+    }
+    pub fn to_string(&self, space_num: u32) -> String {
+        match self {
+            Exp11::Single(exp) => exp.to_string(space_num),
+            Exp11::Shl(lhs, rhs) => binary_to_string(
+                space_num,
+                "<<",
+                lhs.to_string(space_num + 1),
+                rhs.to_string(space_num + 1),
+            ),
+            Exp11::Shr(lhs, rhs) => binary_to_string(
+                space_num,
+                ">>",
+                lhs.to_string(space_num + 1),
+                rhs.to_string(space_num + 1),
+            ),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Exp12 {
     Single(Exp13),
-    Add(Vec<Exp13>),
+    Add(Box<Exp12>, Exp13),
+    Sub(Box<Exp12>, Exp13),
 }
 impl Exp12 {
-    fn parse(tokens: &mut Tokens) -> Result<Exp12> {
-        let mut operand = vec![Exp13::parse(tokens)?];
-        while let Some(_token) = tokens.check_next_operator("+") {
-            tokens.next(); // consume "+"
-            operand.push(Exp13::parse(tokens)?);
-        }
-        if operand.len() == 1 {
-            Ok(Exp12::Single(operand.remove(0)))
-        } else {
-            Ok(Exp12::Add(operand))
+    /// exp12 := exp13 ((Token::Punct(Punct::Plus) | Token::Punct(Punct::Minus)) exp13)*
+    fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<Exp12> {
+        let mut exp = Exp12::Single(Exp13::parse(tokens, typedefs)?);
+        loop {
+            if let Some(_token) = tokens.check_next_punct(Punct::Plus) {
+                tokens.next(); // consume "+"
+                exp = Exp12::Add(Box::new(exp), Exp13::parse(tokens, typedefs)?);
+            } else if let Some(_token) = tokens.check_next_punct(Punct::Minus) {
+                tokens.next(); // consume "-"
+                exp = Exp12::Sub(Box::new(exp), Exp13::parse(tokens, typedefs)?);
+            } else {
+                return Ok(exp);
+            }
         }
     }
     pub fn to_string(&self, space_num: u32) -> String {
         match self {
             Exp12::Single(exp) => format!("{}", exp.to_string(space_num)),
-            Exp12::Add(exps) => {
-                let mut s = format!("{}operator: +\n", get_space(space_num));
-                for exp in exps {
-                    s += &format!("{}\n", exp.to_string(space_num + 1))
-                }
-                s
-            }
+            Exp12::Add(lhs, rhs) => binary_to_string(
+                space_num,
+                "+",
+                lhs.to_string(space_num + 1),
+                rhs.to_string(space_num + 1),
+            ),
+            Exp12::Sub(lhs, rhs) => binary_to_string(
+                space_num,
+                "-",
+                lhs.to_string(space_num + 1),
+                rhs.to_string(space_num + 1),
+            ),
         }
     }
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Exp13 {
-    Single(Exp16),
-    Mul(Vec<Exp16>),
+    Single(Exp15),
+    Mul(Box<Exp13>, Exp15),
+    Div(Box<Exp13>, Exp15),
+    Rem(Box<Exp13>, Exp15),
 }
 impl Exp13 {
-    fn parse(tokens: &mut Tokens) -> Result<Exp13> {
-        let mut operand = vec![Exp16::parse(tokens)?];
-        while let Some(_token) = tokens.check_next_operator("*") {
-            tokens.next(); // consume "*"
-            operand.push(Exp16::parse(tokens)?);
-        }
-        if operand.len() == 1 {
-            Ok(Exp13::Single(operand.remove(0)))
-        } else {
-            Ok(Exp13::Mul(operand))
+    /// exp13 := exp15 ((Token::Punct(Punct::Star) | Token::Punct(Punct::Slash) | Token::Punct(Punct::Percent)) exp15)*
+    fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<Exp13> {
+        let mut exp = Exp13::Single(Exp15::parse(tokens, typedefs)?);
+        loop {
+            if let Some(_token) = tokens.check_next_punct(Punct::Star) {
+                tokens.next(); // consume "*"
+                exp = Exp13::Mul(Box::new(exp), Exp15::parse(tokens, typedefs)?);
+            } else if let Some(_token) = tokens.check_next_punct(Punct::Slash) {
+                tokens.next(); // consume "/"
+                exp = Exp13::Div(Box::new(exp), Exp15::parse(tokens, typedefs)?);
+            } else if let Some(_token) = tokens.check_next_punct(Punct::Percent) {
+                tokens.next(); // consume "%"
+                exp = Exp13::Rem(Box::new(exp), Exp15::parse(tokens, typedefs)?);
+            } else {
+                return Ok(exp);
+            }
         }
     }
     pub fn to_string(&self, space_num: u32) -> String {
         match self {
             Exp13::Single(exp) => format!("{}", exp.to_string(space_num)),
-            Exp13::Mul(exps) => {
-                let mut s = format!("{}operator: *\n", get_space(space_num));
-                for exp in exps {
-                    s += &format!("{}\n", exp.to_string(space_num + 1))
+            Exp13::Mul(lhs, rhs) => binary_to_string(
+                space_num,
+                "*",
+                lhs.to_string(space_num + 1),
+                rhs.to_string(space_num + 1),
+            ),
+            Exp13::Div(lhs, rhs) => binary_to_string(
+                space_num,
+                "/",
+                lhs.to_string(space_num + 1),
+                rhs.to_string(space_num + 1),
+            ),
+            Exp13::Rem(lhs, rhs) => binary_to_string(
+                space_num,
+                "%",
+                lhs.to_string(space_num + 1),
+                rhs.to_string(space_num + 1),
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Exp15 {
+    Single(Exp16),
+    Address(Box<Exp15>),
+    Indirection(Box<Exp15>),
+    Plus(Box<Exp15>),
+    Minus(Box<Exp15>),
+    Sizeof(Box<Exp15>),
+    SizeofType(TypeStruct),
+    AlignofType(TypeStruct),
+}
+impl Exp15 {
+    /// exp15 :=
+    /// exp16
+    /// | (Token::Punct(Punct::Ampersand) | Token::Punct(Punct::Star) | Token::Punct(Punct::Plus) | Token::Punct(Punct::Minus))
+    ///     exp15
+    /// | Token::Keyword(Keyword::Sizeof) exp15
+    /// | Token::Keyword(Keyword::Sizeof) Token::Punct(Punct::LeftParen) type_name Token::Punct(Punct::RightParen)
+    /// | Token::Keyword(Keyword::Alignof) Token::Punct(Punct::LeftParen) type_name Token::Punct(Punct::RightParen)
+    fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<Exp15> {
+        if let Some(token) = tokens.peek() {
+            if let Token::Keyword(Keyword::Sizeof) = token.get_token() {
+                tokens.next(); // consume sizeof
+                if !Exp15::is_next_type_name(tokens, typedefs) {
+                    return Ok(Exp15::Sizeof(Box::new(Exp15::parse(tokens, typedefs)?)));
                 }
-                s
+                tokens.consume_punct(Punct::LeftParen)?;
+                let type_name =
+                    TypeStruct::parse_type_name(tokens, typedefs, Qualifiers::default())?;
+                tokens.consume_punct(Punct::RightParen)?;
+                return Ok(Exp15::SizeofType(type_name));
+            }
+            if let Token::Keyword(Keyword::Alignof) = token.get_token() {
+                tokens.next(); // consume _Alignof
+                tokens.consume_punct(Punct::LeftParen)?;
+                let type_name =
+                    TypeStruct::parse_type_name(tokens, typedefs, Qualifiers::default())?;
+                tokens.consume_punct(Punct::RightParen)?;
+                return Ok(Exp15::AlignofType(type_name));
             }
         }
+        if let Some(_token) = tokens.check_next_punct(Punct::Plus) {
+            tokens.next(); // consume "+"
+            Ok(Exp15::Plus(Box::new(Exp15::parse(tokens, typedefs)?)))
+        } else if let Some(_token) = tokens.check_next_punct(Punct::Minus) {
+            tokens.next(); // consume "-"
+            Ok(Exp15::Minus(Box::new(Exp15::parse(tokens, typedefs)?)))
+        } else if let Some(_token) = tokens.check_next_punct(Punct::Ampersand) {
+            tokens.next(); // consume "&"
+            Ok(Exp15::Address(Box::new(Exp15::parse(tokens, typedefs)?)))
+        } else if let Some(_token) = tokens.check_next_punct(Punct::Star) {
+            tokens.next(); // consume "*"
+            Ok(Exp15::Indirection(Box::new(Exp15::parse(
+                tokens, typedefs,
+            )?)))
+        } else {
+            Ok(Exp15::Single(Exp16::parse(tokens, typedefs)?))
+        }
+    }
+    /// check whether the next tokens are a type name in parentheses (e.g. `(unsigned int)`)
+    pub fn is_next_type_name(tokens: &Tokens, typedefs: &TypedefTable) -> bool {
+        match (tokens.peek(), tokens.peek_nth(1)) {
+            (Some(token), Some(token2)) => {
+                token.get_token() == &Token::Punct(Punct::LeftParen)
+                    && match token2.get_token() {
+                        token if token.get_type().is_ok() => true,
+                        Token::Keyword(Keyword::Const)
+                        | Token::Keyword(Keyword::Volatile)
+                        | Token::Keyword(Keyword::Enum)
+                        | Token::Keyword(Keyword::Struct) => true,
+                        Token::Identifier(identifier) => typedefs.is_typedef_name(*identifier),
+                        _ => false,
+                    }
+            }
+            _ => false,
+        }
+    }
+    pub fn to_string(&self, space_num: u32) -> String {
+        match self {
+            Exp15::Single(exp) => exp.to_string(space_num),
+            Exp15::Address(exp) => format!(
+                "{}operator: &\n{}\n",
+                get_space(space_num),
+                exp.to_string(space_num + 1)
+            ),
+            Exp15::Indirection(exp) => format!(
+                "{}operator: *\n{}\n",
+                get_space(space_num),
+                exp.to_string(space_num + 1)
+            ),
+            Exp15::Plus(exp) => format!(
+                "{}operator: +\n{}\n",
+                get_space(space_num),
+                exp.to_string(space_num + 1)
+            ),
+            Exp15::Minus(exp) => format!(
+                "{}operator: -\n{}\n",
+                get_space(space_num),
+                exp.to_string(space_num + 1)
+            ),
+            Exp15::Sizeof(exp) => format!(
+                "{}operator: sizeof\n{}\n",
+                get_space(space_num),
+                exp.to_string(space_num + 1)
+            ),
+            Exp15::SizeofType(type_name) => format!(
+                "{}operator: sizeof\n{}",
+                get_space(space_num),
+                type_name.to_string(space_num + 1)
+            ),
+            Exp15::AlignofType(type_name) => format!(
+                "{}operator: _Alignof\n{}",
+                get_space(space_num),
+                type_name.to_string(space_num + 1)
+            ),
+        }
     }
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Exp16 {
-    Number(u64),
-    Identifier(String),
-    FunctionCall(String, Vec<Expression>),
+    Number(u64, IntegerType),
+    Floating(f64, FloatingType),
+    Identifier(Symbol, Span),
+    Parenthesis(Box<Expression>),
+    FunctionCall(Box<Exp16>, Vec<Expression>),
+    Member(Box<Exp16>, Symbol),
+    VaStart(Box<Expression>),
+    VaArg(Box<Expression>, TypeStruct),
+    VaEnd(Box<Expression>),
 }
 impl Exp16 {
-    fn parse(tokens: &mut Tokens) -> Result<Exp16> {
+    /// exp16 := primary (function_call_arguments | member_access)*
+    /// function_call_arguments :=
+    /// Token::Punct(Punct::LeftParen) (expression (Token::Punct(Punct::Comma) expression)*)? Token::Punct(Punct::RightParen)
+    /// member_access := Token::Punct(Punct::Dot) Token::Identifier
+    fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<Exp16> {
+        let mut exp = Exp16::parse_primary(tokens, typedefs)?;
+        while let Some(token) = tokens.peek() {
+            if token.get_token() == &Token::Punct(Punct::Dot) {
+                tokens.next(); // consume .
+                let member = tokens.consume_identifier()?.get_token().get_identifier()?;
+                exp = Exp16::Member(Box::new(exp), member);
+                continue;
+            }
+            if token.get_token() != &Token::Punct(Punct::LeftParen) {
+                break;
+            }
+            tokens.next(); // consume (
+
+            // match (expression_node (Token::Punct(Punct::Comma) expresssion_node)*)?
+            let mut parameters: Vec<Expression> = vec![];
+            if tokens.check_next_is_expression_node() {
+                parameters.push(Expression::parse(tokens, typedefs)?);
+            }
+            loop {
+                if let Some(token) = tokens.peek() {
+                    if let Token::Punct(Punct::Comma) = token.get_token() {
+                        tokens.next(); // consume ,
+                        parameters.push(Expression::parse(tokens, typedefs)?);
+                        continue;
+                    }
+                }
+                break;
+            }
+            tokens.consume_punct(Punct::RightParen)?;
+            exp = Exp16::FunctionCall(Box::new(exp), parameters);
+        }
+        Ok(exp)
+    }
+
+    /// primary :=
+    /// Token::Number | Token::Character | Token::Float | Token::Double | Token::Identifier
+    /// | Token::Punct(Punct::LeftParen) expression Token::Punct(Punct::RightParen) | va_builtin
+    fn parse_primary(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<Exp16> {
+        if Exp16::is_next_va_builtin(tokens) {
+            return Exp16::parse_va_builtin(tokens, typedefs);
+        }
         if let Some(token) = tokens.peek() {
             if let Token::Number(_) = token.get_token() {
-                let num = tokens.next().unwrap().get_token().get_number().unwrap();
-                return Ok(Exp16::Number(num));
+                let token = tokens.next().unwrap();
+                let (num, ty) = Exp16::integer_constant(&token)?;
+                return Ok(Exp16::Number(num, ty));
             }
-            if let Token::Identifier(_) = token.get_token() {
-                if let Some(token2) = tokens.peek2() {
-                    if let Token::Parenthesis(_) = token2.get_token() {
-                        let identifier =
-                            tokens.next().unwrap().get_token().get_identifier().unwrap();
-                        tokens.next(); // consume (
-
-                        // match (expression_node (Token::Comma expresssion_node)*)?
-                        let mut parameters: Vec<Expression> = vec![];
-                        if tokens.check_next_is_expression_node() {
-                            parameters.push(Expression::parse(tokens)?);
-                        }
-                        loop {
-                            if let Some(token) = tokens.peek() {
-                                if let Token::Comma = token.get_token() {
-                                    tokens.next(); // consume ,
-                                    parameters.push(Expression::parse(tokens)?);
-                                    continue;
-                                }
-                            }
-                            break;
-                        }
-                        tokens.next(); // consume )
-                        return Ok(Exp16::FunctionCall(identifier, parameters));
-                    }
+            match token.get_token() {
+                // a character constant is an int of the value of its char
+                Token::Character(c) => {
+                    let value = character_value(*c) as u64;
+                    tokens.next();
+                    return Ok(Exp16::Number(value, IntegerType::int()));
                 }
-                let identifier = tokens.next().unwrap().get_token().get_identifier().unwrap();
-                return Ok(Exp16::Identifier(identifier));
+                Token::Float(num) => {
+                    let num = *num;
+                    tokens.next();
+                    return Ok(Exp16::Floating(num, FloatingType::Float));
+                }
+                Token::Double(num) => {
+                    let num = *num;
+                    tokens.next();
+                    return Ok(Exp16::Floating(num, FloatingType::Double));
+                }
+                Token::Punct(Punct::LeftParen) => {
+                    tokens.next(); // consume (
+                    let expression = Expression::parse(tokens, typedefs)?;
+                    tokens.consume_punct(Punct::RightParen)?;
+                    return Ok(Exp16::Parenthesis(Box::new(expression)));
+                }
+                _ => (),
+            }
+            if let Token::Identifier(_) = token.get_token() {
+                let token = tokens.next().unwrap();
+                let identifier = token.get_token().get_identifier().unwrap();
+                return Ok(Exp16::Identifier(identifier, token.get_span()));
             }
         }
         Err(ParseError::Unexpect(tokens.next()))
     }
+    fn is_next_va_builtin(tokens: &Tokens) -> bool {
+        let is_builtin = match tokens.peek() {
+            Some(token) => match token.get_token() {
+                Token::Identifier(identifier) => {
+                    ["va_start", "va_arg", "va_end"].contains(&identifier.as_str())
+                }
+                _ => false,
+            },
+            None => false,
+        };
+        is_builtin
+            && match tokens.peek_nth(1) {
+                Some(token) => token.get_token() == &Token::Punct(Punct::LeftParen),
+                None => false,
+            }
+    }
+
+    /// va_builtin :=
+    /// Token::Identifier("va_start")
+    ///     Token::Punct(Punct::LeftParen) expression Token::Punct(Punct::Comma) Token::Identifier Token::Punct(Punct::RightParen)
+    /// | Token::Identifier("va_arg")
+    ///     Token::Punct(Punct::LeftParen) expression Token::Punct(Punct::Comma) type_name Token::Punct(Punct::RightParen)
+    /// | Token::Identifier("va_end") Token::Punct(Punct::LeftParen) expression Token::Punct(Punct::RightParen)
+    fn parse_va_builtin(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<Exp16> {
+        let identifier = tokens.consume_identifier()?.get_token().get_identifier()?;
+        tokens.consume_punct(Punct::LeftParen)?;
+        let list = Box::new(Expression::parse(tokens, typedefs)?);
+        let exp = match identifier.as_str() {
+            "va_start" => {
+                // the last named parameter is not needed to find the variable arguments
+                tokens.consume_punct(Punct::Comma)?;
+                tokens.consume_identifier()?;
+                Exp16::VaStart(list)
+            }
+            "va_arg" => {
+                tokens.consume_punct(Punct::Comma)?;
+                let type_name =
+                    TypeStruct::parse_type_name(tokens, typedefs, Qualifiers::default())?;
+                Exp16::VaArg(list, type_name)
+            }
+            _ => Exp16::VaEnd(list),
+        };
+        tokens.consume_punct(Punct::RightParen)?;
+        Ok(exp)
+    }
+
+    /// get the value and the type of an integer constant
+    fn integer_constant(token: &ManagedToken) -> Result<(u64, IntegerType)> {
+        let constant = token.get_token().get_number()?;
+        let value = constant.value().map_err(|err| {
+            let cause = match err.kind() {
+                IntErrorKind::PosOverflow => "is too large".to_owned(),
+                _ => format!("has invalid digits for radix {}", constant.radix),
+            };
+            ParseError::InvalidInteger(token.clone(), cause)
+        })?;
+        let is_decimal = constant.radix == 10;
+        match IntegerType::of_constant(value, is_decimal, constant.is_unsigned, constant.long) {
+            Some(ty) => Ok((value, ty)),
+            None => Err(ParseError::InvalidInteger(
+                token.clone(),
+                "is too large for its type".to_owned(),
+            )),
+        }
+    }
     pub fn to_string(&self, space_num: u32) -> String {
         match self {
-            Exp16::Number(num) => format!("{}{}", get_space(space_num), num),
-            Exp16::Identifier(identifier) => format!("{}{}", get_space(space_num), identifier),
-            Exp16::FunctionCall(identifier, exps) => {
-                let mut s = format!("{}function_call: {}\n", get_space(space_num), identifier);
+            Exp16::Number(num, ty) => format!("{}{} {}", get_space(space_num), ty, num),
+            Exp16::Floating(num, ty) => format!("{}{} {:?}", get_space(space_num), ty, num),
+            Exp16::Identifier(identifier, _) => {
+                format!("{}{}", get_space(space_num), identifier)
+            }
+            Exp16::Parenthesis(exp) => exp.to_string(space_num),
+            Exp16::FunctionCall(function, exps) => {
+                let mut s = format!(
+                    "{}function_call:\n{}\n",
+                    get_space(space_num),
+                    function.to_string(space_num + 1)
+                );
                 for exp in exps {
                     s += &format!("{}\n", exp.to_string(space_num + 1))
                 }
                 s
             }
+            Exp16::Member(exp, member) => format!(
+                "{}member {}:\n{}\n",
+                get_space(space_num),
+                member,
+                exp.to_string(space_num + 1)
+            ),
+            Exp16::VaStart(list) => format!(
+                "{}va_start:\n{}\n",
+                get_space(space_num),
+                list.to_string(space_num + 1)
+            ),
+            Exp16::VaArg(list, type_name) => format!(
+                "{}va_arg:\n{}\n{}",
+                get_space(space_num),
+                list.to_string(space_num + 1),
+                type_name.to_string(space_num + 1)
+            ),
+            Exp16::VaEnd(list) => format!(
+                "{}va_end:\n{}\n",
+                get_space(space_num),
+                list.to_string(space_num + 1)
+            ),
         }
     }
 }
@@ -204,6 +571,9 @@ mod tests {
 
     use super::super::testutil::*;
     use super::*;
+    use crate::types::Type;
+    use rcc_syntax::span::FileId;
+    use rcc_syntax::Tokenizer;
 
     #[cfg(test)]
     mod tests_parse_expression {
@@ -214,31 +584,305 @@ mod tests {
         fn parse_expression() {
             // a = 10 + 20 * func(30)
             let mut tokens = Tokens::new(vec![
-                mtoken(Token::Identifier("a".to_owned())),
-                mtoken(Token::Operator("=".to_owned())),
-                mtoken(Token::Number(10)),
-                mtoken(Token::Operator("+".to_owned())),
-                mtoken(Token::Number(20)),
-                mtoken(Token::Operator("*".to_owned())),
-                mtoken(Token::Identifier("func".to_owned())),
-                mtoken(Token::Parenthesis("(".to_owned())),
-                mtoken(Token::Number(30)),
-                mtoken(Token::Parenthesis(")".to_owned())),
+                mtoken(Token::Identifier(Symbol::intern("a"))),
+                mtoken(Token::Punct(Punct::Assign)),
+                mtoken(number_token(10)),
+                mtoken(Token::Punct(Punct::Plus)),
+                mtoken(number_token(20)),
+                mtoken(Token::Punct(Punct::Star)),
+                mtoken(Token::Identifier(Symbol::intern("func"))),
+                mtoken(Token::Punct(Punct::LeftParen)),
+                mtoken(number_token(30)),
+                mtoken(Token::Punct(Punct::RightParen)),
             ]);
 
-            let actual = Expression::parse(&mut tokens).unwrap();
+            let actual = Expression::parse(&mut tokens, &TypedefTable::new()).unwrap();
 
-            let num_10 = Exp13::Single(Exp16::Number(10));
-            let num_20 = Exp16::Number(20);
+            let num_10 = Exp12::Single(Exp13::Single(Exp15::Single(Exp16::Number(
+                10,
+                IntegerType::int(),
+            ))));
+            let num_20 = Exp13::Single(Exp15::Single(Exp16::Number(20, IntegerType::int())));
             let parameter = Expression {
-                expression: Exp2::Single(Exp12::Single(Exp13::Single(Exp16::Number(30)))),
+                expression: Exp2::Single(Exp11::Single(Exp12::Single(Exp13::Single(
+                    Exp15::Single(Exp16::Number(30, IntegerType::int())),
+                )))),
+                span: Span::default(),
             };
-            let func_call = Exp16::FunctionCall("func".to_owned(), vec![parameter]);
-            let mul = Exp13::Mul(vec![num_20, func_call]);
-            let add = Exp12::Add(vec![num_10, mul]);
-            let ide = Exp12::Single(Exp13::Single(Exp16::Identifier("a".to_owned())));
+            let func = Box::new(Exp16::Identifier(Symbol::intern("func"), Span::default()));
+            let func_call = Exp15::Single(Exp16::FunctionCall(func, vec![parameter]));
+            let mul = Exp13::Mul(Box::new(num_20), func_call);
+            let add = Exp11::Single(Exp12::Add(Box::new(num_10), mul));
+            let ide = Exp11::Single(Exp12::Single(Exp13::Single(Exp15::Single(
+                Exp16::Identifier(Symbol::intern("a"), Span::default()),
+            ))));
             let eq = Exp2::Eq(vec![ide, add]);
-            let expect = Expression { expression: eq };
+            let expect = Expression {
+                expression: eq,
+                span: Span::default(),
+            };
+            assert_eq!(actual, expect);
+        }
+
+        #[test]
+        fn left_associative() {
+            // 10 - 2 - 3
+            let mut tokens = Tokens::new(vec![
+                mtoken(number_token(10)),
+                mtoken(Token::Punct(Punct::Minus)),
+                mtoken(number_token(2)),
+                mtoken(Token::Punct(Punct::Minus)),
+                mtoken(number_token(3)),
+            ]);
+
+            let actual = Expression::parse(&mut tokens, &TypedefTable::new()).unwrap();
+
+            let num = |num| Exp13::Single(Exp15::Single(Exp16::Number(num, IntegerType::int())));
+            let sub = Exp12::Sub(Box::new(Exp12::Single(num(10))), num(2));
+            let sub = Exp12::Sub(Box::new(sub), num(3));
+            let expect = Expression {
+                expression: Exp2::Single(Exp11::Single(sub)),
+                span: Span::default(),
+            };
+            assert_eq!(actual, expect);
+        }
+
+        #[test]
+        fn identifier_argument() {
+            // func(a)
+            let mut tokens = Tokens::new(vec![
+                mtoken(Token::Identifier(Symbol::intern("func"))),
+                mtoken(Token::Punct(Punct::LeftParen)),
+                mtoken(Token::Identifier(Symbol::intern("a"))),
+                mtoken(Token::Punct(Punct::RightParen)),
+            ]);
+
+            let actual = Expression::parse(&mut tokens, &TypedefTable::new()).unwrap();
+
+            let single = |exp16| {
+                Exp2::Single(Exp11::Single(Exp12::Single(Exp13::Single(Exp15::Single(
+                    exp16,
+                )))))
+            };
+            let parameter = Expression {
+                expression: single(Exp16::Identifier(Symbol::intern("a"), Span::default())),
+                span: Span::default(),
+            };
+            let func = Box::new(Exp16::Identifier(Symbol::intern("func"), Span::default()));
+            let expect = Expression {
+                expression: single(Exp16::FunctionCall(func, vec![parameter])),
+                span: Span::default(),
+            };
+            assert_eq!(actual, expect);
+            assert_eq!(tokens.len(), 0);
+        }
+
+        #[test]
+        fn floating_constants() {
+            // 1.5 * 2.0f
+            let mut tokens = Tokens::new(vec![
+                mtoken(Token::Double(1.5)),
+                mtoken(Token::Punct(Punct::Star)),
+                mtoken(Token::Float(2.0)),
+            ]);
+
+            let actual = Expression::parse(&mut tokens, &TypedefTable::new()).unwrap();
+
+            let lhs = Exp13::Single(Exp15::Single(Exp16::Floating(1.5, FloatingType::Double)));
+            let rhs = Exp15::Single(Exp16::Floating(2.0, FloatingType::Float));
+            let mul = Exp13::Mul(Box::new(lhs), rhs);
+            let expect = Expression {
+                expression: Exp2::Single(Exp11::Single(Exp12::Single(mul))),
+                span: Span::default(),
+            };
+            assert_eq!(actual, expect);
+        }
+
+        #[test]
+        fn va_arg_with_type_name() {
+            // va_arg(ap, double)
+            let mut tokens = Tokens::new(vec![
+                mtoken(Token::Identifier(Symbol::intern("va_arg"))),
+                mtoken(Token::Punct(Punct::LeftParen)),
+                mtoken(Token::Identifier(Symbol::intern("ap"))),
+                mtoken(Token::Punct(Punct::Comma)),
+                mtoken(Token::Keyword(Keyword::Double)),
+                mtoken(Token::Punct(Punct::RightParen)),
+            ]);
+            let actual = Exp16::parse(&mut tokens, &TypedefTable::new()).unwrap();
+            let list = Expression {
+                expression: Exp2::Single(Exp11::Single(Exp12::Single(Exp13::Single(
+                    Exp15::Single(Exp16::Identifier(Symbol::intern("ap"), Span::default())),
+                )))),
+                span: Span::default(),
+            };
+            let double = Type::Floating(FloatingType::Double);
+            let expect = Exp16::VaArg(
+                Box::new(list),
+                TypeStruct::new(double, 0, Symbol::default(), vec![]),
+            );
+            assert_eq!(actual, expect);
+            assert_eq!(tokens.len(), 0);
+        }
+
+        #[test]
+        fn integer_constants() {
+            use crate::types::IntegerKind;
+            use rcc_syntax::token::IntegerConstant;
+            let parse = |digits: &str, radix, is_unsigned, long| {
+                let token = mtoken(Token::Number(IntegerConstant {
+                    digits: digits.to_owned(),
+                    radix,
+                    is_unsigned,
+                    long,
+                }));
+                Exp16::parse(&mut Tokens::new(vec![token]), &TypedefTable::new())
+            };
+            assert_eq!(
+                parse("ffffffff", 16, false, 0),
+                Ok(Exp16::Number(
+                    0xffff_ffff,
+                    IntegerType::new(IntegerKind::Int, false)
+                ))
+            );
+            assert_eq!(
+                parse("017", 8, false, 1),
+                Ok(Exp16::Number(15, IntegerType::new(IntegerKind::Long, true)))
+            );
+            assert!(matches!(
+                parse("09", 8, false, 0),
+                Err(ParseError::InvalidInteger(..))
+            ));
+            assert!(matches!(
+                parse("18446744073709551616", 10, true, 0),
+                Err(ParseError::InvalidInteger(..))
+            ));
+            assert!(matches!(
+                parse("18446744073709551615", 10, false, 0),
+                Err(ParseError::InvalidInteger(..))
+            ));
+        }
+
+        #[test]
+        fn character_constants() {
+            // 'a' - '\n'
+            let mut tokens = Tokens::new(vec![
+                mtoken(Token::Character('a')),
+                mtoken(Token::Punct(Punct::Minus)),
+                mtoken(Token::Character('\n')),
+            ]);
+            let actual = Expression::parse(&mut tokens, &TypedefTable::new()).unwrap();
+            let lhs = Exp12::Single(Exp13::Single(Exp15::Single(Exp16::Number(
+                97,
+                IntegerType::int(),
+            ))));
+            let rhs = Exp13::Single(Exp15::Single(Exp16::Number(10, IntegerType::int())));
+            let expect = Expression {
+                expression: Exp2::Single(Exp11::Single(Exp12::Sub(Box::new(lhs), rhs))),
+                span: Span::default(),
+            };
+            assert_eq!(actual, expect);
+            // char is signed
+            let token = mtoken(Token::Character('\u{ff}'));
+            assert_eq!(
+                Exp16::parse(&mut Tokens::new(vec![token]), &TypedefTable::new()),
+                Ok(Exp16::Number(-1i64 as u64, IntegerType::int()))
+            );
+        }
+
+        #[test]
+        fn call_through_pointer() {
+            // (*op)(a) * &b
+            let mut tokens = Tokens::new(vec![
+                mtoken(Token::Punct(Punct::LeftParen)),
+                mtoken(Token::Punct(Punct::Star)),
+                mtoken(Token::Identifier(Symbol::intern("op"))),
+                mtoken(Token::Punct(Punct::RightParen)),
+                mtoken(Token::Punct(Punct::LeftParen)),
+                mtoken(Token::Identifier(Symbol::intern("a"))),
+                mtoken(Token::Punct(Punct::RightParen)),
+                mtoken(Token::Punct(Punct::Star)),
+                mtoken(Token::Punct(Punct::Ampersand)),
+                mtoken(Token::Identifier(Symbol::intern("b"))),
+            ]);
+
+            let actual = Expression::parse(&mut tokens, &TypedefTable::new()).unwrap();
+
+            let expression = |exp15| Expression {
+                expression: Exp2::Single(Exp11::Single(Exp12::Single(Exp13::Single(exp15)))),
+                span: Span::default(),
+            };
+            let identifier =
+                |identifier: &str| Exp16::Identifier(Symbol::intern(identifier), Span::default());
+            let op = Exp15::Indirection(Box::new(Exp15::Single(identifier("op"))));
+            let call = Exp16::FunctionCall(
+                Box::new(Exp16::Parenthesis(Box::new(expression(op)))),
+                vec![expression(Exp15::Single(identifier("a")))],
+            );
+            let address = Exp15::Address(Box::new(Exp15::Single(identifier("b"))));
+            let mul = Exp13::Mul(Box::new(Exp13::Single(Exp15::Single(call))), address);
+            let expect = Expression {
+                expression: Exp2::Single(Exp11::Single(Exp12::Single(mul))),
+                span: Span::default(),
+            };
+            assert_eq!(actual, expect);
+            assert_eq!(tokens.len(), 0);
+        }
+
+        #[test]
+        fn member_access() {
+            // s.inner.flags(a)
+            let mut tokens = Tokens::new(vec![
+                mtoken(Token::Identifier(Symbol::intern("s"))),
+                mtoken(Token::Punct(Punct::Dot)),
+                mtoken(Token::Identifier(Symbol::intern("inner"))),
+                mtoken(Token::Punct(Punct::Dot)),
+                mtoken(Token::Identifier(Symbol::intern("flags"))),
+                mtoken(Token::Punct(Punct::LeftParen)),
+                mtoken(Token::Identifier(Symbol::intern("a"))),
+                mtoken(Token::Punct(Punct::RightParen)),
+            ]);
+
+            let actual = Exp16::parse(&mut tokens, &TypedefTable::new()).unwrap();
+
+            let s = Box::new(Exp16::Identifier(Symbol::intern("s"), Span::default()));
+            let inner = Box::new(Exp16::Member(s, Symbol::intern("inner")));
+            let flags = Box::new(Exp16::Member(inner, Symbol::intern("flags")));
+            let argument = Expression {
+                expression: Exp2::Single(Exp11::Single(Exp12::Single(Exp13::Single(
+                    Exp15::Single(Exp16::Identifier(Symbol::intern("a"), Span::default())),
+                )))),
+                span: Span::default(),
+            };
+            assert_eq!(actual, Exp16::FunctionCall(flags, vec![argument]));
+            assert_eq!(tokens.len(), 0);
+        }
+
+        #[test]
+        fn identifier_spans() {
+            let mut tokens = Tokens::new(Tokenizer::tokenize(&"(a) +\n  bc".to_owned()).unwrap());
+
+            let actual = Expression::parse(&mut tokens, &TypedefTable::new()).unwrap();
+
+            let span = |start, end| Span::new(FileId::default(), start, end);
+            let identifier = |identifier: &str, span| {
+                Exp15::Single(Exp16::Identifier(Symbol::intern(identifier), span))
+            };
+            let parenthesis = Exp16::Parenthesis(Box::new(Expression {
+                expression: Exp2::Single(Exp11::Single(Exp12::Single(Exp13::Single(identifier(
+                    "a",
+                    span(1, 2),
+                ))))),
+                span: span(1, 2),
+            }));
+            let add = Exp12::Add(
+                Box::new(Exp12::Single(Exp13::Single(Exp15::Single(parenthesis)))),
+                Exp13::Single(identifier("bc", span(8, 10))),
+            );
+            let expect = Expression {
+                expression: Exp2::Single(Exp11::Single(add)),
+                span: span(0, 10),
+            };
             assert_eq!(actual, expect);
         }
     }
@@ -1,87 +1,173 @@
 use super::error::ParseError;
-use super::statement::{ReturnStatement, Statement};
+use super::statement::{ReturnStatement, Statement, TypeStruct};
+use super::storage::{Specifiers, StorageClass};
+use super::typedef::TypedefTable;
 use super::util::get_space;
-use rcc_syntax::token::{ManagedToken, Token};
+use rcc_syntax::span::Span;
+use rcc_syntax::token::{ManagedToken, Punct, Token};
 use rcc_syntax::tokens::Tokens;
+use std::fmt;
 
 type Result<T> = std::result::Result<T, ParseError>;
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct Function {
+pub struct FunctionDeclaration {
+    pub storage_class: Option<StorageClass>,
+    pub is_inline: bool,
     pub identifier: ManagedToken,
-    pub return_type: ManagedToken,
-    pub arguments: Vec<(ManagedToken, ManagedToken)>, // Vec<(identifier, type)>
+    pub return_type: TypeStruct,
+    pub arguments: Vec<TypeStruct>,
+    pub is_variadic: bool,
+    pub span: Span, // tokens of the declaration without the body
+}
+impl FunctionDeclaration {
+    /// check whether the next tokens declare a function (not an object)
+    ///
+    /// the tokens are rewound after they are parsed speculatively
+    pub fn is_next(tokens: &mut Tokens, typedefs: &TypedefTable) -> bool {
+        let checkpoint = tokens.checkpoint();
+        let is_next = match Specifiers::parse(tokens, typedefs) {
+            Ok(specifiers) => {
+                TypeStruct::parse_type_name(tokens, typedefs, specifiers.qualifiers).is_ok()
+                    && tokens.consume_identifier().is_ok()
+                    && tokens.check_next_punct(Punct::LeftParen).is_some()
+            }
+            Err(_) => false,
+        };
+        tokens.rewind(checkpoint);
+        is_next
+    }
+
+    /// function_declaration :=
+    /// specifiers type_name Token::Identifier
+    ///     Token::Punct(Punct::LeftParen)
+    ///         (parameter (Token::Punct(Punct::Comma) parameter)* (Token::Punct(Punct::Comma) Token::Punct(Punct::Ellipsis))?)?
+    ///     Token::Punct(Punct::RightParen)
+    pub fn parse(tokens: &mut Tokens, typedefs: &mut TypedefTable) -> Result<FunctionDeclaration> {
+        let checkpoint = tokens.checkpoint();
+        let specifiers = Specifiers::parse(tokens, typedefs)?;
+        if specifiers.alignas.is_some() {
+            // alignment is only for objects
+            return Err(ParseError::Unexpect(specifiers.alignas));
+        }
+        let return_type = TypeStruct::parse_type_name(tokens, typedefs, specifiers.qualifiers)?;
+        let identifier = tokens.consume_identifier()?;
+        typedefs.insert_ordinary(identifier.get_token().get_identifier()?);
+
+        // parameters have their own scope which the function body reopens
+        typedefs.push_scope();
+        tokens.consume_punct(Punct::LeftParen)?;
+        let (arguments, is_variadic) = FunctionDeclaration::consume_arguments(tokens, typedefs)?;
+        tokens.consume_punct(Punct::RightParen)?;
+        typedefs.pop_scope();
+
+        Ok(FunctionDeclaration {
+            storage_class: specifiers.storage_class,
+            is_inline: specifiers.is_inline(),
+            identifier,
+            return_type,
+            arguments,
+            is_variadic,
+            span: tokens.span_since(checkpoint),
+        })
+    }
+
+    /// (parameter (Token::Punct(Punct::Comma) parameter)* (Token::Punct(Punct::Comma) Token::Punct(Punct::Ellipsis))?)?
+    ///
+    /// returns the parameters and whether they end with an ellipsis
+    fn consume_arguments(
+        tokens: &mut Tokens,
+        typedefs: &mut TypedefTable,
+    ) -> Result<(Vec<TypeStruct>, bool)> {
+        let mut arguments: Vec<TypeStruct> = Vec::new();
+        let mut is_variadic = false;
+        if let Some(token) = tokens.peek() {
+            if let Token::Punct(Punct::RightParen) = token.get_token() {
+                return Ok((arguments, is_variadic));
+            }
+        }
+        loop {
+            let argument = TypeStruct::parse_parameter(tokens, typedefs)?;
+            typedefs.insert_ordinary(argument.get_identifier());
+            arguments.push(argument);
+            if let Some(token) = tokens.peek() {
+                if let Token::Punct(Punct::Comma) = token.get_token() {
+                    tokens.consume_punct(Punct::Comma)?;
+                    is_variadic = tokens.consume_punct(Punct::Ellipsis).is_ok();
+                    if !is_variadic {
+                        continue;
+                    }
+                }
+            }
+            break;
+        }
+        Ok((arguments, is_variadic))
+    }
+}
+impl fmt::Display for FunctionDeclaration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "function: {} storage_class {:?} inline {} variadic {}",
+            self.identifier, self.storage_class, self.is_inline, self.is_variadic
+        )?;
+        writeln!(f, "{}return_type:", get_space(1))?;
+        write!(f, "{}", self.return_type.to_string(2))?;
+        writeln!(f, "{}arguments:", get_space(1))?;
+        for argument in self.arguments.iter() {
+            write!(f, "{}", argument.to_string(2))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Function {
+    pub declaration: FunctionDeclaration,
     pub block: Vec<Statement>,
     pub return_statement: ReturnStatement,
 }
 impl Function {
     /// function :=
-    /// Token::Type Token::Identifier
-    ///     Token::Parenthesis("(")
-    ///         (Token::Type Token::Identifier (Token::Comma Token::Type Token::Identifier)*)?
-    ///     Token::Parenthesis(")")
-    ///     Token::Bracket("{")
+    /// function_declaration
+    ///     Token::Punct(Punct::LeftBrace)
     ///         statement*
     ///         return_statement
-    ///     Token::Bracket("}")
-    pub fn parse(tokens: &mut Tokens) -> Result<Function> {
-        let return_type = tokens.consume_type()?;
-        let identifier = tokens.consume_identifier()?;
-        tokens.consume_parenthesis()?; // consume (
-        let arguments = Function::consume_arguments(tokens);
-        tokens.consume_parenthesis()?; // consume )
+    ///     Token::Punct(Punct::RightBrace)
+    pub fn parse(tokens: &mut Tokens, typedefs: &mut TypedefTable) -> Result<Function> {
+        let declaration = FunctionDeclaration::parse(tokens, typedefs)?;
+        Function::parse_body(declaration, tokens, typedefs)
+    }
 
-        tokens.consume_bracket()?; // consume {
+    /// parse the body of a function following its declaration
+    pub fn parse_body(
+        declaration: FunctionDeclaration,
+        tokens: &mut Tokens,
+        typedefs: &mut TypedefTable,
+    ) -> Result<Function> {
+        typedefs.push_scope();
+        for argument in declaration.arguments.iter() {
+            typedefs.insert_ordinary(argument.get_identifier());
+        }
+        tokens.consume_punct(Punct::LeftBrace)?;
 
         let mut block = vec![];
-        while let Some(statement) = Statement::parse(tokens)? {
+        while let Some(statement) = Statement::parse(tokens, typedefs)? {
             block.push(statement)
         }
-        let return_statement = ReturnStatement::parse(tokens)?;
-        tokens.consume_bracket()?; // consume }
+        let return_statement = ReturnStatement::parse(tokens, typedefs)?;
+        tokens.consume_punct(Punct::RightBrace)?;
+        typedefs.pop_scope();
 
         Ok(Function {
-            identifier,
-            return_type,
-            arguments,
+            declaration,
             block,
             return_statement,
         })
     }
 
-    /// (Token::Type Token::Identifier (Token::Comma Token::Type Token::Identifier)*)?
-    fn consume_arguments(tokens: &mut Tokens) -> Vec<(ManagedToken, ManagedToken)> {
-        let mut arguments: Vec<(ManagedToken, ManagedToken)> = Vec::new();
-        if let Some(token) = tokens.peek() {
-            if let Token::Type(_) = token.get_token() {
-                let ty = tokens.consume_type().unwrap();
-                let identifier = tokens.consume_identifier().unwrap();
-                arguments.push((identifier, ty));
-                loop {
-                    if let Some(token) = tokens.peek() {
-                        if let Token::Comma = token.get_token() {
-                            tokens.consume_comma().unwrap();
-                            let ty = tokens.consume_type().unwrap();
-                            let identifier = tokens.consume_identifier().unwrap();
-                            arguments.push((identifier, ty));
-                            continue;
-                        }
-                    }
-                    break;
-                }
-            }
-        }
-        arguments
-    }
-
     pub fn to_string(&self) -> String {
-        let mut s = "".to_owned();
-        s += &format!("function: {}\n", self.identifier);
-        s += &format!("{}return_type: {}\n", get_space(1), self.return_type);
-        s += &format!("{}arguments:\n", get_space(1));
-        for argument in self.arguments.iter() {
-            s += &format!("{}{:?}\n", get_space(1 + 1), argument);
-        }
+        let mut s = self.declaration.to_string();
         s += &format!("{}block:\n", get_space(1));
         for statement in self.block.iter() {
             s += &format!("{}{}", get_space(1), statement.to_string(2));
@@ -97,77 +183,176 @@ mod tests {
 
     use super::super::testutil::*;
     use super::*;
-    use rcc_syntax::token::Token;
+    use crate::types::Type;
+    use rcc_syntax::symbol::Symbol;
+    use rcc_syntax::token::{Keyword, Token};
 
     #[cfg(test)]
     mod consume_arguments {
 
         use super::*;
 
+        fn int_argument(identifier: &str) -> TypeStruct {
+            TypeStruct::new(Type::int(), 0, Symbol::intern(identifier), vec![])
+        }
+
         #[test]
         fn no_argument() {
-            let mut tokens = Tokens::new(vec![mtoken(Token::Parenthesis(")".to_owned()))]);
-            let actual = Function::consume_arguments(&mut tokens);
-            assert_eq!(actual, vec![]);
+            let mut tokens = Tokens::new(vec![mtoken(Token::Punct(Punct::RightParen))]);
+            let actual =
+                FunctionDeclaration::consume_arguments(&mut tokens, &mut TypedefTable::new());
+            assert_eq!(actual, Ok((vec![], false)));
         }
 
         #[test]
         fn one_argument() {
             let mut tokens = Tokens::new(vec![
-                mtoken(Token::Type("int".to_owned())),
-                mtoken(Token::Identifier("a".to_owned())),
-                mtoken(Token::Parenthesis(")".to_owned())),
+                mtoken(Token::Keyword(Keyword::Int)),
+                mtoken(Token::Identifier(Symbol::intern("a"))),
+                mtoken(Token::Punct(Punct::RightParen)),
+            ]);
+            let actual =
+                FunctionDeclaration::consume_arguments(&mut tokens, &mut TypedefTable::new());
+            assert_eq!(actual, Ok((vec![int_argument("a")], false)));
+        }
+
+        #[test]
+        fn two_arguments() {
+            let mut tokens = Tokens::new(vec![
+                mtoken(Token::Keyword(Keyword::Int)),
+                mtoken(Token::Identifier(Symbol::intern("a"))),
+                mtoken(Token::Punct(Punct::Comma)),
+                mtoken(Token::Keyword(Keyword::Int)),
+                mtoken(Token::Identifier(Symbol::intern("b"))),
+                mtoken(Token::Punct(Punct::RightParen)),
             ]);
-            let actual = Function::consume_arguments(&mut tokens);
+            let actual =
+                FunctionDeclaration::consume_arguments(&mut tokens, &mut TypedefTable::new());
             assert_eq!(
                 actual,
-                vec![(
-                    mtoken(Token::Identifier("a".to_owned())),
-                    mtoken(Token::Type("int".to_owned()))
-                )]
+                Ok((vec![int_argument("a"), int_argument("b")], false))
             );
         }
 
         #[test]
-        fn two_arguments() {
+        fn typedef_name_argument() {
+            let mut typedefs = TypedefTable::new();
+            typedefs.insert_typedef(TypeStruct::new(
+                Type::int(),
+                1,
+                Symbol::intern("intptr"),
+                vec![],
+            ));
             let mut tokens = Tokens::new(vec![
-                mtoken(Token::Type("int".to_owned())),
-                mtoken(Token::Identifier("a".to_owned())),
-                mtoken(Token::Comma),
-                mtoken(Token::Type("int".to_owned())),
-                mtoken(Token::Identifier("b".to_owned())),
-                mtoken(Token::Parenthesis(")".to_owned())),
+                mtoken(Token::Identifier(Symbol::intern("intptr"))),
+                mtoken(Token::Identifier(Symbol::intern("a"))),
+                mtoken(Token::Punct(Punct::RightParen)),
             ]);
-            let actual = Function::consume_arguments(&mut tokens);
+            let actual = FunctionDeclaration::consume_arguments(&mut tokens, &mut typedefs);
             assert_eq!(
                 actual,
-                vec![
-                    (
-                        mtoken(Token::Identifier("a".to_owned())),
-                        mtoken(Token::Type("int".to_owned()))
-                    ),
-                    (
-                        mtoken(Token::Identifier("b".to_owned())),
-                        mtoken(Token::Type("int".to_owned()))
-                    )
-                ]
+                Ok((
+                    vec![TypeStruct::new(Type::int(), 1, Symbol::intern("a"), vec![])],
+                    false
+                ))
             );
         }
     }
+    #[test]
+    fn static_declaration_without_parameter_names() {
+        // static int f(int, char *);
+        let mut tokens = Tokens::new(vec![
+            mtoken(Token::Keyword(Keyword::Static)),
+            mtoken(Token::Keyword(Keyword::Int)),
+            mtoken(Token::Identifier(Symbol::intern("f"))),
+            mtoken(Token::Punct(Punct::LeftParen)),
+            mtoken(Token::Keyword(Keyword::Int)),
+            mtoken(Token::Punct(Punct::Comma)),
+            mtoken(Token::Keyword(Keyword::Char)),
+            mtoken(Token::Punct(Punct::Star)),
+            mtoken(Token::Punct(Punct::RightParen)),
+            mtoken(Token::Punct(Punct::Semicolon)),
+        ]);
+        let mut typedefs = TypedefTable::new();
+        assert!(FunctionDeclaration::is_next(&mut tokens, &typedefs));
+        let actual = FunctionDeclaration::parse(&mut tokens, &mut typedefs).unwrap();
+        let char_type = Type::from_specifiers(&[Keyword::Char]).unwrap();
+        let expect = FunctionDeclaration {
+            storage_class: Some(StorageClass::Static),
+            is_inline: false,
+            identifier: mtoken(Token::Identifier(Symbol::intern("f"))),
+            return_type: TypeStruct::new(Type::int(), 0, Symbol::default(), vec![]),
+            arguments: vec![
+                TypeStruct::new(Type::int(), 0, Symbol::default(), vec![]),
+                TypeStruct::new(char_type, 1, Symbol::default(), vec![]),
+            ],
+            is_variadic: false,
+            span: Span::default(),
+        };
+        assert_eq!(actual, expect);
+        assert_eq!(tokens.len(), 1);
+    }
+
+    #[test]
+    fn variadic_parameters() {
+        // int sum(int n, ...);
+        let tokens = vec![
+            mtoken(Token::Keyword(Keyword::Int)),
+            mtoken(Token::Identifier(Symbol::intern("sum"))),
+            mtoken(Token::Punct(Punct::LeftParen)),
+            mtoken(Token::Keyword(Keyword::Int)),
+            mtoken(Token::Identifier(Symbol::intern("n"))),
+            mtoken(Token::Punct(Punct::Comma)),
+            mtoken(Token::Punct(Punct::Ellipsis)),
+            mtoken(Token::Punct(Punct::RightParen)),
+        ];
+        let actual =
+            FunctionDeclaration::parse(&mut Tokens::new(tokens), &mut TypedefTable::new()).unwrap();
+        assert_eq!(actual.arguments.len(), 1);
+        assert!(actual.is_variadic);
+
+        // an ellipsis needs a named parameter before it
+        let tokens = vec![
+            mtoken(Token::Keyword(Keyword::Int)),
+            mtoken(Token::Identifier(Symbol::intern("f"))),
+            mtoken(Token::Punct(Punct::LeftParen)),
+            mtoken(Token::Punct(Punct::Ellipsis)),
+            mtoken(Token::Punct(Punct::RightParen)),
+        ];
+        let actual = FunctionDeclaration::parse(&mut Tokens::new(tokens), &mut TypedefTable::new());
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn object_declaration_is_not_function() {
+        // int f;
+        let mut tokens = Tokens::new(vec![
+            mtoken(Token::Keyword(Keyword::Int)),
+            mtoken(Token::Identifier(Symbol::intern("f"))),
+            mtoken(Token::Punct(Punct::Semicolon)),
+        ]);
+        assert!(!FunctionDeclaration::is_next(
+            &mut tokens,
+            &TypedefTable::new()
+        ));
+        // the tokens are rewound after the lookahead
+        assert_eq!(tokens.len(), 3);
+    }
+
     /*
     #[test]
     fn main_func() {
         let actual = Function::parse(&mut Tokens::new(
             vec![
-                Token::Type("int".to_owned()),
-                Token::Identifier("main".to_owned()),
-                Token::Parenthesis("(".to_owned()),
-                Token::Parenthesis(")".to_owned()),
-                Token::Bracket("{".to_owned()),
-                Token::Return,
-                Token::Number(10),
-                Token::Semicolon,
-                Token::Bracket("}".to_owned()),
+                Token::Keyword(Keyword::Int),
+                Token::Identifier(Symbol::intern("main")),
+                Token::Punct(Punct::LeftParen),
+                Token::Punct(Punct::RightParen),
+                Token::Punct(Punct::LeftBrace),
+                Token::Keyword(Keyword::Return),
+                number_token(10),
+                Token::Punct(Punct::Semicolon),
+                Token::Punct(Punct::RightBrace),
             ]
             .into_iter()
             .map(|token| ManagedToken::new(token, 0, 0))
@@ -176,12 +361,13 @@ mod tests {
         .unwrap();
 
         let expect = Function {
-            identifier: mtoken(Token::Identifier("main".to_owned())),
-            return_type: mtoken(Token::Type("int".to_owned())),
+            identifier: mtoken(Token::Identifier(Symbol::intern("main"))),
+            return_type: mtoken(Token::Keyword(Keyword::Int)),
             arguments: vec![],
             block: vec![],
             return_statement: ReturnStatement {
                 expression: *num(10),
+                span: Span::default(),
             },
         };
         assert_eq!(actual, expect);
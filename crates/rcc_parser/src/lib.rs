@@ -1,9 +1,16 @@
+pub mod constant;
+pub mod enumeration;
 pub mod error;
 pub mod expression;
 pub mod function;
 pub mod program;
 pub mod statement;
+pub mod static_assert;
+pub mod storage;
+pub mod structure;
 pub mod testutil;
+pub mod typedef;
+pub mod types;
 pub mod util;
 
 use error::ParseError;
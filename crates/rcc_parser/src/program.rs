@@ -1,30 +1,129 @@
+use super::enumeration::EnumDeclaration;
 use super::error::ParseError;
-use super::function::Function;
+use super::function::{Function, FunctionDeclaration};
+use super::statement::DeclareStatement;
+use super::static_assert::StaticAssertDeclaration;
+use super::structure::StructDeclaration;
+use super::typedef::{TypedefDeclaration, TypedefTable};
+use rcc_syntax::span::Span;
+use rcc_syntax::token::{Keyword, Punct, Token};
 use rcc_syntax::tokens::Tokens;
+use std::fmt;
 
 type Result<T> = std::result::Result<T, ParseError>;
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExternalDeclaration {
+    Function(Function),
+    FunctionDeclaration(FunctionDeclaration),
+    Declare(DeclareStatement),
+    Enum(EnumDeclaration),
+    Struct(StructDeclaration),
+    Typedef(TypedefDeclaration),
+    StaticAssert(StaticAssertDeclaration),
+}
+impl ExternalDeclaration {
+    /// external_declaration :=
+    /// function
+    /// | function_declaration Token::Punct(Punct::Semicolon)
+    /// | declare_statement
+    /// | enum_declaration
+    /// | struct_declaration
+    /// | typedef_declaration
+    /// | static_assert_declaration
+    pub fn parse(tokens: &mut Tokens, typedefs: &mut TypedefTable) -> Result<ExternalDeclaration> {
+        match tokens.peek().cloned() {
+            Some(token) => match token.get_token() {
+                Token::Keyword(Keyword::Enum) if EnumDeclaration::is_next(tokens) => {
+                    let enum_declaration = EnumDeclaration::parse(tokens, typedefs)?;
+                    for enumerator in enum_declaration.enumerators.iter() {
+                        typedefs.insert_ordinary(enumerator.identifier);
+                    }
+                    Ok(ExternalDeclaration::Enum(enum_declaration))
+                }
+                Token::Keyword(Keyword::Struct) if StructDeclaration::is_next(tokens) => {
+                    let struct_declaration = StructDeclaration::parse(tokens, typedefs)?;
+                    Ok(ExternalDeclaration::Struct(struct_declaration))
+                }
+                Token::Keyword(Keyword::Typedef) => {
+                    let typedef_declaration = TypedefDeclaration::parse(tokens, typedefs)?;
+                    Ok(ExternalDeclaration::Typedef(typedef_declaration))
+                }
+                Token::Keyword(Keyword::StaticAssert) => {
+                    let static_assert = StaticAssertDeclaration::parse(tokens, typedefs)?;
+                    Ok(ExternalDeclaration::StaticAssert(static_assert))
+                }
+                _ if FunctionDeclaration::is_next(tokens, typedefs) => {
+                    let declaration = FunctionDeclaration::parse(tokens, typedefs)?;
+                    if let Some(token) = tokens.peek() {
+                        if let Token::Punct(Punct::Semicolon) = token.get_token() {
+                            tokens.next(); // consume ;
+                            return Ok(ExternalDeclaration::FunctionDeclaration(declaration));
+                        }
+                    }
+                    let function = Function::parse_body(declaration, tokens, typedefs)?;
+                    Ok(ExternalDeclaration::Function(function))
+                }
+                _ => Ok(ExternalDeclaration::Declare(DeclareStatement::parse(
+                    tokens, typedefs,
+                )?)),
+            },
+            None => Err(ParseError::Unexpect(None)),
+        }
+    }
+
+    /// tokens of the declaration, which errors in it are reported at
+    ///
+    /// a function is reported at its declaration without the body
+    pub fn span(&self) -> Span {
+        match self {
+            ExternalDeclaration::Function(function) => function.declaration.span,
+            ExternalDeclaration::FunctionDeclaration(declaration) => declaration.span,
+            ExternalDeclaration::Declare(statement) => statement.span,
+            ExternalDeclaration::Enum(enum_declaration) => enum_declaration.span,
+            ExternalDeclaration::Struct(struct_declaration) => struct_declaration.span,
+            ExternalDeclaration::Typedef(typedef_declaration) => typedef_declaration.span,
+            ExternalDeclaration::StaticAssert(static_assert) => static_assert.span,
+        }
+    }
+}
+impl fmt::Display for ExternalDeclaration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            ExternalDeclaration::Function(function) => function.to_string(),
+            ExternalDeclaration::FunctionDeclaration(declaration) => declaration.to_string(),
+            ExternalDeclaration::Declare(statement) => statement.to_string(0),
+            ExternalDeclaration::Enum(enum_declaration) => enum_declaration.to_string(0),
+            ExternalDeclaration::Struct(struct_declaration) => struct_declaration.to_string(0),
+            ExternalDeclaration::Typedef(typedef_declaration) => typedef_declaration.to_string(0),
+            ExternalDeclaration::StaticAssert(static_assert) => static_assert.to_string(0),
+        };
+        f.write_str(&s)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Program {
-    pub functions: Vec<Function>,
+    pub declarations: Vec<ExternalDeclaration>,
 }
 impl Program {
     /// parse and get program
     ///
-    /// program := function+
+    /// program := external_declaration+
     pub fn parse(tokens: &mut Tokens) -> Result<Program> {
-        let mut functions: Vec<Function> = vec![];
+        let mut typedefs = TypedefTable::new();
+        let mut declarations: Vec<ExternalDeclaration> = vec![];
         while let Some(_token) = tokens.peek() {
-            functions.push(Function::parse(tokens)?);
+            declarations.push(ExternalDeclaration::parse(tokens, &mut typedefs)?);
         }
-        Ok(Program { functions })
+        Ok(Program { declarations })
     }
 
     pub fn to_string(&self) -> String {
         let mut s = "".to_owned();
         s += "Program =========================\n";
-        for function in &self.functions {
-            s += &format!("{}", function.to_string());
+        for declaration in &self.declarations {
+            s += &declaration.to_string();
         }
         s += "================================\n";
         s
@@ -1,7 +1,15 @@
+use super::enumeration::EnumDeclaration;
 use super::error::ParseError;
 use super::expression::Expression;
+use super::static_assert::StaticAssertDeclaration;
+use super::storage::{Alignment, Specifiers, StorageClass};
+use super::structure::StructDeclaration;
+use super::typedef::{TypedefDeclaration, TypedefTable};
+use super::types::{Qualifiers, Type};
 use super::util::get_space;
-use rcc_syntax::token::{ManagedToken, Token};
+use rcc_syntax::span::Span;
+use rcc_syntax::symbol::Symbol;
+use rcc_syntax::token::{Keyword, Punct, Token};
 use rcc_syntax::tokens::Tokens;
 
 type Result<T> = std::result::Result<T, ParseError>;
@@ -9,21 +17,61 @@ type Result<T> = std::result::Result<T, ParseError>;
 #[derive(Clone, Debug, PartialEq)]
 pub enum Statement {
     Declare(DeclareStatement),
+    Enum(EnumDeclaration),
+    Struct(StructDeclaration),
+    Typedef(TypedefDeclaration),
+    StaticAssert(StaticAssertDeclaration),
     Expression(ExpressionStatement),
 }
 impl Statement {
     /// parse and get statement if target statement exists
     ///
-    /// statement := declare_statement | expression_statement
-    pub fn parse(tokens: &mut Tokens) -> Result<Option<Statement>> {
+    /// statement :=
+    /// declare_statement | enum_declaration | struct_declaration | typedef_declaration
+    /// | static_assert_declaration | expression_statement
+    pub fn parse(tokens: &mut Tokens, typedefs: &mut TypedefTable) -> Result<Option<Statement>> {
         match tokens.peek() {
             Some(token) => match token.get_token() {
-                Token::Type(_) => {
-                    let declare_statement = DeclareStatement::parse(tokens)?;
+                Token::Keyword(Keyword::Enum) if EnumDeclaration::is_next(tokens) => {
+                    let enum_declaration = EnumDeclaration::parse(tokens, typedefs)?;
+                    for enumerator in enum_declaration.enumerators.iter() {
+                        typedefs.insert_ordinary(enumerator.identifier);
+                    }
+                    Ok(Some(Statement::Enum(enum_declaration)))
+                }
+                Token::Keyword(Keyword::Struct) if StructDeclaration::is_next(tokens) => {
+                    let struct_declaration = StructDeclaration::parse(tokens, typedefs)?;
+                    Ok(Some(Statement::Struct(struct_declaration)))
+                }
+                Token::Keyword(Keyword::Typedef) => {
+                    let typedef_declaration = TypedefDeclaration::parse(tokens, typedefs)?;
+                    Ok(Some(Statement::Typedef(typedef_declaration)))
+                }
+                Token::Keyword(Keyword::StaticAssert) => {
+                    let static_assert = StaticAssertDeclaration::parse(tokens, typedefs)?;
+                    Ok(Some(Statement::StaticAssert(static_assert)))
+                }
+                Token::Keyword(keyword) if keyword.is_type_specifier() => {
+                    let declare_statement = DeclareStatement::parse(tokens, typedefs)?;
                     Ok(Some(Statement::Declare(declare_statement)))
                 }
-                Token::Number(_) | Token::Identifier(_) => {
-                    let expression_statement = ExpressionStatement::parse(tokens)?;
+                Token::Keyword(Keyword::Enum)
+                | Token::Keyword(Keyword::Struct)
+                | Token::Keyword(Keyword::Static)
+                | Token::Keyword(Keyword::Extern)
+                | Token::Keyword(Keyword::Inline)
+                | Token::Keyword(Keyword::Alignas)
+                | Token::Keyword(Keyword::Const)
+                | Token::Keyword(Keyword::Volatile) => {
+                    let declare_statement = DeclareStatement::parse(tokens, typedefs)?;
+                    Ok(Some(Statement::Declare(declare_statement)))
+                }
+                Token::Identifier(identifier) if typedefs.is_typedef_name(*identifier) => {
+                    let declare_statement = DeclareStatement::parse(tokens, typedefs)?;
+                    Ok(Some(Statement::Declare(declare_statement)))
+                }
+                _ if tokens.check_next_is_expression_node() => {
+                    let expression_statement = ExpressionStatement::parse(tokens, typedefs)?;
                     Ok(Some(Statement::Expression(expression_statement)))
                 }
                 _ => Ok(None),
@@ -31,86 +79,561 @@ impl Statement {
             None => Ok(None),
         }
     }
+    /// tokens of the statement, which errors in it are reported at
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::Declare(statement) => statement.span,
+            Statement::Enum(statement) => statement.span,
+            Statement::Struct(statement) => statement.span,
+            Statement::Typedef(statement) => statement.span,
+            Statement::StaticAssert(statement) => statement.span,
+            Statement::Expression(statement) => statement.span,
+        }
+    }
     pub fn to_string(&self, tab_level: u32) -> String {
         match self {
             Statement::Declare(statement) => statement.to_string(tab_level),
+            Statement::Enum(statement) => statement.to_string(tab_level),
+            Statement::Struct(statement) => statement.to_string(tab_level),
+            Statement::Typedef(statement) => statement.to_string(tab_level),
+            Statement::StaticAssert(statement) => statement.to_string(tab_level),
             Statement::Expression(statement) => statement.to_string(tab_level),
         }
     }
 }
 
+/// type_qualifier := Token::Keyword(Keyword::Const) | Token::Keyword(Keyword::Volatile)
+pub fn consume_type_qualifiers(tokens: &mut Tokens, qualifiers: &mut Qualifiers) {
+    while let Some(token) = tokens.peek() {
+        match token.get_token() {
+            Token::Keyword(Keyword::Const) => qualifiers.is_const = true,
+            Token::Keyword(Keyword::Volatile) => qualifiers.is_volatile = true,
+            _ => break,
+        }
+        tokens.next(); // consume type qualifier
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct TypeStruct {
-    base: String,
-    pointer: u32,
-    identifier: String,
-    post: Vec<u32>,
+    base: Type,
+    qualifiers: Qualifiers,
+    pointers: Vec<Qualifiers>,
+    identifier: Symbol,
+    post: Vec<Expression>,
 }
 impl TypeStruct {
-    pub fn parse(tokens: &mut Tokens) -> Result<TypeStruct> {
-        let base = tokens.consume_type()?.get_token().get_type()?;
-        let pointer = 0; // TODO
-        let identifier = tokens.consume_identifier()?.get_token().get_identifier()?;
-        let post = vec![]; // TODO
-        Ok(TypeStruct {
+    pub fn new(base: Type, pointer: u32, identifier: Symbol, post: Vec<Expression>) -> TypeStruct {
+        TypeStruct {
             base,
-            pointer,
+            qualifiers: Qualifiers::default(),
+            pointers: vec![Qualifiers::default(); pointer as usize],
             identifier,
             post,
-        })
+        }
+    }
+
+    /// type := type_specifier declarator
+    pub fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<TypeStruct> {
+        let specifier =
+            TypeStruct::consume_type_specifier(tokens, typedefs, Qualifiers::default())?;
+        TypeStruct::parse_declarator(tokens, typedefs, &specifier)
+    }
+
+    /// parse a type specifier which declarators derive their types from
+    pub fn parse_specifier(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<TypeStruct> {
+        TypeStruct::consume_type_specifier(tokens, typedefs, Qualifiers::default())
+    }
+
+    /// parse a declarator and get the declared type based on the type specifier
+    ///
+    /// declarator :=
+    /// pointer* (Token::Identifier | Token::Punct(Punct::LeftParen) declarator Token::Punct(Punct::RightParen))
+    ///     (Token::Punct(Punct::LeftBracket) constant_expression Token::Punct(Punct::RightBracket))*
+    ///     function_parameters?
+    pub fn parse_declarator(
+        tokens: &mut Tokens,
+        typedefs: &TypedefTable,
+        specifier: &TypeStruct,
+    ) -> Result<TypeStruct> {
+        TypeStruct::parse_any_declarator(tokens, typedefs, specifier, false)
+    }
+
+    /// parse a parameter whose identifier can be omitted (e.g. `int f(int);`)
+    ///
+    /// parameter := type_specifier declarator_without_identifier
+    pub fn parse_parameter(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<TypeStruct> {
+        let specifier =
+            TypeStruct::consume_type_specifier(tokens, typedefs, Qualifiers::default())?;
+        TypeStruct::parse_any_declarator(tokens, typedefs, &specifier, true)
+    }
+
+    fn parse_any_declarator(
+        tokens: &mut Tokens,
+        typedefs: &TypedefTable,
+        specifier: &TypeStruct,
+        is_abstract_allowed: bool,
+    ) -> Result<TypeStruct> {
+        let mut type_struct = specifier.clone();
+        type_struct.consume_pointers(tokens)?;
+        if TypeStruct::is_next_nested_declarator(tokens) {
+            // the declarator in parentheses derives from the type following it
+            // (e.g. `int (*op)(int, int)` is a pointer to a function), so the suffixes are
+            // consumed before the tokens are rewound to the declarator
+            let nested = tokens.checkpoint();
+            TypeStruct::skip_parentheses(tokens)?;
+            type_struct.consume_suffixes(tokens, typedefs)?;
+            let end = tokens.checkpoint();
+            tokens.rewind(nested);
+            tokens.consume_punct(Punct::LeftParen)?;
+            let type_struct = TypeStruct::parse_any_declarator(
+                tokens,
+                typedefs,
+                &type_struct,
+                is_abstract_allowed,
+            )?;
+            tokens.consume_punct(Punct::RightParen)?;
+            tokens.rewind(end);
+            return Ok(type_struct);
+        }
+        type_struct.identifier = match tokens.peek() {
+            Some(token) if is_abstract_allowed => match token.get_token() {
+                Token::Identifier(_) => tokens.next().unwrap().get_token().get_identifier()?,
+                _ => Symbol::default(),
+            },
+            _ => tokens.consume_identifier()?.get_token().get_identifier()?,
+        };
+        type_struct.consume_suffixes(tokens, typedefs)?;
+        Ok(type_struct)
+    }
+
+    fn is_next_nested_declarator(tokens: &Tokens) -> bool {
+        match (tokens.peek(), tokens.peek_nth(1)) {
+            (Some(token), Some(token2)) => {
+                token.get_token() == &Token::Punct(Punct::LeftParen)
+                    && token2.get_token() == &Token::Punct(Punct::Star)
+            }
+            _ => false,
+        }
+    }
+
+    /// consume tokens until the parenthesis which closes the next one
+    fn skip_parentheses(tokens: &mut Tokens) -> Result<()> {
+        let mut depth = 0;
+        loop {
+            let token = tokens.next();
+            depth += match token.as_ref().map(|token| token.get_token()) {
+                Some(Token::Punct(Punct::LeftParen)) => 1,
+                Some(Token::Punct(Punct::RightParen)) => -1,
+                _ => return Err(ParseError::Unexpect(token)),
+            };
+            if depth == 0 {
+                return Ok(());
+            }
+            while let Some(token) = tokens.peek() {
+                if let Token::Punct(Punct::LeftParen) | Token::Punct(Punct::RightParen) =
+                    token.get_token()
+                {
+                    break;
+                }
+                tokens.next();
+            }
+        }
+    }
+
+    /// consume array sizes or function parameters which follow a declarator
+    ///
+    /// function_parameters :=
+    /// Token::Punct(Punct::LeftParen)
+    ///     (parameter (Token::Punct(Punct::Comma) parameter)* (Token::Punct(Punct::Comma) Token::Punct(Punct::Ellipsis))?)?
+    /// Token::Punct(Punct::RightParen)
+    fn consume_suffixes(&mut self, tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<()> {
+        let mut post = vec![];
+        while let Some(token) = tokens.peek().cloned() {
+            match token.get_token() {
+                Token::Punct(Punct::LeftBracket) => {
+                    tokens.next(); // consume [
+                    post.push(Expression::parse_constant(tokens, typedefs)?);
+                    tokens.consume_punct(Punct::RightBracket)?;
+                }
+                Token::Punct(Punct::LeftParen) if post.is_empty() => {
+                    tokens.next(); // consume (
+                    let mut parameter_types = vec![];
+                    let mut is_variadic = false;
+                    let is_empty = match tokens.peek() {
+                        Some(token) => token.get_token() == &Token::Punct(Punct::RightParen),
+                        None => false,
+                    };
+                    if !is_empty {
+                        loop {
+                            let parameter = TypeStruct::parse_parameter(tokens, typedefs)?;
+                            match parameter.get_parameter_type() {
+                                Some(ty) => parameter_types.push(ty),
+                                None => return Err(ParseError::Unexpect(Some(token))),
+                            }
+                            if let Some(token) = tokens.peek() {
+                                if let Token::Punct(Punct::Comma) = token.get_token() {
+                                    tokens.next(); // consume ,
+                                    is_variadic = tokens.consume_punct(Punct::Ellipsis).is_ok();
+                                    if !is_variadic {
+                                        continue;
+                                    }
+                                }
+                            }
+                            break;
+                        }
+                    }
+                    tokens.consume_punct(Punct::RightParen)?;
+
+                    // a function cannot return an array or a function
+                    let return_type = self.get_unsized_type();
+                    if !self.post.is_empty() || return_type.is_function() {
+                        return Err(ParseError::Unexpect(Some(token)));
+                    }
+                    let function_type =
+                        Type::Function(Box::new(return_type), parameter_types, is_variadic);
+                    *self = TypeStruct::new(function_type, 0, self.identifier, vec![]);
+                    return Ok(());
+                }
+                _ => break,
+            }
+        }
+        // array dimensions of the declarator are outside of the ones from a typedef name
+        post.append(&mut self.post);
+        self.post = post;
+        Ok(())
+    }
+
+    /// get the type without array dimensions
+    ///
+    /// each pointer points to a type with the qualifiers which come before its `*`
+    pub fn get_unsized_type(&self) -> Type {
+        let mut ty = self.base.clone();
+        let mut qualifiers = self.qualifiers;
+        for pointer_qualifiers in self.pointers.iter() {
+            ty = Type::Pointer(Box::new(ty), qualifiers);
+            qualifiers = *pointer_qualifiers;
+        }
+        ty
+    }
+
+    /// get the type in a function type, which is resolved without array sizes
+    ///
+    /// this is enough for a parameter since an array parameter is adjusted to a pointer
+    fn get_parameter_type(&self) -> Option<Type> {
+        let ty = self.get_unsized_type();
+        match self.post.len() {
+            0 => Some(ty.adjust_parameter(self.get_qualifiers())),
+            1 => Some(Type::Pointer(Box::new(ty), self.get_qualifiers())),
+            // pointer to array is not representable
+            _ => None,
+        }
+    }
+
+    /// parse a type without identifier
+    ///
+    /// `qualifiers` are the ones which come before a storage class (e.g. `const static int`)
+    ///
+    /// type_name := type_specifier pointer*
+    pub fn parse_type_name(
+        tokens: &mut Tokens,
+        typedefs: &TypedefTable,
+        qualifiers: Qualifiers,
+    ) -> Result<TypeStruct> {
+        let mut type_struct = TypeStruct::consume_type_specifier(tokens, typedefs, qualifiers)?;
+        type_struct.consume_pointers(tokens)?;
+        Ok(type_struct)
+    }
+
+    /// pointer := Token::Punct(Punct::Star) type_qualifier*
+    fn consume_pointers(&mut self, tokens: &mut Tokens) -> Result<()> {
+        while let Some(token) = tokens.check_next_punct(Punct::Star) {
+            if !self.post.is_empty() {
+                // pointer to array is not representable
+                return Err(ParseError::Unexpect(Some(token.clone())));
+            }
+            tokens.next(); // consume *
+            let mut qualifiers = Qualifiers::default();
+            consume_type_qualifiers(tokens, &mut qualifiers);
+            self.pointers.push(qualifiers);
+        }
+        Ok(())
+    }
+
+    /// type qualifiers of a specifier apply to the outermost type of a typedef name
+    /// (e.g. `const intptr p` is a const pointer)
+    fn qualify(&mut self, qualifiers: Qualifiers) {
+        match self.pointers.last_mut() {
+            Some(pointer) => *pointer = pointer.merge(qualifiers),
+            None => self.qualifiers = self.qualifiers.merge(qualifiers),
+        }
+    }
+
+    /// type_specifier :=
+    /// type_qualifier*
+    ///     (
+    ///         (Token::Keyword(type specifier) type_qualifier*)+
+    ///         | Token::Keyword(Keyword::Enum) Token::Identifier
+    ///         | Token::Keyword(Keyword::Struct) Token::Identifier
+    ///         | typedef_name
+    ///     )
+    /// type_qualifier*
+    fn consume_type_specifier(
+        tokens: &mut Tokens,
+        typedefs: &TypedefTable,
+        qualifiers: Qualifiers,
+    ) -> Result<TypeStruct> {
+        let mut qualifiers = qualifiers;
+        consume_type_qualifiers(tokens, &mut qualifiers);
+        let mut type_struct =
+            TypeStruct::consume_unqualified_type_specifier(tokens, typedefs, &mut qualifiers)?;
+        consume_type_qualifiers(tokens, &mut qualifiers);
+        type_struct.qualify(qualifiers);
+        Ok(type_struct)
+    }
+
+    fn consume_unqualified_type_specifier(
+        tokens: &mut Tokens,
+        typedefs: &TypedefTable,
+        qualifiers: &mut Qualifiers,
+    ) -> Result<TypeStruct> {
+        if let Some(token) = tokens.peek() {
+            match token.get_token() {
+                Token::Keyword(Keyword::Enum) => {
+                    // enumerated types are compatible with int
+                    tokens.consume_keyword(Keyword::Enum)?;
+                    tokens.consume_identifier()?; // consume tag
+                    return Ok(TypeStruct::new(Type::int(), 0, Symbol::default(), vec![]));
+                }
+                Token::Keyword(Keyword::Struct) => {
+                    tokens.consume_keyword(Keyword::Struct)?;
+                    let tag = tokens.consume_identifier()?.get_token().get_identifier()?;
+                    return Ok(TypeStruct::new(
+                        Type::Struct(tag),
+                        0,
+                        Symbol::default(),
+                        vec![],
+                    ));
+                }
+                Token::Identifier(identifier) => {
+                    if let Some(type_struct) = typedefs.get(*identifier) {
+                        tokens.next(); // consume typedef name
+                        let mut type_struct = type_struct.clone();
+                        type_struct.identifier = Symbol::default();
+                        return Ok(type_struct);
+                    }
+                }
+                _ => (),
+            }
+        }
+        // the keywords of an arithmetic type can come in any order (e.g. long const unsigned int)
+        let mut last_token = tokens.consume_type()?;
+        let mut specifiers = vec![last_token.get_token().get_type()?];
+        loop {
+            consume_type_qualifiers(tokens, qualifiers);
+            if let Some(token) = tokens.peek() {
+                if token.get_token().get_type().is_ok() {
+                    last_token = tokens.consume_type()?;
+                    specifiers.push(last_token.get_token().get_type()?);
+                    continue;
+                }
+            }
+            break;
+        }
+        match Type::from_specifiers(&specifiers) {
+            Some(ty) => Ok(TypeStruct::new(ty, 0, Symbol::default(), vec![])),
+            None => Err(ParseError::Unexpect(Some(last_token))),
+        }
+    }
+    pub fn get_base(&self) -> &Type {
+        &self.base
+    }
+    pub fn get_identifier(&self) -> Symbol {
+        self.identifier
+    }
+    pub fn get_pointer(&self) -> u32 {
+        self.pointers.len() as u32
+    }
+    /// qualifiers of the base type
+    pub fn get_base_qualifiers(&self) -> Qualifiers {
+        self.qualifiers
+    }
+    /// qualifiers of each pointer from the innermost one
+    pub fn get_pointer_qualifiers(&self) -> &Vec<Qualifiers> {
+        &self.pointers
     }
-    pub fn get_identifier(&self) -> String {
-        self.identifier.clone()
+    /// qualifiers of the declared object (of its elements for an array)
+    pub fn get_qualifiers(&self) -> Qualifiers {
+        match self.pointers.last() {
+            Some(qualifiers) => *qualifiers,
+            None => self.qualifiers,
+        }
+    }
+    /// array sizes from the outermost dimension
+    pub fn get_post(&self) -> &Vec<Expression> {
+        &self.post
     }
     pub fn to_string(&self, tab_level: u32) -> String {
+        let mut ty = format!("{}{}", self.base, self.qualifiers);
+        for qualifiers in self.pointers.iter() {
+            ty += &format!("*{}", qualifiers);
+        }
         format!(
             "{}declare_statement -> type {}, identifier {}\n",
             get_space(tab_level),
-            self.base,
+            ty,
             self.identifier
         )
     }
 }
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct DeclareStatement {
+pub enum Initializer {
+    Expression(Expression),
+    List(Vec<Initializer>),
+}
+impl Initializer {
+    /// initializer :=
+    /// expression
+    /// | Token::Punct(Punct::LeftBrace) initializer (Token::Punct(Punct::Comma) initializer)* Token::Punct(Punct::Comma)? Token::Punct(Punct::RightBrace)
+    pub fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<Initializer> {
+        if let Some(token) = tokens.peek() {
+            if token.get_token() == &Token::Punct(Punct::LeftBrace) {
+                tokens.consume_punct(Punct::LeftBrace)?;
+                let mut initializers = vec![Initializer::parse(tokens, typedefs)?];
+                while let Some(token) = tokens.peek() {
+                    if let Token::Punct(Punct::Comma) = token.get_token() {
+                        tokens.next(); // consume ,
+                        if let Some(token) = tokens.peek() {
+                            if token.get_token() == &Token::Punct(Punct::RightBrace) {
+                                break; // trailing comma
+                            }
+                        }
+                        initializers.push(Initializer::parse(tokens, typedefs)?);
+                        continue;
+                    }
+                    break;
+                }
+                tokens.consume_punct(Punct::RightBrace)?;
+                return Ok(Initializer::List(initializers));
+            }
+        }
+        Ok(Initializer::Expression(Expression::parse(
+            tokens, typedefs,
+        )?))
+    }
+    pub fn to_string(&self, tab_level: u32) -> String {
+        match self {
+            Initializer::Expression(expression) => format!("{}\n", expression.to_string(tab_level)),
+            Initializer::List(initializers) => {
+                let mut s = format!("{}initializer_list\n", get_space(tab_level));
+                for initializer in initializers.iter() {
+                    s += &initializer.to_string(tab_level + 1);
+                }
+                s
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Declarator {
     pub type_struct: TypeStruct,
+    pub initializer: Option<Initializer>,
+    pub span: Span, // tokens of the declarator and its initializer
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeclareStatement {
+    pub storage_class: Option<StorageClass>,
+    pub alignments: Vec<Alignment>, // apply to all declarators
+    pub declarators: Vec<Declarator>,
+    pub span: Span,
 }
 impl DeclareStatement {
-    pub fn parse(tokens: &mut Tokens) -> Result<DeclareStatement> {
-        let type_struct = TypeStruct::parse(tokens)?;
-        tokens.consume_semicolon()?;
-        return Ok(DeclareStatement { type_struct });
+    /// parse and get declare_statement
+    ///
+    /// declare_statement :=
+    /// specifiers type_specifier init_declarator (Token::Punct(Punct::Comma) init_declarator)* Token::Punct(Punct::Semicolon)
+    /// init_declarator := declarator (Token::Punct(Punct::Assign) initializer)?
+    pub fn parse(tokens: &mut Tokens, typedefs: &mut TypedefTable) -> Result<DeclareStatement> {
+        let checkpoint = tokens.checkpoint();
+        let specifiers = Specifiers::parse(tokens, typedefs)?;
+        if specifiers.is_inline() {
+            // inline is only for functions
+            return Err(ParseError::Unexpect(specifiers.inline));
+        }
+        let specifier =
+            TypeStruct::consume_type_specifier(tokens, typedefs, specifiers.qualifiers)?;
+        let mut declarators = vec![];
+        loop {
+            let declarator_checkpoint = tokens.checkpoint();
+            let type_struct = TypeStruct::parse_declarator(tokens, typedefs, &specifier)?;
+            // the scope of an identifier begins just after its declarator
+            typedefs.insert_ordinary(type_struct.get_identifier());
+            let mut initializer = None;
+            if let Some(_token) = tokens.check_next_punct(Punct::Assign) {
+                tokens.next(); // consume "="
+                initializer = Some(Initializer::parse(tokens, typedefs)?);
+            }
+            declarators.push(Declarator {
+                type_struct,
+                initializer,
+                span: tokens.span_since(declarator_checkpoint),
+            });
+            if let Some(token) = tokens.peek() {
+                if let Token::Punct(Punct::Comma) = token.get_token() {
+                    tokens.next(); // consume ,
+                    continue;
+                }
+            }
+            break;
+        }
+        tokens.consume_punct(Punct::Semicolon)?;
+        Ok(DeclareStatement {
+            storage_class: specifiers.storage_class,
+            alignments: specifiers.alignments,
+            declarators,
+            span: tokens.span_since(checkpoint),
+        })
     }
     pub fn to_string(&self, tab_level: u32) -> String {
-        format!(
-            "{}declare_statement -> {}\n",
-            get_space(tab_level),
-            self.type_struct.to_string(tab_level),
-        )
+        let mut s = "".to_owned();
+        for declarator in self.declarators.iter() {
+            s += &format!(
+                "{}declare_statement -> storage_class {:?} {}",
+                get_space(tab_level),
+                self.storage_class,
+                declarator.type_struct.to_string(tab_level),
+            );
+            for alignment in self.alignments.iter() {
+                s += &alignment.to_string(tab_level + 1);
+            }
+            if let Some(initializer) = &declarator.initializer {
+                s += &initializer.to_string(tab_level + 1);
+            }
+        }
+        s
     }
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct ExpressionStatement {
     pub expression: Expression,
+    pub span: Span,
 }
 impl ExpressionStatement {
     /// parse and get expression_statement
     ///
-    /// expression_statement := expression_node Token::Semicolon
-    pub fn parse(tokens: &mut Tokens) -> Result<ExpressionStatement> {
-        if let Some(token) = tokens.peek() {
-            match token.get_token() {
-                Token::Number(_) | Token::Identifier(_) => {
-                    let expression = Expression::parse(tokens)?;
-                    tokens.consume_semicolon()?;
-                    return Ok(ExpressionStatement { expression });
-                }
-                _ => (),
-            }
-        }
-        Err(ParseError::Unexpect(tokens.next()))
+    /// expression_statement := expression Token::Punct(Punct::Semicolon)
+    pub fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<ExpressionStatement> {
+        let checkpoint = tokens.checkpoint();
+        let expression = Expression::parse(tokens, typedefs)?;
+        tokens.consume_punct(Punct::Semicolon)?;
+        Ok(ExpressionStatement {
+            expression,
+            span: tokens.span_since(checkpoint),
+        })
     }
     pub fn to_string(&self, tab_level: u32) -> String {
         format!(
@@ -124,16 +647,21 @@ impl ExpressionStatement {
 #[derive(Clone, Debug, PartialEq)]
 pub struct ReturnStatement {
     pub expression: Expression,
+    pub span: Span,
 }
 impl ReturnStatement {
     /// parse and get return_statement
     ///
-    /// return_statement := Token::Return expression_node Token::Semicolon
-    pub fn parse(tokens: &mut Tokens) -> Result<ReturnStatement> {
-        tokens.consume_return()?;
-        let expression = Expression::parse(tokens)?;
-        tokens.consume_semicolon()?;
-        Ok(ReturnStatement { expression })
+    /// return_statement := Token::Keyword(Keyword::Return) expression_node Token::Punct(Punct::Semicolon)
+    pub fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<ReturnStatement> {
+        let checkpoint = tokens.checkpoint();
+        tokens.consume_keyword(Keyword::Return)?;
+        let expression = Expression::parse(tokens, typedefs)?;
+        tokens.consume_punct(Punct::Semicolon)?;
+        Ok(ReturnStatement {
+            expression,
+            span: tokens.span_since(checkpoint),
+        })
     }
 }
 
@@ -142,6 +670,371 @@ mod tests {
 
     use super::super::testutil::*;
     use super::*;
+    use crate::types::IntegerType;
+
+    #[cfg(test)]
+    mod test_statement {
+        use super::*;
+        use crate::expression::{Exp11, Exp12, Exp13, Exp15, Exp16, Exp2};
+        use rcc_syntax::Tokenizer;
+
+        // foo * bar;
+        fn foo_mul_bar() -> Tokens {
+            Tokens::new(vec![
+                mtoken(Token::Identifier(Symbol::intern("foo"))),
+                mtoken(Token::Punct(Punct::Star)),
+                mtoken(Token::Identifier(Symbol::intern("bar"))),
+                mtoken(Token::Punct(Punct::Semicolon)),
+            ])
+        }
+
+        #[test]
+        fn typedef_name_starts_declaration() {
+            let mut typedefs = TypedefTable::new();
+            typedefs.insert_typedef(TypeStruct::new(
+                Type::int(),
+                0,
+                Symbol::intern("foo"),
+                vec![],
+            ));
+            let actual = Statement::parse(&mut foo_mul_bar(), &mut typedefs).unwrap();
+            let expect = Statement::Declare(DeclareStatement {
+                storage_class: None,
+                alignments: vec![],
+                declarators: vec![Declarator {
+                    type_struct: TypeStruct::new(Type::int(), 1, Symbol::intern("bar"), vec![]),
+                    initializer: None,
+                    span: Span::default(),
+                }],
+                span: Span::default(),
+            });
+            assert_eq!(actual, Some(expect));
+            assert!(!typedefs.is_typedef_name(Symbol::intern("bar")));
+        }
+
+        fn number(num: u64) -> Expression {
+            Expression {
+                expression: Exp2::Single(Exp11::Single(Exp12::Single(Exp13::Single(
+                    Exp15::Single(Exp16::Number(num, IntegerType::int())),
+                )))),
+                span: Span::default(),
+            }
+        }
+
+        #[test]
+        fn multiple_declarators_with_initializers() {
+            // int a, b = 2, *c, d[2] = { 1, { 2 }, };
+            let mut tokens = Tokens::new(vec![
+                mtoken(Token::Keyword(Keyword::Int)),
+                mtoken(Token::Identifier(Symbol::intern("a"))),
+                mtoken(Token::Punct(Punct::Comma)),
+                mtoken(Token::Identifier(Symbol::intern("b"))),
+                mtoken(Token::Punct(Punct::Assign)),
+                mtoken(number_token(2)),
+                mtoken(Token::Punct(Punct::Comma)),
+                mtoken(Token::Punct(Punct::Star)),
+                mtoken(Token::Identifier(Symbol::intern("c"))),
+                mtoken(Token::Punct(Punct::Comma)),
+                mtoken(Token::Identifier(Symbol::intern("d"))),
+                mtoken(Token::Punct(Punct::LeftBracket)),
+                mtoken(number_token(2)),
+                mtoken(Token::Punct(Punct::RightBracket)),
+                mtoken(Token::Punct(Punct::Assign)),
+                mtoken(Token::Punct(Punct::LeftBrace)),
+                mtoken(number_token(1)),
+                mtoken(Token::Punct(Punct::Comma)),
+                mtoken(Token::Punct(Punct::LeftBrace)),
+                mtoken(number_token(2)),
+                mtoken(Token::Punct(Punct::RightBrace)),
+                mtoken(Token::Punct(Punct::Comma)),
+                mtoken(Token::Punct(Punct::RightBrace)),
+                mtoken(Token::Punct(Punct::Semicolon)),
+            ]);
+            let actual = Statement::parse(&mut tokens, &mut TypedefTable::new()).unwrap();
+            let declarator = |pointer, identifier: &str, post, initializer| Declarator {
+                type_struct: TypeStruct::new(
+                    Type::int(),
+                    pointer,
+                    Symbol::intern(identifier),
+                    post,
+                ),
+                initializer,
+                span: Span::default(),
+            };
+            let expect = Statement::Declare(DeclareStatement {
+                storage_class: None,
+                alignments: vec![],
+                declarators: vec![
+                    declarator(0, "a", vec![], None),
+                    declarator(0, "b", vec![], Some(Initializer::Expression(number(2)))),
+                    declarator(1, "c", vec![], None),
+                    declarator(
+                        0,
+                        "d",
+                        vec![number(2)],
+                        Some(Initializer::List(vec![
+                            Initializer::Expression(number(1)),
+                            Initializer::List(vec![Initializer::Expression(number(2))]),
+                        ])),
+                    ),
+                ],
+                span: Span::default(),
+            });
+            assert_eq!(actual, Some(expect));
+            assert_eq!(tokens.len(), 0);
+        }
+
+        #[test]
+        fn ordinary_identifier_starts_expression() {
+            let actual = Statement::parse(&mut foo_mul_bar(), &mut TypedefTable::new()).unwrap();
+            match actual {
+                Some(Statement::Expression(_)) => (),
+                _ => panic!("expect expression statement: {:?}", actual),
+            }
+        }
+
+        #[test]
+        fn unary_operator_statement() {
+            // -a;
+            let mut tokens = Tokens::new(Tokenizer::tokenize(&"-a;".to_owned()).unwrap());
+            let actual = Statement::parse(&mut tokens, &mut TypedefTable::new()).unwrap();
+            match actual {
+                Some(Statement::Expression(statement)) => match statement.expression.expression {
+                    Exp2::Single(Exp11::Single(Exp12::Single(Exp13::Single(Exp15::Minus(_))))) => {}
+                    exp => panic!("expect unary minus: {:?}", exp),
+                },
+                _ => panic!("expect expression statement: {:?}", actual),
+            }
+            assert_eq!(tokens.len(), 0);
+        }
+
+        #[test]
+        fn call_through_pointer_statement() {
+            // (*op)(x);
+            let mut tokens = Tokens::new(Tokenizer::tokenize(&"(*op)(x);".to_owned()).unwrap());
+            let actual = Statement::parse(&mut tokens, &mut TypedefTable::new()).unwrap();
+            let exp16 = match actual {
+                Some(Statement::Expression(statement)) => match statement.expression.expression {
+                    Exp2::Single(Exp11::Single(Exp12::Single(Exp13::Single(Exp15::Single(
+                        exp,
+                    ))))) => exp,
+                    exp => panic!("expect function call: {:?}", exp),
+                },
+                _ => panic!("expect expression statement: {:?}", actual),
+            };
+            match exp16 {
+                Exp16::FunctionCall(function, arguments) => {
+                    assert_eq!(arguments.len(), 1);
+                    match *function {
+                        Exp16::Parenthesis(expression) => match expression.expression {
+                            Exp2::Single(Exp11::Single(Exp12::Single(Exp13::Single(
+                                Exp15::Indirection(_),
+                            )))) => (),
+                            exp => panic!("expect indirection: {:?}", exp),
+                        },
+                        exp => panic!("expect parenthesis: {:?}", exp),
+                    }
+                }
+                exp => panic!("expect function call: {:?}", exp),
+            }
+            assert_eq!(tokens.len(), 0);
+        }
+
+        #[test]
+        fn statement_spans() {
+            let code = "int a = 1,\n  b;\na = b;".to_owned();
+            let mut tokens = Tokens::new(Tokenizer::tokenize(&code).unwrap());
+            let mut typedefs = TypedefTable::new();
+            let span = |start, end| Span::new(Default::default(), start, end);
+            let declare = Statement::parse(&mut tokens, &mut typedefs)
+                .unwrap()
+                .unwrap();
+            assert_eq!(declare.span(), span(0, 15));
+            match declare {
+                Statement::Declare(statement) => {
+                    let spans: Vec<Span> = statement.declarators.iter().map(|d| d.span).collect();
+                    assert_eq!(spans, vec![span(4, 9), span(13, 14)]);
+                }
+                _ => panic!("expect declare statement: {:?}", declare),
+            }
+            let expression = Statement::parse(&mut tokens, &mut typedefs)
+                .unwrap()
+                .unwrap();
+            assert_eq!(expression.span(), span(16, 22));
+        }
+    }
+
+    #[cfg(test)]
+    mod test_type_struct {
+        use super::*;
+        use crate::types::IntegerKind;
+
+        #[test]
+        fn multiple_keywords() {
+            // unsigned long long a;
+            let mut tokens = Tokens::new(vec![
+                mtoken(Token::Keyword(Keyword::Unsigned)),
+                mtoken(Token::Keyword(Keyword::Long)),
+                mtoken(Token::Keyword(Keyword::Long)),
+                mtoken(Token::Identifier(Symbol::intern("a"))),
+            ]);
+            let actual = TypeStruct::parse(&mut tokens, &TypedefTable::new()).unwrap();
+            let base = Type::Integer(IntegerType::new(IntegerKind::LongLong, false));
+            assert_eq!(
+                actual,
+                TypeStruct::new(base, 0, Symbol::intern("a"), vec![])
+            );
+        }
+
+        #[test]
+        fn qualifiers_at_each_pointer_level() {
+            // unsigned const int * volatile * const a;
+            let mut tokens = Tokens::new(vec![
+                mtoken(Token::Keyword(Keyword::Unsigned)),
+                mtoken(Token::Keyword(Keyword::Const)),
+                mtoken(Token::Keyword(Keyword::Int)),
+                mtoken(Token::Punct(Punct::Star)),
+                mtoken(Token::Keyword(Keyword::Volatile)),
+                mtoken(Token::Punct(Punct::Star)),
+                mtoken(Token::Keyword(Keyword::Const)),
+                mtoken(Token::Identifier(Symbol::intern("a"))),
+            ]);
+            let actual = TypeStruct::parse(&mut tokens, &TypedefTable::new()).unwrap();
+            let (constant, volatile) = (
+                Qualifiers {
+                    is_const: true,
+                    is_volatile: false,
+                },
+                Qualifiers {
+                    is_const: false,
+                    is_volatile: true,
+                },
+            );
+            assert_eq!(actual.get_pointer(), 2);
+            assert_eq!(actual.get_base_qualifiers(), constant);
+            assert_eq!(actual.get_pointer_qualifiers(), &vec![volatile, constant]);
+            assert_eq!(actual.get_qualifiers(), constant);
+            let unsigned = Type::from_specifiers(&[Keyword::Unsigned, Keyword::Int]).unwrap();
+            assert_eq!(
+                actual.get_unsized_type(),
+                Type::Pointer(
+                    Box::new(Type::Pointer(Box::new(unsigned), constant)),
+                    volatile
+                )
+            );
+        }
+
+        #[test]
+        fn qualified_typedef_name() {
+            // typedef int *intptr; volatile intptr a;
+            let mut typedefs = TypedefTable::new();
+            typedefs.insert_typedef(TypeStruct::new(
+                Type::int(),
+                1,
+                Symbol::intern("intptr"),
+                vec![],
+            ));
+            let mut tokens = Tokens::new(vec![
+                mtoken(Token::Keyword(Keyword::Volatile)),
+                mtoken(Token::Identifier(Symbol::intern("intptr"))),
+                mtoken(Token::Identifier(Symbol::intern("a"))),
+            ]);
+            let actual = TypeStruct::parse(&mut tokens, &typedefs).unwrap();
+            assert_eq!(actual.get_base_qualifiers(), Qualifiers::default());
+            assert!(actual.get_qualifiers().is_volatile);
+        }
+
+        #[test]
+        fn array_of_function_pointers() {
+            // int (*ops[2])(int, char *);
+            let mut tokens = Tokens::new(vec![
+                mtoken(Token::Keyword(Keyword::Int)),
+                mtoken(Token::Punct(Punct::LeftParen)),
+                mtoken(Token::Punct(Punct::Star)),
+                mtoken(Token::Identifier(Symbol::intern("ops"))),
+                mtoken(Token::Punct(Punct::LeftBracket)),
+                mtoken(number_token(2)),
+                mtoken(Token::Punct(Punct::RightBracket)),
+                mtoken(Token::Punct(Punct::RightParen)),
+                mtoken(Token::Punct(Punct::LeftParen)),
+                mtoken(Token::Keyword(Keyword::Int)),
+                mtoken(Token::Punct(Punct::Comma)),
+                mtoken(Token::Keyword(Keyword::Char)),
+                mtoken(Token::Punct(Punct::Star)),
+                mtoken(Token::Punct(Punct::RightParen)),
+                mtoken(Token::Punct(Punct::Semicolon)),
+            ]);
+            let actual = TypeStruct::parse(&mut tokens, &TypedefTable::new()).unwrap();
+            let char_pointer = Type::Pointer(
+                Box::new(Type::from_specifiers(&[Keyword::Char]).unwrap()),
+                Qualifiers::default(),
+            );
+            let function = Type::Function(
+                Box::new(Type::int()),
+                vec![Type::int(), char_pointer],
+                false,
+            );
+            assert_eq!(actual.get_base(), &function);
+            assert_eq!(actual.get_pointer(), 1);
+            assert_eq!(actual.get_identifier(), "ops");
+            assert_eq!(actual.get_post().len(), 1);
+            assert_eq!(tokens.len(), 1);
+        }
+
+        #[test]
+        fn abstract_function_pointer_parameter() {
+            // int (*)(int f(int))
+            let mut tokens = Tokens::new(vec![
+                mtoken(Token::Keyword(Keyword::Int)),
+                mtoken(Token::Punct(Punct::LeftParen)),
+                mtoken(Token::Punct(Punct::Star)),
+                mtoken(Token::Punct(Punct::RightParen)),
+                mtoken(Token::Punct(Punct::LeftParen)),
+                mtoken(Token::Keyword(Keyword::Int)),
+                mtoken(Token::Identifier(Symbol::intern("f"))),
+                mtoken(Token::Punct(Punct::LeftParen)),
+                mtoken(Token::Keyword(Keyword::Int)),
+                mtoken(Token::Punct(Punct::RightParen)),
+                mtoken(Token::Punct(Punct::RightParen)),
+            ]);
+            let actual = TypeStruct::parse_parameter(&mut tokens, &TypedefTable::new()).unwrap();
+            // a function parameter is adjusted to a function pointer
+            let function = Type::Function(
+                Box::new(Type::int()),
+                vec![Type::Pointer(
+                    Box::new(Type::Function(
+                        Box::new(Type::int()),
+                        vec![Type::int()],
+                        false,
+                    )),
+                    Qualifiers::default(),
+                )],
+                false,
+            );
+            assert_eq!(
+                actual,
+                TypeStruct::new(function, 1, Symbol::default(), vec![])
+            );
+            assert_eq!(tokens.len(), 0);
+        }
+
+        #[test]
+        fn invalid_keywords() {
+            // short long a;
+            let mut tokens = Tokens::new(vec![
+                mtoken(Token::Keyword(Keyword::Short)),
+                mtoken(Token::Keyword(Keyword::Long)),
+                mtoken(Token::Identifier(Symbol::intern("a"))),
+            ]);
+            let actual = TypeStruct::parse(&mut tokens, &TypedefTable::new());
+            assert_eq!(
+                actual,
+                Err(ParseError::Unexpect(Some(mtoken(Token::Keyword(
+                    Keyword::Long
+                )))))
+            );
+        }
+    }
 
     #[cfg(test)]
     mod test_return_statement {
@@ -151,23 +1044,30 @@ mod tests {
         #[test]
         fn pass() {
             let mut tokens = Tokens::new(vec![
-                mtoken(Token::Return),
-                mtoken(Token::Number(10)),
-                mtoken(Token::Semicolon),
+                mtoken(Token::Keyword(Keyword::Return)),
+                mtoken(number_token(10)),
+                mtoken(Token::Punct(Punct::Semicolon)),
             ]);
-            let actual = ReturnStatement::parse(&mut tokens).unwrap();
+            let actual = ReturnStatement::parse(&mut tokens, &TypedefTable::new()).unwrap();
             let expect = ReturnStatement {
                 expression: *num(10),
+                span: Span::default(),
             };
             assert_eq!(actual, expect);
         }
         */
 
         #[test]
-        #[should_panic(expected = "expect [Token::Semicolon]: Consume(Consume(Some(number: 10)))")]
+        #[should_panic(
+            expected = "expect [Token::Punct(Punct::Semicolon)]: Consume(Consume(Some(number: 10)))"
+        )]
         fn fail_without_return() {
-            let mut tokens = Tokens::new(vec![mtoken(Token::Number(10)), mtoken(Token::Semicolon)]);
-            ReturnStatement::parse(&mut tokens).expect("expect [Token::Semicolon]");
+            let mut tokens = Tokens::new(vec![
+                mtoken(number_token(10)),
+                mtoken(Token::Punct(Punct::Semicolon)),
+            ]);
+            ReturnStatement::parse(&mut tokens, &TypedefTable::new())
+                .expect("expect [Token::Punct(Punct::Semicolon)]");
         }
     }
 }
@@ -0,0 +1,100 @@
+use super::error::ParseError;
+use super::expression::Expression;
+use super::typedef::TypedefTable;
+use super::util::get_space;
+use rcc_syntax::span::Span;
+use rcc_syntax::token::{Keyword, Punct, Token};
+use rcc_syntax::tokens::Tokens;
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StaticAssertDeclaration {
+    pub expression: Expression,
+    pub message: String,
+    pub span: Span, // reported on failure
+}
+impl StaticAssertDeclaration {
+    /// parse and get static_assert_declaration
+    ///
+    /// adjacent string literals of the message are concatenated
+    ///
+    /// static_assert_declaration :=
+    /// Token::Keyword(Keyword::StaticAssert)
+    ///     Token::Punct(Punct::LeftParen)
+    ///         constant_expression Token::Punct(Punct::Comma) Token::StringLiteral+
+    ///     Token::Punct(Punct::RightParen)
+    /// Token::Punct(Punct::Semicolon)
+    pub fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<StaticAssertDeclaration> {
+        let checkpoint = tokens.checkpoint();
+        tokens.consume_keyword(Keyword::StaticAssert)?;
+        tokens.consume_punct(Punct::LeftParen)?;
+        let expression = Expression::parse_constant(tokens, typedefs)?;
+        tokens.consume_punct(Punct::Comma)?;
+        let mut message = tokens
+            .consume_string_literal()?
+            .get_token()
+            .get_string_literal()?;
+        while let Some(token) = tokens.peek() {
+            if let Token::StringLiteral(string) = token.get_token() {
+                message += string;
+                tokens.next();
+                continue;
+            }
+            break;
+        }
+        tokens.consume_punct(Punct::RightParen)?;
+        tokens.consume_punct(Punct::Semicolon)?;
+        Ok(StaticAssertDeclaration {
+            expression,
+            message,
+            span: tokens.span_since(checkpoint),
+        })
+    }
+
+    pub fn to_string(&self, tab_level: u32) -> String {
+        format!(
+            "{}static_assert_declaration -> message {:?}\n{}\n",
+            get_space(tab_level),
+            self.message,
+            self.expression.to_string(tab_level + 1)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::expression::{Exp11, Exp12, Exp13, Exp15, Exp16, Exp2};
+    use super::super::testutil::*;
+    use super::super::types::IntegerType;
+    use super::*;
+
+    #[test]
+    fn concatenated_message() {
+        // _Static_assert(1, "one " "message");
+        let mut tokens = Tokens::new(vec![
+            mtoken(Token::Keyword(Keyword::StaticAssert)),
+            mtoken(Token::Punct(Punct::LeftParen)),
+            mtoken(number_token(1)),
+            mtoken(Token::Punct(Punct::Comma)),
+            mtoken(Token::StringLiteral("one ".to_owned())),
+            mtoken(Token::StringLiteral("message".to_owned())),
+            mtoken(Token::Punct(Punct::RightParen)),
+            mtoken(Token::Punct(Punct::Semicolon)),
+        ]);
+        let actual = StaticAssertDeclaration::parse(&mut tokens, &TypedefTable::new()).unwrap();
+        let expect = StaticAssertDeclaration {
+            expression: Expression {
+                expression: Exp2::Single(Exp11::Single(Exp12::Single(Exp13::Single(
+                    Exp15::Single(Exp16::Number(1, IntegerType::int())),
+                )))),
+                span: Span::default(),
+            },
+            message: "one message".to_owned(),
+            span: Span::default(),
+        };
+        assert_eq!(actual, expect);
+        assert_eq!(tokens.len(), 0);
+    }
+}
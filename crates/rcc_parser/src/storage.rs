@@ -0,0 +1,197 @@
+use super::error::ParseError;
+use super::expression::{Exp15, Expression};
+use super::statement::{consume_type_qualifiers, TypeStruct};
+use super::typedef::TypedefTable;
+use super::types::Qualifiers;
+use super::util::get_space;
+use rcc_syntax::token::{Keyword, ManagedToken, Punct, Token};
+use rcc_syntax::tokens::Tokens;
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StorageClass {
+    Static,
+    Extern,
+}
+
+/// operand of an alignment specifier
+#[derive(Clone, Debug, PartialEq)]
+pub enum Alignment {
+    Type(TypeStruct),
+    Expression(Expression),
+}
+impl Alignment {
+    /// alignment_specifier :=
+    /// Token::Keyword(Keyword::Alignas) Token::Punct(Punct::LeftParen) (type_name | constant_expression) Token::Punct(Punct::RightParen)
+    fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<Alignment> {
+        tokens.consume_keyword(Keyword::Alignas)?;
+        let alignment = if Exp15::is_next_type_name(tokens, typedefs) {
+            tokens.consume_punct(Punct::LeftParen)?;
+            Alignment::Type(TypeStruct::parse_type_name(
+                tokens,
+                typedefs,
+                Qualifiers::default(),
+            )?)
+        } else {
+            tokens.consume_punct(Punct::LeftParen)?;
+            Alignment::Expression(Expression::parse_constant(tokens, typedefs)?)
+        };
+        tokens.consume_punct(Punct::RightParen)?;
+        Ok(alignment)
+    }
+
+    /// alignment_specifier*
+    pub fn parse_all(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<Vec<Alignment>> {
+        let mut alignments = vec![];
+        while let Some(token) = tokens.peek() {
+            if token.get_token() != &Token::Keyword(Keyword::Alignas) {
+                break;
+            }
+            alignments.push(Alignment::parse(tokens, typedefs)?);
+        }
+        Ok(alignments)
+    }
+
+    pub fn to_string(&self, tab_level: u32) -> String {
+        match self {
+            Alignment::Type(type_name) => format!(
+                "{}alignas\n{}",
+                get_space(tab_level),
+                type_name.to_string(tab_level + 1)
+            ),
+            Alignment::Expression(expression) => format!(
+                "{}alignas\n{}\n",
+                get_space(tab_level),
+                expression.to_string(tab_level + 1)
+            ),
+        }
+    }
+}
+
+/// storage class specifier, function specifier and alignment specifiers of a declaration
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Specifiers {
+    pub storage_class: Option<StorageClass>,
+    pub inline: Option<ManagedToken>,
+    pub alignas: Option<ManagedToken>, // the first alignment specifier
+    pub alignments: Vec<Alignment>,
+    pub qualifiers: Qualifiers,
+}
+impl Specifiers {
+    /// specifiers :=
+    /// (Token::Keyword(Keyword::Static) | Token::Keyword(Keyword::Extern) | Token::Keyword(Keyword::Inline) | alignment_specifier | type_qualifier)*
+    ///
+    /// a declaration can have at most one storage class specifier
+    pub fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<Specifiers> {
+        let mut specifiers = Specifiers::default();
+        loop {
+            consume_type_qualifiers(tokens, &mut specifiers.qualifiers);
+            let token = match tokens.peek() {
+                Some(token) => token,
+                None => break,
+            };
+            let storage_class = match token.get_token() {
+                Token::Keyword(Keyword::Static) => StorageClass::Static,
+                Token::Keyword(Keyword::Extern) => StorageClass::Extern,
+                Token::Keyword(Keyword::Inline) => {
+                    specifiers.inline = tokens.next();
+                    continue;
+                }
+                Token::Keyword(Keyword::Alignas) => {
+                    if specifiers.alignas.is_none() {
+                        specifiers.alignas = Some(token.clone());
+                    }
+                    let alignment = Alignment::parse(tokens, typedefs)?;
+                    specifiers.alignments.push(alignment);
+                    continue;
+                }
+                _ => break,
+            };
+            if specifiers.storage_class.is_some() {
+                return Err(ParseError::Unexpect(tokens.next()));
+            }
+            tokens.next(); // consume storage class specifier
+            specifiers.storage_class = Some(storage_class);
+        }
+        Ok(specifiers)
+    }
+
+    pub fn is_inline(&self) -> bool {
+        self.inline.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::expression::{Exp11, Exp12, Exp13, Exp16, Exp2};
+    use super::super::testutil::*;
+    use super::super::types::{IntegerKind, IntegerType, Type};
+    use super::*;
+    use rcc_syntax::span::Span;
+    use rcc_syntax::symbol::Symbol;
+
+    #[test]
+    fn static_inline() {
+        let mut tokens = Tokens::new(vec![
+            mtoken(Token::Keyword(Keyword::Static)),
+            mtoken(Token::Keyword(Keyword::Inline)),
+            mtoken(Token::Keyword(Keyword::Int)),
+        ]);
+        let actual = Specifiers::parse(&mut tokens, &TypedefTable::new()).unwrap();
+        assert_eq!(actual.storage_class, Some(StorageClass::Static));
+        assert!(actual.is_inline());
+        assert_eq!(tokens.len(), 1);
+    }
+
+    #[test]
+    fn multiple_storage_classes() {
+        let mut tokens = Tokens::new(vec![
+            mtoken(Token::Keyword(Keyword::Static)),
+            mtoken(Token::Keyword(Keyword::Extern)),
+        ]);
+        let actual = Specifiers::parse(&mut tokens, &TypedefTable::new());
+        assert_eq!(
+            actual,
+            Err(ParseError::Unexpect(Some(mtoken(Token::Keyword(
+                Keyword::Extern
+            )))))
+        );
+    }
+
+    #[test]
+    fn alignment_specifiers() {
+        // _Alignas(16) static _Alignas(long) int
+        let mut tokens = Tokens::new(vec![
+            mtoken(Token::Keyword(Keyword::Alignas)),
+            mtoken(Token::Punct(Punct::LeftParen)),
+            mtoken(number_token(16)),
+            mtoken(Token::Punct(Punct::RightParen)),
+            mtoken(Token::Keyword(Keyword::Static)),
+            mtoken(Token::Keyword(Keyword::Alignas)),
+            mtoken(Token::Punct(Punct::LeftParen)),
+            mtoken(Token::Keyword(Keyword::Long)),
+            mtoken(Token::Punct(Punct::RightParen)),
+            mtoken(Token::Keyword(Keyword::Int)),
+        ]);
+        let actual = Specifiers::parse(&mut tokens, &TypedefTable::new()).unwrap();
+        let long = Type::Integer(IntegerType::new(IntegerKind::Long, true));
+        let expect = vec![
+            Alignment::Expression(Expression {
+                expression: Exp2::Single(Exp11::Single(Exp12::Single(Exp13::Single(
+                    Exp15::Single(Exp16::Number(16, IntegerType::int())),
+                )))),
+                span: Span::default(),
+            }),
+            Alignment::Type(TypeStruct::new(long, 0, Symbol::default(), vec![])),
+        ];
+        assert_eq!(actual.storage_class, Some(StorageClass::Static));
+        assert_eq!(
+            actual.alignas,
+            Some(mtoken(Token::Keyword(Keyword::Alignas)))
+        );
+        assert_eq!(actual.alignments, expect);
+        assert_eq!(tokens.len(), 1);
+    }
+}
@@ -0,0 +1,202 @@
+use super::error::ParseError;
+use super::expression::Expression;
+use super::statement::TypeStruct;
+use super::storage::Alignment;
+use super::typedef::TypedefTable;
+use super::util::get_space;
+use rcc_syntax::span::Span;
+use rcc_syntax::symbol::Symbol;
+use rcc_syntax::token::{Keyword, Punct, Token};
+use rcc_syntax::tokens::Tokens;
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+/// member of a structure (the identifier of an unnamed bit-field is empty)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Member {
+    pub type_struct: TypeStruct,
+    pub width: Option<Expression>, // width of a bit-field
+    pub alignments: Vec<Alignment>,
+}
+impl Member {
+    /// member_declarator := declarator (Token::Punct(Punct::Colon) constant_expression)? | Token::Punct(Punct::Colon) constant_expression
+    fn parse(
+        tokens: &mut Tokens,
+        typedefs: &TypedefTable,
+        specifier: &TypeStruct,
+        alignments: &[Alignment],
+    ) -> Result<Member> {
+        let type_struct = match tokens.peek() {
+            Some(token) if token.get_token() == &Token::Punct(Punct::Colon) => specifier.clone(),
+            _ => TypeStruct::parse_declarator(tokens, typedefs, specifier)?,
+        };
+        let mut width = None;
+        if tokens.consume_punct(Punct::Colon).is_ok() {
+            width = Some(Expression::parse_constant(tokens, typedefs)?);
+        }
+        Ok(Member {
+            type_struct,
+            width,
+            alignments: alignments.to_vec(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructDeclaration {
+    pub tag: Symbol,
+    pub members: Vec<Member>,
+    pub span: Span,
+}
+impl StructDeclaration {
+    /// check whether the next tokens start a struct declaration (not `struct tag identifier`)
+    pub fn is_next(tokens: &Tokens) -> bool {
+        let token = |n| tokens.peek_nth(n).map(|token| token.get_token());
+        matches!(
+            (token(0), token(1), token(2)),
+            (
+                Some(Token::Keyword(Keyword::Struct)),
+                Some(Token::Identifier(_)),
+                Some(Token::Punct(Punct::LeftBrace))
+            )
+        )
+    }
+
+    /// parse and get struct_declaration
+    ///
+    /// struct_declaration :=
+    /// Token::Keyword(Keyword::Struct) Token::Identifier
+    ///     Token::Punct(Punct::LeftBrace) member_declaration+ Token::Punct(Punct::RightBrace)
+    /// Token::Punct(Punct::Semicolon)
+    /// member_declaration :=
+    /// alignment_specifier* type_specifier
+    ///     member_declarator (Token::Punct(Punct::Comma) member_declarator)* Token::Punct(Punct::Semicolon)
+    pub fn parse(tokens: &mut Tokens, typedefs: &TypedefTable) -> Result<StructDeclaration> {
+        let checkpoint = tokens.checkpoint();
+        tokens.consume_keyword(Keyword::Struct)?;
+        let tag = tokens.consume_identifier()?.get_token().get_identifier()?;
+        tokens.consume_punct(Punct::LeftBrace)?;
+
+        let mut members = vec![];
+        loop {
+            let alignments = Alignment::parse_all(tokens, typedefs)?;
+            let specifier = TypeStruct::parse_specifier(tokens, typedefs)?;
+            members.push(Member::parse(tokens, typedefs, &specifier, &alignments)?);
+            while tokens.consume_punct(Punct::Comma).is_ok() {
+                members.push(Member::parse(tokens, typedefs, &specifier, &alignments)?);
+            }
+            tokens.consume_punct(Punct::Semicolon)?;
+            if let Some(token) = tokens.peek() {
+                if let Token::Punct(Punct::RightBrace) = token.get_token() {
+                    break;
+                }
+            }
+        }
+
+        tokens.consume_punct(Punct::RightBrace)?;
+        tokens.consume_punct(Punct::Semicolon)?;
+        Ok(StructDeclaration {
+            tag,
+            members,
+            span: tokens.span_since(checkpoint),
+        })
+    }
+
+    pub fn to_string(&self, tab_level: u32) -> String {
+        let mut s = format!(
+            "{}struct_declaration -> tag {}\n",
+            get_space(tab_level),
+            self.tag
+        );
+        for member in self.members.iter() {
+            s += &member.type_struct.to_string(tab_level + 1);
+            if let Some(width) = &member.width {
+                s += &format!("{}\n", width.to_string(tab_level + 2));
+            }
+            for alignment in member.alignments.iter() {
+                s += &alignment.to_string(tab_level + 2);
+            }
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::expression::{Exp11, Exp12, Exp13, Exp15, Exp16, Exp2};
+    use super::super::testutil::*;
+    use super::super::types::{IntegerKind, IntegerType, Type};
+    use super::*;
+
+    fn constant(num: u64) -> Expression {
+        Expression {
+            expression: Exp2::Single(Exp11::Single(Exp12::Single(Exp13::Single(Exp15::Single(
+                Exp16::Number(num, IntegerType::int()),
+            ))))),
+            span: Span::default(),
+        }
+    }
+
+    #[test]
+    fn bit_fields() {
+        // struct flags { unsigned ready : 1, mode : 3; int : 0; long count; };
+        let unsigned = || mtoken(Token::Keyword(Keyword::Unsigned));
+        let mut tokens = Tokens::new(vec![
+            mtoken(Token::Keyword(Keyword::Struct)),
+            mtoken(Token::Identifier(Symbol::intern("flags"))),
+            mtoken(Token::Punct(Punct::LeftBrace)),
+            unsigned(),
+            mtoken(Token::Identifier(Symbol::intern("ready"))),
+            mtoken(Token::Punct(Punct::Colon)),
+            mtoken(number_token(1)),
+            mtoken(Token::Punct(Punct::Comma)),
+            mtoken(Token::Identifier(Symbol::intern("mode"))),
+            mtoken(Token::Punct(Punct::Colon)),
+            mtoken(number_token(3)),
+            mtoken(Token::Punct(Punct::Semicolon)),
+            mtoken(Token::Keyword(Keyword::Int)),
+            mtoken(Token::Punct(Punct::Colon)),
+            mtoken(number_token(0)),
+            mtoken(Token::Punct(Punct::Semicolon)),
+            mtoken(Token::Keyword(Keyword::Long)),
+            mtoken(Token::Identifier(Symbol::intern("count"))),
+            mtoken(Token::Punct(Punct::Semicolon)),
+            mtoken(Token::Punct(Punct::RightBrace)),
+            mtoken(Token::Punct(Punct::Semicolon)),
+        ]);
+        assert!(StructDeclaration::is_next(&tokens));
+        let actual = StructDeclaration::parse(&mut tokens, &TypedefTable::new()).unwrap();
+        let unsigned = Type::Integer(IntegerType::new(IntegerKind::Int, false));
+        let long = Type::Integer(IntegerType::new(IntegerKind::Long, true));
+        let member = |ty: &Type, identifier: &str, width: Option<u64>| Member {
+            type_struct: TypeStruct::new(ty.clone(), 0, Symbol::intern(identifier), vec![]),
+            width: width.map(constant),
+            alignments: vec![],
+        };
+        let expect = StructDeclaration {
+            tag: Symbol::intern("flags"),
+            members: vec![
+                member(&unsigned, "ready", Some(1)),
+                member(&unsigned, "mode", Some(3)),
+                member(&Type::int(), "", Some(0)),
+                member(&long, "count", None),
+            ],
+            span: Span::default(),
+        };
+        assert_eq!(actual, expect);
+        assert_eq!(tokens.len(), 0);
+    }
+
+    #[test]
+    fn struct_typed_variable_is_not_a_declaration() {
+        // struct flags f;
+        let tokens = Tokens::new(vec![
+            mtoken(Token::Keyword(Keyword::Struct)),
+            mtoken(Token::Identifier(Symbol::intern("flags"))),
+            mtoken(Token::Identifier(Symbol::intern("f"))),
+            mtoken(Token::Punct(Punct::Semicolon)),
+        ]);
+        assert!(!StructDeclaration::is_next(&tokens));
+    }
+}
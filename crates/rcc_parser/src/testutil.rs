@@ -1,5 +1,9 @@
-use rcc_syntax::token::{ManagedToken, Token};
+use rcc_syntax::token::{IntegerConstant, ManagedToken, Token};
 
 pub fn mtoken(token: Token) -> ManagedToken {
     ManagedToken::new(token, 0, 0)
 }
+
+pub fn number_token(value: u64) -> Token {
+    Token::Number(IntegerConstant::decimal(value))
+}
@@ -0,0 +1,117 @@
+use super::error::ParseError;
+use super::statement::TypeStruct;
+use super::types::Type;
+use super::util::get_space;
+use rcc_syntax::span::Span;
+use rcc_syntax::symbol::Symbol;
+use rcc_syntax::token::{Keyword, Punct};
+use rcc_syntax::tokens::Tokens;
+use std::collections::HashMap;
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+/// scoped table of typedef names (the first scope is the file scope)
+///
+/// ordinary identifiers are recorded as `None` since they hide typedef names of outer scopes
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypedefTable {
+    scopes: Vec<HashMap<Symbol, Option<TypeStruct>>>,
+}
+impl TypedefTable {
+    pub fn new() -> TypedefTable {
+        let mut typedefs = TypedefTable {
+            scopes: vec![HashMap::new()],
+        };
+        // va_list is declared as if <stdarg.h> were included
+        typedefs.insert_typedef(TypeStruct::new(
+            Type::VaList,
+            0,
+            Symbol::intern("va_list"),
+            vec![],
+        ));
+        typedefs
+    }
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+    pub fn insert_typedef(&mut self, type_struct: TypeStruct) {
+        self.insert(type_struct.get_identifier(), Some(type_struct));
+    }
+    pub fn insert_ordinary(&mut self, identifier: Symbol) {
+        self.insert(identifier, None);
+    }
+    fn insert(&mut self, identifier: Symbol, type_struct: Option<TypeStruct>) {
+        self.scopes
+            .last_mut()
+            .expect("typedef table has no scope")
+            .insert(identifier, type_struct);
+    }
+    /// get the type named by a typedef name visible in the current scope
+    pub fn get(&self, identifier: Symbol) -> Option<&TypeStruct> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&identifier))
+            .and_then(|type_struct| type_struct.as_ref())
+    }
+    pub fn is_typedef_name(&self, identifier: Symbol) -> bool {
+        self.get(identifier).is_some()
+    }
+}
+
+impl Default for TypedefTable {
+    fn default() -> TypedefTable {
+        TypedefTable::new()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypedefDeclaration {
+    pub type_struct: TypeStruct,
+    pub span: Span,
+}
+impl TypedefDeclaration {
+    /// parse typedef_declaration and register the declared typedef name
+    ///
+    /// typedef_declaration := Token::Keyword(Keyword::Typedef) type Token::Punct(Punct::Semicolon)
+    pub fn parse(tokens: &mut Tokens, typedefs: &mut TypedefTable) -> Result<TypedefDeclaration> {
+        let checkpoint = tokens.checkpoint();
+        tokens.consume_keyword(Keyword::Typedef)?;
+        let type_struct = TypeStruct::parse(tokens, typedefs)?;
+        tokens.consume_punct(Punct::Semicolon)?;
+        typedefs.insert_typedef(type_struct.clone());
+        Ok(TypedefDeclaration {
+            type_struct,
+            span: tokens.span_since(checkpoint),
+        })
+    }
+    pub fn to_string(&self, tab_level: u32) -> String {
+        format!(
+            "{}typedef_declaration -> {}",
+            get_space(tab_level),
+            self.type_struct.to_string(tab_level),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn inner_scope_hides_typedef_name() {
+        let mut typedefs = TypedefTable::new();
+        let foo = TypeStruct::new(Type::int(), 0, Symbol::intern("foo"), vec![]);
+        typedefs.insert_typedef(foo.clone());
+        typedefs.push_scope();
+        assert_eq!(typedefs.get(Symbol::intern("foo")), Some(&foo));
+        typedefs.insert_ordinary(Symbol::intern("foo"));
+        assert!(!typedefs.is_typedef_name(Symbol::intern("foo")));
+        typedefs.pop_scope();
+        assert!(typedefs.is_typedef_name(Symbol::intern("foo")));
+    }
+}
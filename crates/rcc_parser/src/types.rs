@@ -0,0 +1,452 @@
+use rcc_syntax::symbol::Symbol;
+use rcc_syntax::token::Keyword;
+use std::fmt;
+
+/// integer kinds in the order of their conversion rank
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IntegerKind {
+    Bool,
+    Char,
+    Short,
+    Int,
+    Long,
+    LongLong,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IntegerType {
+    pub kind: IntegerKind,
+    pub signed: bool,
+}
+impl IntegerType {
+    pub fn new(kind: IntegerKind, signed: bool) -> IntegerType {
+        IntegerType { kind, signed }
+    }
+
+    pub fn int() -> IntegerType {
+        IntegerType::new(IntegerKind::Int, true)
+    }
+
+    /// get integer type from type specifier keywords (e.g. ["unsigned", "long", "int"])
+    pub fn from_specifiers(specifiers: &[Keyword]) -> Option<IntegerType> {
+        let count = |keyword| specifiers.iter().filter(|&&s| s == keyword).count();
+        let (bool_, char_, short, int, long) = (
+            count(Keyword::Bool),
+            count(Keyword::Char),
+            count(Keyword::Short),
+            count(Keyword::Int),
+            count(Keyword::Long),
+        );
+        let (signed, unsigned) = (count(Keyword::Signed), count(Keyword::Unsigned));
+        if bool_ + char_ + short + int + signed + unsigned + long != specifiers.len()
+            || bool_ > 1
+            || char_ > 1
+            || short > 1
+            || int > 1
+            || long > 2
+            || signed + unsigned > 1
+        {
+            return None;
+        }
+        let kind = if bool_ == 1 {
+            if specifiers.len() != 1 {
+                return None;
+            }
+            return Some(IntegerType::new(IntegerKind::Bool, false));
+        } else if char_ == 1 {
+            if short + int + long != 0 {
+                return None;
+            }
+            IntegerKind::Char
+        } else if short == 1 {
+            if long != 0 {
+                return None;
+            }
+            IntegerKind::Short
+        } else if long == 2 {
+            IntegerKind::LongLong
+        } else if long == 1 {
+            IntegerKind::Long
+        } else {
+            IntegerKind::Int
+        };
+        // plain char is signed on x86-64
+        Some(IntegerType::new(kind, unsigned == 0))
+    }
+
+    /// type of an integer constant, which is the first type in the list of its suffix that can
+    /// represent its value (octal and hexadecimal constants can also be unsigned without `u`)
+    ///
+    /// returns None when no type in the list can represent the value
+    pub fn of_constant(
+        value: u64,
+        is_decimal: bool,
+        is_unsigned: bool,
+        long: u8,
+    ) -> Option<IntegerType> {
+        let kinds = [IntegerKind::Int, IntegerKind::Long, IntegerKind::LongLong];
+        let mut candidates = vec![];
+        for kind in kinds.iter().skip(long as usize) {
+            if !is_unsigned {
+                candidates.push(IntegerType::new(*kind, true));
+            }
+            if is_unsigned || !is_decimal {
+                candidates.push(IntegerType::new(*kind, false));
+            }
+        }
+        candidates.into_iter().find(|ty| value <= ty.max_value())
+    }
+
+    /// largest value of the type
+    pub fn max_value(&self) -> u64 {
+        match self.kind {
+            IntegerKind::Bool => 1,
+            _ if self.signed => (1 << (self.bit_width() - 1)) - 1,
+            _ => u64::MAX >> (64 - self.bit_width()),
+        }
+    }
+
+    /// size in bytes on x86-64
+    pub fn size(&self) -> u32 {
+        match self.kind {
+            IntegerKind::Bool | IntegerKind::Char => 1,
+            IntegerKind::Short => 2,
+            IntegerKind::Int => 4,
+            IntegerKind::Long | IntegerKind::LongLong => 8,
+        }
+    }
+
+    pub fn bit_width(&self) -> u32 {
+        self.size() * 8
+    }
+
+    /// integer promotion
+    pub fn promote(self) -> IntegerType {
+        if self.kind < IntegerKind::Int {
+            // every value of smaller types can be represented by int
+            IntegerType::int()
+        } else {
+            self
+        }
+    }
+
+    /// usual arithmetic conversion of two integer operands
+    pub fn common(lhs: IntegerType, rhs: IntegerType) -> IntegerType {
+        let (lhs, rhs) = (lhs.promote(), rhs.promote());
+        if lhs == rhs {
+            return lhs;
+        }
+        if lhs.signed == rhs.signed {
+            return if lhs.kind >= rhs.kind { lhs } else { rhs };
+        }
+        let (signed, unsigned) = if lhs.signed { (lhs, rhs) } else { (rhs, lhs) };
+        if unsigned.kind >= signed.kind {
+            unsigned
+        } else if signed.size() > unsigned.size() {
+            signed
+        } else {
+            IntegerType::new(signed.kind, false)
+        }
+    }
+}
+impl fmt::Display for IntegerType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self.kind {
+            IntegerKind::Bool => return write!(f, "_Bool"),
+            IntegerKind::Char => "char",
+            IntegerKind::Short => "short",
+            IntegerKind::Int => "int",
+            IntegerKind::Long => "long",
+            IntegerKind::LongLong => "long long",
+        };
+        if self.signed {
+            write!(f, "{}", name)
+        } else {
+            write!(f, "unsigned {}", name)
+        }
+    }
+}
+
+/// real floating types in the order of their conversion rank
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FloatingType {
+    Float,
+    Double,
+}
+impl FloatingType {
+    /// size in bytes (IEEE 754 binary32 and binary64)
+    pub fn size(&self) -> u32 {
+        match self {
+            FloatingType::Float => 4,
+            FloatingType::Double => 8,
+        }
+    }
+}
+impl fmt::Display for FloatingType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FloatingType::Float => write!(f, "float"),
+            FloatingType::Double => write!(f, "double"),
+        }
+    }
+}
+
+/// type qualifiers of an object or a pointer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Qualifiers {
+    pub is_const: bool,
+    pub is_volatile: bool,
+}
+impl Qualifiers {
+    /// qualifiers can be repeated (e.g. `const const int`)
+    pub fn merge(self, other: Qualifiers) -> Qualifiers {
+        Qualifiers {
+            is_const: self.is_const || other.is_const,
+            is_volatile: self.is_volatile || other.is_volatile,
+        }
+    }
+}
+impl fmt::Display for Qualifiers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_const {
+            write!(f, " const")?;
+        }
+        if self.is_volatile {
+            write!(f, " volatile")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Type {
+    Integer(IntegerType),
+    Floating(FloatingType),
+    Pointer(Box<Type>, Qualifiers), // (pointed-to type, its qualifiers)
+    Array(Box<Type>, u64),
+    Function(Box<Type>, Vec<Type>, bool), // (return type, parameter types, is variadic)
+    VaList,
+    Struct(Symbol), // tag
+}
+impl Type {
+    pub fn int() -> Type {
+        Type::Integer(IntegerType::int())
+    }
+
+    /// get type from type specifier keywords (e.g. ["double"], ["unsigned", "int"])
+    pub fn from_specifiers(specifiers: &[Keyword]) -> Option<Type> {
+        match specifiers {
+            [Keyword::Float] => Some(Type::Floating(FloatingType::Float)),
+            [Keyword::Double] => Some(Type::Floating(FloatingType::Double)),
+            _ => IntegerType::from_specifiers(specifiers).map(Type::Integer),
+        }
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self, Type::Array(..))
+    }
+
+    /// arrays and structures, which are initialized by lists of their elements or members
+    pub fn is_aggregate(&self) -> bool {
+        matches!(self, Type::Array(..) | Type::Struct(_))
+    }
+
+    pub fn is_arithmetic(&self) -> bool {
+        matches!(self, Type::Integer(_) | Type::Floating(_))
+    }
+
+    pub fn is_scalar(&self) -> bool {
+        self.is_arithmetic() || matches!(self, Type::Pointer(..))
+    }
+
+    pub fn is_function(&self) -> bool {
+        matches!(self, Type::Function(..))
+    }
+
+    /// adjust the type of a parameter (array and function parameters are pointers)
+    ///
+    /// `qualifiers` are the ones of the elements of an array
+    pub fn adjust_parameter(self, qualifiers: Qualifiers) -> Type {
+        match self {
+            Type::Array(ty, _) => Type::Pointer(ty, qualifiers),
+            ty @ Type::Function(..) => Type::Pointer(Box::new(ty), Qualifiers::default()),
+            ty => ty,
+        }
+    }
+
+    /// usual arithmetic conversion of two arithmetic operands
+    pub fn common(lhs: &Type, rhs: &Type) -> Option<Type> {
+        match (lhs, rhs) {
+            (Type::Integer(lhs), Type::Integer(rhs)) => {
+                Some(Type::Integer(IntegerType::common(*lhs, *rhs)))
+            }
+            (Type::Floating(lhs), Type::Floating(rhs)) => Some(Type::Floating(*lhs.max(rhs))),
+            (Type::Floating(floating), Type::Integer(_))
+            | (Type::Integer(_), Type::Floating(floating)) => Some(Type::Floating(*floating)),
+            _ => None,
+        }
+    }
+}
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::Integer(integer_type) => write!(f, "{}", integer_type),
+            Type::Floating(floating_type) => write!(f, "{}", floating_type),
+            Type::Pointer(ty, qualifiers) => write!(f, "{}{}*", ty, qualifiers),
+            Type::Array(ty, size) => write!(f, "{}[{}]", ty, size),
+            Type::Function(return_type, parameter_types, is_variadic) => {
+                let mut parameter_types: Vec<String> =
+                    parameter_types.iter().map(|ty| ty.to_string()).collect();
+                if *is_variadic {
+                    parameter_types.push("...".to_owned());
+                }
+                write!(f, "{}({})", return_type, parameter_types.join(", "))
+            }
+            Type::VaList => write!(f, "va_list"),
+            Type::Struct(tag) => write!(f, "struct {}", tag),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn specifiers(s: &str) -> Vec<Keyword> {
+        s.split(' ')
+            .map(|s| Keyword::from_spelling(s).unwrap())
+            .collect()
+    }
+
+    fn integer(kind: IntegerKind, signed: bool) -> IntegerType {
+        IntegerType::new(kind, signed)
+    }
+
+    #[test]
+    fn from_specifiers() {
+        use IntegerKind::*;
+        let cases = vec![
+            ("int", Some(integer(Int, true))),
+            ("unsigned", Some(integer(Int, false))),
+            ("short int", Some(integer(Short, true))),
+            ("unsigned char", Some(integer(Char, false))),
+            ("long unsigned int long", Some(integer(LongLong, false))),
+            ("signed long", Some(integer(Long, true))),
+            ("_Bool", Some(integer(Bool, false))),
+            ("short long", None),
+            ("signed unsigned", None),
+            ("long long long", None),
+            ("_Bool int", None),
+        ];
+        for (s, expect) in cases {
+            assert_eq!(
+                IntegerType::from_specifiers(&specifiers(s)),
+                expect,
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn promote() {
+        use IntegerKind::*;
+        assert_eq!(integer(Char, false).promote(), IntegerType::int());
+        assert_eq!(integer(Bool, false).promote(), IntegerType::int());
+        assert_eq!(integer(Int, false).promote(), integer(Int, false));
+        assert_eq!(integer(Long, true).promote(), integer(Long, true));
+    }
+
+    #[test]
+    fn usual_arithmetic_conversion() {
+        use IntegerKind::*;
+        let cases = vec![
+            (
+                integer(Short, true),
+                integer(Char, false),
+                integer(Int, true),
+            ),
+            (integer(Int, true), integer(Int, false), integer(Int, false)),
+            (
+                integer(Long, true),
+                integer(Int, false),
+                integer(Long, true),
+            ),
+            (integer(Int, true), integer(Long, true), integer(Long, true)),
+            (
+                integer(LongLong, true),
+                integer(Long, false),
+                integer(LongLong, false),
+            ),
+            (
+                integer(Long, false),
+                integer(Int, true),
+                integer(Long, false),
+            ),
+        ];
+        for (lhs, rhs, expect) in cases {
+            assert_eq!(IntegerType::common(lhs, rhs), expect);
+            assert_eq!(IntegerType::common(rhs, lhs), expect);
+        }
+    }
+
+    #[test]
+    fn integer_constant_types() {
+        use IntegerKind::*;
+        let cases = vec![
+            (1, true, false, 0, Some(integer(Int, true))),
+            (0x8000_0000, true, false, 0, Some(integer(Long, true))),
+            (0x8000_0000, false, false, 0, Some(integer(Int, false))),
+            (1, true, true, 0, Some(integer(Int, false))),
+            (1, true, false, 1, Some(integer(Long, true))),
+            (1, false, true, 2, Some(integer(LongLong, false))),
+            (u64::MAX, false, false, 0, Some(integer(Long, false))),
+            (u64::MAX, true, true, 1, Some(integer(Long, false))),
+            (u64::MAX, true, false, 0, None),
+            (u64::MAX, true, false, 2, None),
+        ];
+        for (value, is_decimal, is_unsigned, long, expect) in cases {
+            assert_eq!(
+                IntegerType::of_constant(value, is_decimal, is_unsigned, long),
+                expect,
+                "{:x} {} {} {}",
+                value,
+                is_decimal,
+                is_unsigned,
+                long
+            );
+        }
+    }
+
+    #[test]
+    fn floating_specifiers() {
+        assert_eq!(
+            Type::from_specifiers(&specifiers("double")),
+            Some(Type::Floating(FloatingType::Double))
+        );
+        assert_eq!(
+            Type::from_specifiers(&specifiers("unsigned long")),
+            Some(Type::Integer(integer(IntegerKind::Long, false)))
+        );
+        assert_eq!(Type::from_specifiers(&specifiers("long double")), None);
+        assert_eq!(Type::from_specifiers(&specifiers("unsigned float")), None);
+    }
+
+    #[test]
+    fn floating_arithmetic_conversion() {
+        let float = Type::Floating(FloatingType::Float);
+        let double = Type::Floating(FloatingType::Double);
+        let long = Type::Integer(integer(IntegerKind::LongLong, false));
+        assert_eq!(Type::common(&float, &long), Some(float.clone()));
+        assert_eq!(Type::common(&double, &float), Some(double.clone()));
+        assert_eq!(Type::common(&long, &double), Some(double));
+        assert_eq!(
+            Type::common(
+                &float,
+                &Type::Pointer(Box::new(Type::int()), Qualifiers::default())
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use super::error::PreprocessErrorKind;
+use super::macros::{name_of, Macros};
+use rcc_syntax::symbol::Symbol;
+use rcc_syntax::token::{character_value, IntegerConstant, ManagedToken, Punct, Token};
+use std::cmp::Ordering;
+
+type Result<T> = std::result::Result<T, PreprocessErrorKind>;
+
+/// binary operators from the lowest precedence
+const BINARY_OPERATORS: [&[Punct]; 10] = [
+    &[Punct::OrOr],
+    &[Punct::AndAnd],
+    &[Punct::Pipe],
+    &[Punct::Caret],
+    &[Punct::Ampersand],
+    &[Punct::Equal, Punct::NotEqual],
+    &[
+        Punct::Less,
+        Punct::Greater,
+        Punct::LessEqual,
+        Punct::GreaterEqual,
+    ],
+    &[Punct::LeftShift, Punct::RightShift],
+    &[Punct::Plus, Punct::Minus],
+    &[Punct::Star, Punct::Slash, Punct::Percent],
+];
+
+/// replace `defined X` and `defined(X)` with 1 or 0, which is done before macros are expanded
+pub fn replace_defined(tokens: Vec<ManagedToken>, macros: &Macros) -> Result<Vec<ManagedToken>> {
+    let invalid =
+        || PreprocessErrorKind::InvalidCondition("defined without a macro name".to_owned());
+    let mut replaced = vec![];
+    let mut tokens = tokens.into_iter();
+    while let Some(token) = tokens.next() {
+        if token.get_token() != &Token::Identifier(Symbol::intern("defined")) {
+            replaced.push(token);
+            continue;
+        }
+        let mut name = tokens.next().ok_or_else(invalid)?;
+        let is_parenthesized = name.get_token().is_punct(Punct::LeftParen);
+        if is_parenthesized {
+            name = tokens.next().ok_or_else(invalid)?;
+        }
+        let is_defined = match name_of(name.get_token()) {
+            Some(name) => macros.is_defined(name),
+            None => return Err(invalid()),
+        };
+        if is_parenthesized
+            && !matches!(tokens.next(), Some(close) if close.get_token().is_punct(Punct::RightParen))
+        {
+            return Err(invalid());
+        }
+        let value = Token::Number(IntegerConstant::decimal(is_defined as u64));
+        replaced.push(
+            ManagedToken::new(value, token.get_line(), token.get_location())
+                .with_span(token.get_span()),
+        );
+    }
+    Ok(replaced)
+}
+
+/// evaluate the controlling expression of `#if` or `#elif` after macros are expanded
+///
+/// condition := exp1 (Token::Punct(Punct::Question) condition Token::Punct(Punct::Colon) condition)?
+/// exp1 ... exp10 := binary operators in the order of BINARY_OPERATORS
+/// unary := (Token::Punct(Punct::Plus) | Token::Punct(Punct::Minus) | Token::Punct(Punct::Exclamation) | Token::Punct(Punct::Tilde)) unary
+///     | Token::Punct(Punct::LeftParen) condition Token::Punct(Punct::RightParen)
+///     | Token::Number
+///     | Token::Character
+///     | Token::Identifier
+///
+/// values are intmax_t (i64) or uintmax_t (u64) and identifiers which are not macros are 0.
+/// operands which are not evaluated (`0 && x / 0`) do not cause errors.
+pub fn evaluate(tokens: &[ManagedToken]) -> Result<bool> {
+    let mut evaluator = Evaluator {
+        tokens,
+        position: 0,
+    };
+    let value = evaluator.condition(true)?;
+    match evaluator.next() {
+        Some(token) => Err(unexpected(token)),
+        None => Ok(value.is_true()),
+    }
+}
+
+/// value of intmax_t or uintmax_t
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Value {
+    bits: i64,
+    is_unsigned: bool,
+}
+impl Value {
+    fn signed(bits: i64) -> Value {
+        Value {
+            bits,
+            is_unsigned: false,
+        }
+    }
+
+    fn is_true(self) -> bool {
+        self.bits != 0
+    }
+}
+
+struct Evaluator<'a> {
+    tokens: &'a [ManagedToken],
+    position: usize,
+}
+impl<'a> Evaluator<'a> {
+    fn condition(&mut self, is_evaluated: bool) -> Result<Value> {
+        let condition = self.binary(0, is_evaluated)?;
+        if self.consume_operator(&[Punct::Question]).is_none() {
+            return Ok(condition);
+        }
+        let then = self.condition(is_evaluated && condition.is_true())?;
+        match self.next() {
+            Some(token) if token.get_token() == &Token::Punct(Punct::Colon) => (),
+            token => return Err(unexpected_or_missing(token)),
+        }
+        let otherwise = self.condition(is_evaluated && !condition.is_true())?;
+        let bits = if condition.is_true() {
+            then.bits
+        } else {
+            otherwise.bits
+        };
+        Ok(Value {
+            bits,
+            is_unsigned: then.is_unsigned || otherwise.is_unsigned,
+        })
+    }
+
+    fn binary(&mut self, level: usize, is_evaluated: bool) -> Result<Value> {
+        if level == BINARY_OPERATORS.len() {
+            return self.unary(is_evaluated);
+        }
+        let mut lhs = self.binary(level + 1, is_evaluated)?;
+        while let Some(operator) = self.consume_operator(BINARY_OPERATORS[level]) {
+            // the right operands of && and || are evaluated only when they decide the value
+            let is_rhs_evaluated = is_evaluated
+                && match operator {
+                    Punct::AndAnd => lhs.is_true(),
+                    Punct::OrOr => !lhs.is_true(),
+                    _ => true,
+                };
+            let rhs = self.binary(level + 1, is_rhs_evaluated)?;
+            // the usual arithmetic conversions make both operands unsigned if either is
+            let is_unsigned = lhs.is_unsigned || rhs.is_unsigned;
+            let (l, r) = (lhs.bits, rhs.bits);
+            let arithmetic = |bits| Value { bits, is_unsigned };
+            let ordering = if is_unsigned {
+                (l as u64).cmp(&(r as u64))
+            } else {
+                l.cmp(&r)
+            };
+            lhs = match operator {
+                // logical and relational operators give int
+                Punct::OrOr => Value::signed((lhs.is_true() || rhs.is_true()) as i64),
+                Punct::AndAnd => Value::signed((lhs.is_true() && rhs.is_true()) as i64),
+                Punct::Equal => Value::signed((ordering == Ordering::Equal) as i64),
+                Punct::NotEqual => Value::signed((ordering != Ordering::Equal) as i64),
+                Punct::Less => Value::signed((ordering == Ordering::Less) as i64),
+                Punct::Greater => Value::signed((ordering == Ordering::Greater) as i64),
+                Punct::LessEqual => Value::signed((ordering != Ordering::Greater) as i64),
+                Punct::GreaterEqual => Value::signed((ordering != Ordering::Less) as i64),
+                // the result of a shift has the type of the left operand
+                Punct::LeftShift => Value {
+                    bits: l.wrapping_shl(r as u32),
+                    is_unsigned: lhs.is_unsigned,
+                },
+                Punct::RightShift if lhs.is_unsigned => Value {
+                    bits: (l as u64).wrapping_shr(r as u32) as i64,
+                    is_unsigned: true,
+                },
+                Punct::RightShift => Value::signed(l.wrapping_shr(r as u32)),
+                Punct::Pipe => arithmetic(l | r),
+                Punct::Caret => arithmetic(l ^ r),
+                Punct::Ampersand => arithmetic(l & r),
+                Punct::Plus => arithmetic(l.wrapping_add(r)),
+                Punct::Minus => arithmetic(l.wrapping_sub(r)),
+                Punct::Star => arithmetic(l.wrapping_mul(r)),
+                Punct::Slash | Punct::Percent if r == 0 => {
+                    if is_rhs_evaluated {
+                        let cause = "division by zero".to_owned();
+                        return Err(PreprocessErrorKind::InvalidCondition(cause));
+                    }
+                    arithmetic(0)
+                }
+                Punct::Slash if is_unsigned => arithmetic(((l as u64) / (r as u64)) as i64),
+                Punct::Percent if is_unsigned => arithmetic(((l as u64) % (r as u64)) as i64),
+                Punct::Slash => arithmetic(l.wrapping_div(r)),
+                Punct::Percent => arithmetic(l.wrapping_rem(r)),
+                _ => unreachable!(),
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self, is_evaluated: bool) -> Result<Value> {
+        let operators = [Punct::Plus, Punct::Minus, Punct::Exclamation, Punct::Tilde];
+        if let Some(operator) = self.consume_operator(&operators) {
+            let operand = self.unary(is_evaluated)?;
+            let bits = match operator {
+                Punct::Plus => operand.bits,
+                Punct::Minus => operand.bits.wrapping_neg(),
+                Punct::Exclamation => return Ok(Value::signed(!operand.is_true() as i64)),
+                _ => !operand.bits,
+            };
+            return Ok(Value {
+                bits,
+                is_unsigned: operand.is_unsigned,
+            });
+        }
+        let token = self.next();
+        match token.map(|token| token.get_token()) {
+            Some(Token::Punct(Punct::LeftParen)) => {
+                let value = self.condition(is_evaluated)?;
+                match self.next() {
+                    Some(close) if close.get_token().is_punct(Punct::RightParen) => Ok(value),
+                    token => Err(unexpected_or_missing(token)),
+                }
+            }
+            // a constant which is too large for intmax_t is uintmax_t
+            Some(Token::Number(number)) => match number.value() {
+                Ok(value) => Ok(Value {
+                    bits: value as i64,
+                    is_unsigned: number.is_unsigned || value > i64::MAX as u64,
+                }),
+                Err(_) => Err(PreprocessErrorKind::InvalidCondition(format!(
+                    "integer constant {} is too large",
+                    number
+                ))),
+            },
+            Some(Token::Character(c)) => Ok(Value::signed(character_value(*c))),
+            // keywords are identifiers in conditions
+            Some(Token::Identifier(_)) | Some(Token::Keyword(_)) => Ok(Value::signed(0)),
+            _ => Err(unexpected_or_missing(token)),
+        }
+    }
+
+    fn consume_operator(&mut self, operators: &[Punct]) -> Option<Punct> {
+        match self
+            .tokens
+            .get(self.position)
+            .map(|token| token.get_token())
+        {
+            Some(Token::Punct(operator)) if operators.contains(operator) => {
+                self.position += 1;
+                Some(*operator)
+            }
+            _ => None,
+        }
+    }
+
+    fn next(&mut self) -> Option<&'a ManagedToken> {
+        let token = self.tokens.get(self.position)?;
+        self.position += 1;
+        Some(token)
+    }
+}
+
+fn unexpected(token: &ManagedToken) -> PreprocessErrorKind {
+    let cause = format!("unexpected {}", token.get_token().spelling());
+    PreprocessErrorKind::InvalidCondition(cause)
+}
+
+fn unexpected_or_missing(token: Option<&ManagedToken>) -> PreprocessErrorKind {
+    match token {
+        Some(token) => unexpected(token),
+        None => PreprocessErrorKind::InvalidCondition("missing expression".to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use rcc_syntax::Tokenizer;
+
+    fn evaluate_code(code: &str) -> Result<bool> {
+        evaluate(&Tokenizer::tokenize(&code.to_owned()).unwrap())
+    }
+
+    #[test]
+    fn operators() {
+        let cases = [
+            ("1 + 2 * 3 == 7", true),
+            ("(1 + 2) * 3 == 7", false),
+            ("-1 < 0 && ~0 == -1 && !0", true),
+            ("1 << 3 | 1 ^ 3 & 2", true),
+            ("10 % 4 - 2", false),
+            ("0 || 3 >= 4 || 2 != 2", false),
+            ("1 ? 2 ? 3 : 0 : 0", true),
+            ("0 ? 1 : 1 ? 0 : 1", false),
+            ("0x7fffffffffffffff + 1 < 0", true),
+            ("undefined_name + int", false),
+            ("'a' == 97 && '\\n' == 10 && '\\'' == 39", true),
+            ("'\\0' == 0 && '\\012' == 10 && '\\x41' == 'A'", true),
+            ("'\\xff' == -1 && '\\377' < 0", true),
+        ];
+        for (code, expect) in cases.iter() {
+            assert_eq!(evaluate_code(code), Ok(*expect), "{}", code);
+        }
+    }
+
+    #[test]
+    fn unsigned_arithmetic() {
+        let cases = [
+            ("-1 < 0u", false),
+            ("-1 < 0", true),
+            ("0xffffffffffffffff == -1 && 0xffffffffffffffff > 0", true),
+            ("-2 / 2u == 0x7fffffffffffffff", true),
+            ("-1u >> 63 == 1 && -1 >> 63 == -1", true),
+            ("(1 ? -1 : 0u) > 0", true),
+            ("(0u < 1) - 2 < 0", true),
+            ("!0u - 2 < 0", true),
+        ];
+        for (code, expect) in cases.iter() {
+            assert_eq!(evaluate_code(code), Ok(*expect), "{}", code);
+        }
+    }
+
+    #[test]
+    fn errors_in_evaluated_operands() {
+        let invalid = |cause: &str| Err(PreprocessErrorKind::InvalidCondition(cause.to_owned()));
+        assert_eq!(evaluate_code("0 && 1 / 0 || 1 ? 1 : 1 % 0"), Ok(true));
+        assert_eq!(evaluate_code("1 || 1 / 0"), Ok(true));
+        assert_eq!(evaluate_code("1 / 0"), invalid("division by zero"));
+        assert_eq!(evaluate_code(""), invalid("missing expression"));
+        assert_eq!(evaluate_code("(1"), invalid("missing expression"));
+        assert_eq!(evaluate_code("1 2"), invalid("unexpected 2"));
+        assert_eq!(evaluate_code("1.5"), invalid("unexpected 1.5"));
+    }
+}
@@ -0,0 +1,165 @@
+use rcc_syntax::error::LexError;
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PreprocessErrorKind {
+    Read(PathBuf, String),
+    Lex(Vec<LexError>),
+    NotFound(String),
+    InvalidInclude(String),
+    IncludeCycle(PathBuf),
+    IncludeDepth(PathBuf),
+    UnknownDirective(String),
+    InvalidMacro(String),
+    Redefinition(String),
+    MacroArguments(String, usize),
+    UnterminatedInvocation(String),
+    InvalidPaste(String, String),
+    InvalidCondition(String),
+    UnbalancedConditional(String),
+    UnterminatedConditional,
+    InvalidLine(String),
+    ErrorDirective(String),
+}
+
+impl fmt::Display for PreprocessErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PreprocessErrorKind::Read(path, cause) => {
+                write!(f, "cannot read {}: {}", path.display(), cause)
+            }
+            PreprocessErrorKind::Lex(errors) => {
+                let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+                write!(f, "{}", errors.join(", "))
+            }
+            PreprocessErrorKind::NotFound(name) => {
+                write!(f, "include file {:?} not found", name)
+            }
+            PreprocessErrorKind::InvalidInclude(header) => {
+                write!(f, "invalid include {:?}", header)
+            }
+            PreprocessErrorKind::IncludeCycle(path) => {
+                write!(f, "{} includes itself", path.display())
+            }
+            PreprocessErrorKind::IncludeDepth(path) => {
+                write!(f, "includes are nested too deeply at {}", path.display())
+            }
+            PreprocessErrorKind::UnknownDirective(name) => {
+                write!(f, "unknown directive #{}", name)
+            }
+            PreprocessErrorKind::InvalidMacro(cause) => write!(f, "invalid macro: {}", cause),
+            PreprocessErrorKind::Redefinition(name) => {
+                write!(f, "macro {} is redefined differently", name)
+            }
+            PreprocessErrorKind::MacroArguments(name, count) => {
+                write!(
+                    f,
+                    "macro {} is invoked with wrong {} arguments",
+                    name, count
+                )
+            }
+            PreprocessErrorKind::UnterminatedInvocation(name) => {
+                write!(f, "unterminated invocation of macro {}", name)
+            }
+            PreprocessErrorKind::InvalidPaste(lhs, rhs) => {
+                write!(f, "pasting {} and {} does not give a token", lhs, rhs)
+            }
+            PreprocessErrorKind::InvalidCondition(cause) => {
+                write!(f, "invalid condition: {}", cause)
+            }
+            PreprocessErrorKind::UnbalancedConditional(cause) => write!(f, "{}", cause),
+            PreprocessErrorKind::UnterminatedConditional => write!(f, "unterminated #if"),
+            PreprocessErrorKind::InvalidLine(cause) => write!(f, "invalid #line: {}", cause),
+            PreprocessErrorKind::ErrorDirective(message) => write!(f, "#error {}", message),
+        }
+    }
+}
+
+/// error with the include stack where it occurs
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreprocessError {
+    pub kind: PreprocessErrorKind,
+    pub stack: Vec<(PathBuf, u32)>, // (file, line of the directive from 1) from the innermost file
+}
+
+impl fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "preprocess error: {}", self.kind)?;
+        write_stack(f, &self.stack)
+    }
+}
+
+/// write where an error or a warning occurs with the files which include it
+fn write_stack(f: &mut fmt::Formatter, stack: &[(PathBuf, u32)]) -> fmt::Result {
+    for (i, (path, line)) in stack.iter().enumerate() {
+        if i == 0 {
+            write!(f, " at line {} of {}", line, path.display())?;
+        } else {
+            write!(f, "\n    included at line {} of {}", line, path.display())?;
+        }
+    }
+    Ok(())
+}
+
+impl Error for PreprocessError {
+    fn description(&self) -> &str {
+        match self.kind {
+            PreprocessErrorKind::Read(..) => "preprocess error: reading a file",
+            PreprocessErrorKind::Lex(_) => "preprocess error: lexical error",
+            PreprocessErrorKind::NotFound(_) => "preprocess error: include file not found",
+            PreprocessErrorKind::InvalidInclude(_) => "preprocess error: invalid include",
+            PreprocessErrorKind::IncludeCycle(_) => "preprocess error: include cycle",
+            PreprocessErrorKind::IncludeDepth(_) => "preprocess error: include depth",
+            PreprocessErrorKind::UnknownDirective(_) => "preprocess error: unknown directive",
+            PreprocessErrorKind::InvalidMacro(_) => "preprocess error: invalid macro",
+            PreprocessErrorKind::Redefinition(_) => "preprocess error: macro redefinition",
+            PreprocessErrorKind::MacroArguments(..) => "preprocess error: wrong macro arguments",
+            PreprocessErrorKind::UnterminatedInvocation(_) => {
+                "preprocess error: unterminated macro invocation"
+            }
+            PreprocessErrorKind::InvalidPaste(..) => "preprocess error: invalid token paste",
+            PreprocessErrorKind::InvalidCondition(_) => "preprocess error: invalid condition",
+            PreprocessErrorKind::UnbalancedConditional(_) => {
+                "preprocess error: unbalanced conditional"
+            }
+            PreprocessErrorKind::UnterminatedConditional => {
+                "preprocess error: unterminated conditional"
+            }
+            PreprocessErrorKind::InvalidLine(_) => "preprocess error: invalid #line",
+            PreprocessErrorKind::ErrorDirective(_) => "preprocess error: #error",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PreprocessWarningKind {
+    WarningDirective(String),
+    UnknownPragma(String),
+}
+
+impl fmt::Display for PreprocessWarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PreprocessWarningKind::WarningDirective(message) => write!(f, "#warning {}", message),
+            PreprocessWarningKind::UnknownPragma(pragma) => {
+                write!(f, "unknown pragma {:?} is ignored", pragma)
+            }
+        }
+    }
+}
+
+/// warning which does not stop preprocessing, with the include stack where it occurs
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreprocessWarning {
+    pub kind: PreprocessWarningKind,
+    pub stack: Vec<(PathBuf, u32)>,
+}
+
+impl fmt::Display for PreprocessWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "preprocess warning: {}", self.kind)?;
+        write_stack(f, &self.stack)
+    }
+}
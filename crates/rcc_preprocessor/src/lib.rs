@@ -0,0 +1,1061 @@
+mod condition;
+pub mod error;
+mod line;
+pub mod macros;
+pub mod output;
+mod predefined;
+
+use self::error::{PreprocessError, PreprocessErrorKind, PreprocessWarning, PreprocessWarningKind};
+use self::line::{split_lines, SourceLine};
+use self::macros::{name_of, Macro, Macros};
+use self::output::{LineMarker, MarkerKind};
+use rcc_syntax::error::LexError;
+use rcc_syntax::span::{FileId, SourceMap};
+use rcc_syntax::token::{ManagedToken, Token};
+use rcc_syntax::Tokenizer;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+type Result<T> = std::result::Result<T, PreprocessError>;
+
+/// limit of nested includes, which ends includes repeated with changing macros
+const MAX_INCLUDE_DEPTH: usize = 200;
+
+const CONDITIONAL_DIRECTIVES: [&str; 6] = ["if", "ifdef", "ifndef", "elif", "else", "endif"];
+
+/// file in the include stack
+#[derive(Debug, Clone)]
+struct IncludeFrame {
+    path: PathBuf,             // as it is found
+    file: FileId,              // file in the source map
+    identity: Option<PathBuf>, // canonical path (None for code which is not read from a file)
+    name: PathBuf,             // presumed name, which is changed by `#line`
+    line: u32,                 // presumed line of the directive being processed
+    line_delta: i64,           // presumed line - physical line, which is changed by `#line`
+    generation: u64,           // generation of the macros when the file is entered
+    conditionals: Vec<Conditional>,
+}
+
+/// lines from `#if`, `#ifdef` or `#ifndef` to `#endif` in a file, which are divided into groups by
+/// `#elif` and `#else`
+#[derive(Debug, Clone)]
+struct Conditional {
+    line: u32,       // line of the `#if`
+    is_active: bool, // the lines of the current group are processed
+    is_taken: bool,  // a group has been processed, or the conditional itself is skipped
+    has_else: bool,
+}
+
+/// preprocessor which gives the tokens of a translation unit (translation phase 4)
+///
+/// `#include "..."` searches the directory of the including file and then the include paths, and
+/// `#include <...>` searches the include paths only. tokens keep their lines in the files which
+/// they come from (as presumed by `#line`), and tokens replaced by macros are at the invocations
+/// with their expansions. the spans of tokens are in the files of the source map, which keeps
+/// every file preprocessed.
+///
+/// a file can include itself only after macros change, so that include guards end the recursion.
+/// a file with `#pragma once` is not included again.
+pub struct Preprocessor {
+    include_paths: Vec<PathBuf>,
+    stack: Vec<IncludeFrame>,
+    macros: Macros,
+    once: HashSet<PathBuf>, // identities of the files with `#pragma once`
+    warnings: Vec<PreprocessWarning>,
+    warns_unknown_pragmas: bool,
+    markers: Vec<LineMarker>,
+    emitted: usize, // number of the tokens given so far
+    source_map: SourceMap,
+}
+impl Preprocessor {
+    pub fn new(include_paths: Vec<PathBuf>) -> Preprocessor {
+        let mut preprocessor = Preprocessor {
+            include_paths,
+            stack: vec![],
+            macros: Macros::default(),
+            once: HashSet::new(),
+            warnings: vec![],
+            warns_unknown_pragmas: false,
+            markers: vec![],
+            emitted: 0,
+            source_map: SourceMap::default(),
+        };
+        for definition in predefined::definitions() {
+            preprocessor
+                .define_in(&definition, "<built-in>")
+                .expect("predefined macros are valid");
+        }
+        preprocessor
+    }
+
+    /// warn about pragmas which are ignored (`-Wunknown-pragmas`)
+    pub fn warn_unknown_pragmas(&mut self, is_enabled: bool) {
+        self.warns_unknown_pragmas = is_enabled;
+    }
+
+    /// warnings by `#warning` and unknown pragmas
+    pub fn warnings(&self) -> &[PreprocessWarning] {
+        &self.warnings
+    }
+
+    /// changes of the presumed files and lines in the tokens of the last preprocessed file, which
+    /// are written by `output::write_preprocessed`
+    pub fn line_markers(&self) -> &[LineMarker] {
+        &self.markers
+    }
+
+    /// files of the spans of the tokens
+    pub fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
+
+    /// define a macro as `-D name` (defined as 1) or `-D name=replacement`
+    pub fn define(&mut self, definition: &str) -> Result<()> {
+        self.define_in(definition, "<command line>")
+    }
+
+    /// define a macro whose definition is added to the source map as a file of the name
+    fn define_in(&mut self, definition: &str, name: &str) -> Result<()> {
+        let definition = match definition.find('=') {
+            Some(index) => format!("{} {}", &definition[..index], &definition[index + 1..]),
+            None => format!("{} 1", definition),
+        };
+        let file = self
+            .source_map
+            .add_file(name.to_owned(), definition.clone());
+        let tokens =
+            tokenize(&definition, 0, 0, file, 0).map_err(|errors| self.lex_error(errors))?;
+        let definition = Macro::parse(tokens).map_err(|kind| self.error(kind))?;
+        self.macros
+            .define(definition)
+            .map_err(|kind| self.error(kind))
+    }
+
+    /// undefine a macro as `-U name`
+    pub fn undefine(&mut self, name: &str) -> Result<()> {
+        self.macros.undefine(name).map_err(|kind| self.error(kind))
+    }
+
+    pub fn preprocess_file(&mut self, path: &Path) -> Result<Vec<ManagedToken>> {
+        let code = self.read(path)?;
+        self.preprocess(&code, path)
+    }
+
+    /// preprocess code of a file at the path (the file does not have to exist)
+    pub fn preprocess(&mut self, code: &str, path: &Path) -> Result<Vec<ManagedToken>> {
+        let kind = if self.stack.is_empty() {
+            self.markers.clear();
+            self.emitted = 0;
+            MarkerKind::Start
+        } else {
+            MarkerKind::Enter
+        };
+        self.mark(0, path.to_owned(), kind);
+        let file = self
+            .source_map
+            .add_file(path.display().to_string(), code.to_owned());
+        self.stack.push(IncludeFrame {
+            path: path.to_owned(),
+            file,
+            identity: fs::canonicalize(path).ok(),
+            name: path.to_owned(),
+            line: 0,
+            line_delta: 0,
+            generation: self.macros.generation(),
+            conditionals: vec![],
+        });
+        self.macros.set_file(path.display().to_string());
+        let tokens = self.preprocess_lines(code);
+        self.stack.pop();
+        if let Some(frame) = self.stack.last() {
+            self.macros.set_file(frame.name.display().to_string());
+        }
+        tokens
+    }
+
+    /// lines between directives are expanded together, since an invocation of a function-like
+    /// macro can continue over lines
+    ///
+    /// skipped groups of conditionals are not tokenized, and only conditional directives in them
+    /// are processed
+    fn preprocess_lines(&mut self, code: &str) -> Result<Vec<ManagedToken>> {
+        let mut tokens = vec![];
+        let mut pending = vec![];
+        // lines after a lexical error are still tokenized to find all the errors in the file
+        let mut lex_errors = vec![];
+        for source_line in split_lines(code) {
+            match source_line.directive() {
+                Some((name, rest))
+                    if self.is_active() || CONDITIONAL_DIRECTIVES.contains(&name) =>
+                {
+                    if lex_errors.is_empty() {
+                        self.flush(&mut tokens, pending)?;
+                    }
+                    pending = vec![];
+                    self.frame().line = self.presumed_line(source_line.line);
+                    match self.preprocess_directive(name, rest, &source_line) {
+                        Ok(mut directive_tokens) => tokens.append(&mut directive_tokens),
+                        // the lexical errors before the directive are reported first
+                        Err(_) if !lex_errors.is_empty() => break,
+                        Err(error) => return Err(error),
+                    }
+                }
+                _ if !self.is_active() => (),
+                _ => {
+                    let line = self.presumed_line(source_line.line);
+                    let file = self.frame().file;
+                    match tokenize(source_line.text, line, 0, file, source_line.offset) {
+                        Ok(mut line_tokens) => pending.append(&mut line_tokens),
+                        Err(mut errors) => lex_errors.append(&mut errors),
+                    }
+                }
+            }
+        }
+        if let Some(error) = lex_errors.first() {
+            self.frame().line = error.line;
+            return Err(self.lex_error(lex_errors));
+        }
+        self.flush(&mut tokens, pending)?;
+        if let Some(conditional) = self.frame().conditionals.last() {
+            self.frame().line = conditional.line;
+            return Err(self.error(PreprocessErrorKind::UnterminatedConditional));
+        }
+        Ok(tokens)
+    }
+
+    /// expand tokens which are given by lines into the tokens of the file
+    fn flush(&mut self, tokens: &mut Vec<ManagedToken>, pending: Vec<ManagedToken>) -> Result<()> {
+        let mut expanded = self.expand(pending)?;
+        self.emitted += expanded.len();
+        tokens.append(&mut expanded);
+        Ok(())
+    }
+
+    fn expand(&mut self, tokens: Vec<ManagedToken>) -> Result<Vec<ManagedToken>> {
+        self.macros.expand(tokens).map_err(|(kind, line)| {
+            self.frame().line = line;
+            self.error(kind)
+        })
+    }
+
+    /// directive := Token::Punct(Punct::Hash) Token::Identifier rest_of_line
+    ///
+    /// returns the tokens which the directive is replaced with
+    fn preprocess_directive(
+        &mut self,
+        name: &str,
+        rest: &str,
+        source_line: &SourceLine,
+    ) -> Result<Vec<ManagedToken>> {
+        // the rest of the line is tokenized at its column in the line (locations count chars)
+        let offset = rest.as_ptr() as usize - source_line.text.as_ptr() as usize;
+        let column = source_line.text[..offset].chars().count() as u32;
+        let line = self.presumed_line(source_line.line);
+        let file = self.frame().file;
+        let stack = self.stack();
+        let tokens = || {
+            tokenize(rest, line, column, file, source_line.offset + offset).map_err(|errors| {
+                let stack = stack.clone();
+                let kind = PreprocessErrorKind::Lex(errors);
+                PreprocessError { kind, stack }
+            })
+        };
+        // physical line after the directive
+        let next_line = source_line.line + source_line.text.matches('\n').count() as u32 + 1;
+        match name {
+            // null directive
+            "" if tokens()?.is_empty() => Ok(vec![]),
+            "include" => self.include(rest, tokens, next_line),
+            "define" => {
+                let definition = Macro::parse(tokens()?).map_err(|kind| self.error(kind))?;
+                self.macros
+                    .define(definition)
+                    .map_err(|kind| self.error(kind))?;
+                Ok(vec![])
+            }
+            "if" | "ifdef" | "ifndef" => {
+                let line = self.frame().line;
+                // conditionals in skipped groups are skipped without their conditions
+                let is_skipped = !self.is_active();
+                let is_active = !is_skipped && self.condition(name, tokens()?)?;
+                self.frame().conditionals.push(Conditional {
+                    line,
+                    is_active,
+                    is_taken: is_active || is_skipped,
+                    has_else: false,
+                });
+                Ok(vec![])
+            }
+            "elif" => {
+                let conditional = self.conditional(name)?.clone();
+                if conditional.has_else {
+                    return Err(self.unbalanced("#elif after #else"));
+                }
+                let is_active = !conditional.is_taken && self.condition("if", tokens()?)?;
+                let conditional = self.conditional(name)?;
+                conditional.is_active = is_active;
+                conditional.is_taken |= is_active;
+                Ok(vec![])
+            }
+            "else" => {
+                let conditional = self.conditional(name)?;
+                if conditional.has_else {
+                    return Err(self.unbalanced("#else after #else"));
+                }
+                conditional.is_active = !conditional.is_taken;
+                conditional.is_taken = true;
+                conditional.has_else = true;
+                Ok(vec![])
+            }
+            "endif" => {
+                self.conditional(name)?;
+                self.frame().conditionals.pop();
+                Ok(vec![])
+            }
+            "undef" => match tokens()?.as_slice() {
+                [name] => match name_of(name.get_token()) {
+                    Some(name) => {
+                        self.undefine(name)?;
+                        Ok(vec![])
+                    }
+                    None => {
+                        let cause = format!("macro name {}", name.get_token().spelling());
+                        Err(self.invalid_macro(cause))
+                    }
+                },
+                _ => Err(self.invalid_macro("#undef without a macro name".to_owned())),
+            },
+            "line" => {
+                self.set_line(tokens()?, next_line, false)?;
+                Ok(vec![])
+            }
+            // line marker of preprocessed code (`# 12 "file.h" 2`)
+            _ if !name.is_empty() && name.chars().all(|c| c.is_ascii_digit()) => {
+                let (name_column, name_offset) = (
+                    column - name.chars().count() as u32,
+                    source_line.offset + offset - name.len(),
+                );
+                let mut marker = tokenize(name, line, name_column, file, name_offset)
+                    .map_err(|errors| self.lex_error(errors))?;
+                marker.append(&mut tokens()?);
+                self.set_line(marker, next_line, true)?;
+                Ok(vec![])
+            }
+            "error" => {
+                let kind = PreprocessErrorKind::ErrorDirective(rest.trim().to_owned());
+                Err(self.error(kind))
+            }
+            "warning" => {
+                self.warn(PreprocessWarningKind::WarningDirective(
+                    rest.trim().to_owned(),
+                ));
+                Ok(vec![])
+            }
+            "pragma" => {
+                let tokens = tokens()?;
+                match tokens.first().map(|token| token.get_token().spelling()) {
+                    Some(pragma) if pragma == "once" && tokens.len() == 1 => {
+                        if let Some(identity) = self.frame().identity.clone() {
+                            self.once.insert(identity);
+                        }
+                    }
+                    // standard pragmas are ignored without warnings
+                    Some(pragma) if pragma == "STDC" => (),
+                    _ if self.warns_unknown_pragmas => {
+                        self.warn(PreprocessWarningKind::UnknownPragma(rest.trim().to_owned()))
+                    }
+                    _ => (),
+                }
+                Ok(vec![])
+            }
+            _ => Err(self.error(PreprocessErrorKind::UnknownDirective(name.to_owned()))),
+        }
+    }
+
+    /// line := Token::Punct(Punct::Hash) Token::Identifier("line") Token::Number Token::StringLiteral?
+    /// marker := Token::Punct(Punct::Hash) Token::Number (Token::StringLiteral Token::Number*)?
+    ///
+    /// the line (starting with 1) and the file name of the next line are given after macros are
+    /// expanded, and the flags of a marker are ignored
+    fn set_line(
+        &mut self,
+        tokens: Vec<ManagedToken>,
+        next_line: u32,
+        is_marker: bool,
+    ) -> Result<()> {
+        let mut tokens = self.expand(tokens)?;
+        if is_marker && tokens.len() > 2 {
+            if let Some(flag) = tokens[2..]
+                .iter()
+                .find(|token| !matches!(token.get_token(), Token::Number(_)))
+            {
+                return Err(self.invalid_line(&flag.get_token().spelling()));
+            }
+            tokens.truncate(2);
+        }
+        let (number, name) = match tokens.as_slice() {
+            [number] => (number, None),
+            [number, name] => match name.get_token().get_string_literal() {
+                Ok(name) => (number, Some(name)),
+                Err(_) => return Err(self.invalid_line(&name.get_token().spelling())),
+            },
+            _ => return Err(self.invalid_line("no line number")),
+        };
+        let line = match number.get_token() {
+            Token::Number(number)
+                if number.radix == 10 && !number.is_unsigned && number.long == 0 =>
+            {
+                number
+                    .value()
+                    .ok()
+                    .filter(|line| (1..=2_147_483_647).contains(line))
+            }
+            _ => None,
+        };
+        let line = match line {
+            Some(line) => line,
+            None => return Err(self.invalid_line(&number.get_token().spelling())),
+        };
+        // lines of tokens start with 0
+        self.frame().line_delta = line as i64 - 1 - next_line as i64;
+        if let Some(name) = name {
+            self.frame().name = PathBuf::from(&name);
+            self.macros.set_file(name);
+        }
+        let name = self.frame().name.clone();
+        self.mark(line as u32 - 1, name, MarkerKind::Line);
+        Ok(())
+    }
+
+    /// mark the presumed file and line of the next token
+    fn mark(&mut self, line: u32, file: PathBuf, kind: MarkerKind) {
+        self.markers.push(LineMarker {
+            index: self.emitted,
+            line,
+            file,
+            kind,
+        });
+    }
+
+    /// convert a physical line of the current file to the presumed line
+    fn presumed_line(&self, line: u32) -> u32 {
+        let delta = self.stack.last().map_or(0, |frame| frame.line_delta);
+        (line as i64 + delta) as u32
+    }
+
+    fn lex_error(&self, errors: Vec<LexError>) -> PreprocessError {
+        self.error(PreprocessErrorKind::Lex(errors))
+    }
+
+    fn invalid_line(&self, cause: &str) -> PreprocessError {
+        self.error(PreprocessErrorKind::InvalidLine(cause.to_owned()))
+    }
+
+    fn warn(&mut self, kind: PreprocessWarningKind) {
+        let stack = self.stack();
+        self.warnings.push(PreprocessWarning { kind, stack });
+    }
+
+    /// if := Token::Punct(Punct::Hash) Token::Identifier("if") condition
+    /// ifdef := Token::Punct(Punct::Hash) (Token::Identifier("ifdef") | Token::Identifier("ifndef")) Token::Identifier
+    ///
+    /// `defined` in a condition is replaced before macros are expanded
+    fn condition(&mut self, name: &str, tokens: Vec<ManagedToken>) -> Result<bool> {
+        match name {
+            "ifdef" | "ifndef" => match tokens.as_slice() {
+                [token] if name_of(token.get_token()).is_some() => {
+                    let is_defined = self.macros.is_defined(&token.get_token().spelling());
+                    Ok(is_defined == (name == "ifdef"))
+                }
+                _ => {
+                    let cause = format!("#{} without a macro name", name);
+                    Err(self.error(PreprocessErrorKind::InvalidCondition(cause)))
+                }
+            },
+            _ => {
+                let tokens = condition::replace_defined(tokens, &self.macros)
+                    .map_err(|kind| self.error(kind))?;
+                let tokens = self.expand(tokens)?;
+                condition::evaluate(&tokens).map_err(|kind| self.error(kind))
+            }
+        }
+    }
+
+    /// check whether the lines are processed, which are not in skipped groups
+    fn is_active(&self) -> bool {
+        match self
+            .stack
+            .last()
+            .and_then(|frame| frame.conditionals.last())
+        {
+            Some(conditional) => conditional.is_active,
+            None => true,
+        }
+    }
+
+    /// get the innermost conditional for `#elif`, `#else` or `#endif`
+    fn conditional(&mut self, directive: &str) -> Result<&mut Conditional> {
+        if self.frame().conditionals.is_empty() {
+            return Err(self.unbalanced(&format!("#{} without #if", directive)));
+        }
+        Ok(self.frame().conditionals.last_mut().unwrap())
+    }
+
+    fn unbalanced(&self, cause: &str) -> PreprocessError {
+        self.error(PreprocessErrorKind::UnbalancedConditional(cause.to_owned()))
+    }
+
+    /// include := Token::Punct(Punct::Hash) Token::Identifier("include") ("\"" path "\"" | "<" path ">")
+    ///     | Token::Punct(Punct::Hash) Token::Identifier("include") tokens_expanded_to_string_literal
+    fn include(
+        &mut self,
+        rest: &str,
+        tokens: impl Fn() -> Result<Vec<ManagedToken>>,
+        next_line: u32,
+    ) -> Result<Vec<ManagedToken>> {
+        let invalid = || PreprocessErrorKind::InvalidInclude(rest.trim().to_owned());
+        // only white space and comments can follow a header name
+        let is_blank = |text: &str| matches!(Tokenizer::tokenize(&text.to_owned()), Ok(tokens) if tokens.is_empty());
+        let (name, is_quoted) = match parse_header_name(rest) {
+            Some((name, is_quoted, trailing)) if is_blank(trailing) => (name.to_owned(), is_quoted),
+            Some(_) => return Err(self.error(invalid())),
+            // the header name is given by macros
+            None => match self.expand(tokens()?)?.as_slice() {
+                [name] => match name.get_token().get_string_literal() {
+                    Ok(name) if !name.is_empty() => (name, true),
+                    _ => return Err(self.error(invalid())),
+                },
+                _ => return Err(self.error(invalid())),
+            },
+        };
+        let name = name.as_str();
+        let path = match self.find(name, is_quoted) {
+            Some(path) => path,
+            None => return Err(self.error(PreprocessErrorKind::NotFound(name.to_owned()))),
+        };
+        // including a file again with the same macros would repeat forever
+        let identity = fs::canonicalize(&path).ok();
+        if matches!(&identity, Some(identity) if self.once.contains(identity)) {
+            return Ok(vec![]);
+        }
+        let generation = self.macros.generation();
+        if self.stack.iter().any(|frame| {
+            frame.identity.is_some() && frame.identity == identity && frame.generation == generation
+        }) {
+            return Err(self.error(PreprocessErrorKind::IncludeCycle(path)));
+        }
+        if self.stack.len() >= MAX_INCLUDE_DEPTH {
+            return Err(self.error(PreprocessErrorKind::IncludeDepth(path)));
+        }
+        let tokens = self.preprocess_file(&path)?;
+        let (line, name) = (self.presumed_line(next_line), self.frame().name.clone());
+        self.mark(line, name, MarkerKind::Return);
+        Ok(tokens)
+    }
+
+    /// find a file to include
+    fn find(&self, name: &str, is_quoted: bool) -> Option<PathBuf> {
+        let mut directories = vec![];
+        if is_quoted {
+            let directory = self.stack.last().and_then(|frame| frame.path.parent());
+            directories.push(directory.unwrap_or_else(|| Path::new("")).to_owned());
+        }
+        directories.extend(self.include_paths.iter().cloned());
+        directories
+            .into_iter()
+            .map(|directory| directory.join(name))
+            .find(|path| path.is_file())
+    }
+
+    fn read(&self, path: &Path) -> Result<String> {
+        fs::read_to_string(path)
+            .map_err(|err| self.error(PreprocessErrorKind::Read(path.to_owned(), err.to_string())))
+    }
+
+    fn invalid_macro(&self, cause: String) -> PreprocessError {
+        self.error(PreprocessErrorKind::InvalidMacro(cause))
+    }
+
+    fn frame(&mut self) -> &mut IncludeFrame {
+        self.stack.last_mut().expect("no file is preprocessed")
+    }
+
+    fn error(&self, kind: PreprocessErrorKind) -> PreprocessError {
+        PreprocessError {
+            kind,
+            stack: self.stack(),
+        }
+    }
+
+    /// presumed files and lines of the include stack from the innermost file
+    ///
+    /// lines start with 1 as `__LINE__` and `#line`
+    fn stack(&self) -> Vec<(PathBuf, u32)> {
+        self.stack
+            .iter()
+            .rev()
+            .map(|frame| (frame.name.clone(), frame.line + 1))
+            .collect()
+    }
+}
+
+/// tokenize a logical line and move its tokens (or its lexical errors) to the physical line and
+/// the column where it starts, and their spans to the offset in the file
+fn tokenize(
+    text: &str,
+    line: u32,
+    column: u32,
+    file: FileId,
+    offset: usize,
+) -> std::result::Result<Vec<ManagedToken>, Vec<LexError>> {
+    let position = |token_line: u32, location: u32| match token_line {
+        0 => (token_line + line, location + column),
+        _ => (token_line + line, location),
+    };
+    match Tokenizer::tokenize(&text.to_owned()) {
+        Ok(tokens) => Ok(tokens
+            .into_iter()
+            .map(|token| {
+                let (line, location) = position(token.get_line(), token.get_location());
+                let span = token.get_span().shift(file, offset as u32);
+                ManagedToken::new(token.get_token().clone(), line, location).with_span(span)
+            })
+            .collect()),
+        Err(errors) => Err(errors
+            .into_iter()
+            .map(|error| {
+                let (line, location) = position(error.line, error.location);
+                let span = error.span.shift(file, offset as u32);
+                LexError {
+                    line,
+                    location,
+                    span,
+                    ..error
+                }
+            })
+            .collect()),
+    }
+}
+
+/// get the name of a header (`"name"` or `<name>`), whether it is quoted and the trailing text
+fn parse_header_name(text: &str) -> Option<(&str, bool, &str)> {
+    let text = text.trim_start();
+    let (close, is_quoted) = match text.chars().next()? {
+        '"' => ('"', true),
+        '<' => ('>', false),
+        _ => return None,
+    };
+    let end = text[1..].find(close)? + 1;
+    match &text[1..end] {
+        "" => None,
+        name => Some((name, is_quoted, &text[end + 1..])),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use rcc_syntax::symbol::Symbol;
+    use rcc_syntax::token::{Keyword, Punct};
+
+    /// create files in a new temporary directory
+    fn files(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("rcc_preprocessor_{}", name));
+        let _ = fs::remove_dir_all(&directory);
+        for (path, code) in files {
+            let path = directory.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, code).unwrap();
+        }
+        directory
+    }
+
+    #[test]
+    fn include_with_search_paths() {
+        let directory = files(
+            "search_paths",
+            &[
+                (
+                    "main.c",
+                    "#include \"local.h\"\n  # include <lib.h> // lib\nint c;\n",
+                ),
+                ("local.h", "int a;\n"),
+                ("include/lib.h", "\n#include \"local.h\"\n"),
+                ("include/local.h", "int b;\n"),
+            ],
+        );
+        let mut preprocessor = Preprocessor::new(vec![directory.join("include")]);
+        let tokens = preprocessor
+            .preprocess_file(&directory.join("main.c"))
+            .unwrap();
+        let actual: Vec<(Token, u32)> = tokens
+            .into_iter()
+            .map(|token| (token.get_token().clone(), token.get_line()))
+            .collect();
+        let declaration = |identifier: &str, line| {
+            vec![
+                (Token::Keyword(Keyword::Int), line),
+                (Token::Identifier(Symbol::intern(identifier)), line),
+                (Token::Punct(Punct::Semicolon), line),
+            ]
+        };
+        // the quoted include in lib.h finds the file next to it
+        let expect = [
+            declaration("a", 0),
+            declaration("b", 0),
+            declaration("c", 2),
+        ]
+        .concat();
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn include_not_found() {
+        let directory = files("not_found", &[("main.c", "int a;\n#include <none.h>\n")]);
+        let path = directory.join("main.c");
+        let actual = Preprocessor::new(vec![]).preprocess_file(&path);
+        let expect = PreprocessError {
+            kind: PreprocessErrorKind::NotFound("none.h".to_owned()),
+            stack: vec![(path, 2)],
+        };
+        assert_eq!(actual, Err(expect));
+    }
+
+    #[test]
+    fn include_cycle() {
+        let directory = files(
+            "cycle",
+            &[
+                ("main.c", "#include \"a.h\"\n"),
+                ("a.h", "int a;\n\n#include \"b.h\"\n"),
+                ("b.h", "#include \"a.h\"\n"),
+            ],
+        );
+        let actual = Preprocessor::new(vec![]).preprocess_file(&directory.join("main.c"));
+        let expect = PreprocessError {
+            kind: PreprocessErrorKind::IncludeCycle(directory.join("a.h")),
+            stack: vec![
+                (directory.join("b.h"), 1),
+                (directory.join("a.h"), 3),
+                (directory.join("main.c"), 1),
+            ],
+        };
+        assert_eq!(actual, Err(expect));
+    }
+
+    /// spell the tokens of code preprocessed with command-line definitions
+    fn preprocess_with(definitions: &[&str], code: &str) -> Result<String> {
+        let mut preprocessor = Preprocessor::new(vec![]);
+        for definition in definitions {
+            match definition.strip_prefix('!') {
+                Some(name) => preprocessor.undefine(name)?,
+                None => preprocessor.define(definition)?,
+            }
+        }
+        let tokens = preprocessor.preprocess(code, Path::new("test.c"))?;
+        let spellings: Vec<String> = tokens
+            .iter()
+            .map(|token| token.get_token().spelling())
+            .collect();
+        Ok(spellings.join(" "))
+    }
+
+    #[test]
+    fn conditionals() {
+        let code = r#"
+#ifdef DEBUG
+int debug;
+# if LEVEL > 1 && defined(TRACE) || defined VERBOSE
+int trace;
+# elif LEVEL == 1
+int level1;
+# else
+int quiet;
+# endif
+#elif !defined(RELEASE)
+int test;
+#else
+int release;
+#endif
+#if 0
+#  unknown directives and 'unterminated literals are skipped
+#endif
+#ifndef DEBUG
+int no_debug;
+#endif
+"#;
+        let cases: [(&[&str], &str); 6] = [
+            (&[], "int test ; int no_debug ;"),
+            (&["RELEASE"], "int release ; int no_debug ;"),
+            (&["DEBUG"], "int debug ; int quiet ;"),
+            (&["DEBUG", "LEVEL=1"], "int debug ; int level1 ;"),
+            (&["DEBUG", "LEVEL=2", "TRACE"], "int debug ; int trace ;"),
+            (&["DEBUG", "VERBOSE", "!DEBUG"], "int test ; int no_debug ;"),
+        ];
+        for (definitions, expect) in cases.iter() {
+            assert_eq!(
+                preprocess_with(definitions, code),
+                Ok(expect.to_string()),
+                "{:?}",
+                definitions
+            );
+        }
+        assert_eq!(
+            preprocess_with(
+                &["SIZE(n)=n * 2"],
+                "#if SIZE(3) != 6\n#error\n#endif\nSIZE(1)"
+            ),
+            Ok("1 * 2".to_owned())
+        );
+    }
+
+    #[test]
+    fn unbalanced_conditionals() {
+        let error = |code: &str| {
+            let actual = Preprocessor::new(vec![]).preprocess(code, Path::new("test.c"));
+            let error = actual.unwrap_err();
+            (error.kind, error.stack[0].1)
+        };
+        let unbalanced = |cause: &str| PreprocessErrorKind::UnbalancedConditional(cause.to_owned());
+        assert_eq!(
+            error("#if 1\nint a;\n#if 0\n#endif\n"),
+            (PreprocessErrorKind::UnterminatedConditional, 1)
+        );
+        assert_eq!(
+            error("#if 0\n#else\n#endif\n#else\n"),
+            (unbalanced("#else without #if"), 4)
+        );
+        assert_eq!(
+            error("#if 0\n#else\n#elif 1\n"),
+            (unbalanced("#elif after #else"), 3)
+        );
+        assert_eq!(
+            error("#ifdef 1\n#endif\n"),
+            (
+                PreprocessErrorKind::InvalidCondition("#ifdef without a macro name".to_owned()),
+                1
+            )
+        );
+        assert_eq!(
+            error("#if defined()\n#endif\n"),
+            (
+                PreprocessErrorKind::InvalidCondition("defined without a macro name".to_owned()),
+                1
+            )
+        );
+    }
+
+    #[test]
+    fn include_guards() {
+        let directory = files(
+            "guards",
+            &[
+                ("main.c", "#include \"a.h\"\n#include \"a.h\"\nint c;\n"),
+                (
+                    "a.h",
+                    "#ifndef A_H\n#define A_H\n#include \"b.h\"\nint a;\n#endif\n",
+                ),
+                ("b.h", "#include \"a.h\"\nint b;\n"),
+            ],
+        );
+        let tokens = Preprocessor::new(vec![])
+            .preprocess_file(&directory.join("main.c"))
+            .unwrap();
+        let identifiers: Vec<Symbol> = tokens
+            .iter()
+            .filter_map(|token| token.get_token().get_identifier().ok())
+            .collect();
+        assert_eq!(identifiers, vec!["b", "a", "c"]);
+    }
+
+    #[test]
+    fn predefined_macros_and_line() {
+        let code = r#"int a = __LINE__;
+#if __STDC__ && __STDC_VERSION__ >= 201112L && defined(__x86_64__) && defined __FILE__
+#line 100 "renamed.c"
+int b = __LINE__; char *f = __FILE__;
+#define L __LINE__
+L
+#endif
+"#;
+        let tokens = Preprocessor::new(vec![])
+            .preprocess(code, Path::new("test.c"))
+            .unwrap();
+        let actual: Vec<(String, u32)> = tokens
+            .iter()
+            .map(|token| (token.get_token().spelling(), token.get_line()))
+            .collect();
+        let token = |spelling: &str, line| (spelling.to_owned(), line);
+        let expect = vec![
+            token("int", 0),
+            token("a", 0),
+            token("=", 0),
+            token("1", 0),
+            token(";", 0),
+            token("int", 99),
+            token("b", 99),
+            token("=", 99),
+            token("100", 99),
+            token(";", 99),
+            token("char", 99),
+            token("*", 99),
+            token("f", 99),
+            token("=", 99),
+            token("\"renamed.c\"", 99),
+            token(";", 99),
+            token("102", 101),
+        ];
+        assert_eq!(actual, expect);
+
+        let date_time = preprocess_with(&[], "__DATE__ __TIME__").unwrap();
+        assert_eq!(date_time.len(), "\"Jan  1 1970\" \"00:00:00\"".len());
+        for name in &["__FILE__", "__LINE__"] {
+            let kind = PreprocessErrorKind::InvalidMacro(format!("macro name {}", name));
+            let error = preprocess_with(&[], &format!("#define {} 1\n", name)).unwrap_err();
+            assert_eq!(error.kind, kind);
+            let error = preprocess_with(&[], &format!("#undef {}\n", name)).unwrap_err();
+            assert_eq!(error.kind, kind);
+        }
+    }
+
+    #[test]
+    fn invalid_line() {
+        let invalid = |cause: &str| PreprocessErrorKind::InvalidLine(cause.to_owned());
+        for (code, cause) in [
+            ("#line\n", "no line number"),
+            ("#line 0\n", "0"),
+            ("#line 0x10\n", "0x10"),
+            ("#line 10 name\n", "name"),
+        ]
+        .iter()
+        {
+            assert_eq!(preprocess_with(&[], code).unwrap_err().kind, invalid(cause));
+        }
+        // the line and the file name can be given by macros
+        let code = "#define LINE 20\n#define FILE \"file.c\"\n#line LINE FILE\n__LINE__ __FILE__";
+        assert_eq!(preprocess_with(&[], code), Ok("20 \"file.c\"".to_owned()));
+    }
+
+    #[test]
+    fn lexical_errors() {
+        // skipped groups and the messages of #error are not tokenized
+        let code = "int a;\n#if 0\n@\n#endif\nint @ b = 1 $;\n#error don't\n";
+        let error = preprocess_with(&[], code).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "preprocess error: unexpected char '@' at line 4 location 4, \
+             unexpected char '$' at line 4 location 12 at line 5 of test.c"
+        );
+        match error.kind {
+            PreprocessErrorKind::Lex(errors) => assert_eq!(errors[0].span.start, 26),
+            kind => panic!("unexpected error {:?}", kind),
+        }
+        let error = preprocess_with(&[], "#define A(x) x\n#if A(`)\n#endif\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "preprocess error: unexpected char '`' at line 1 location 6 at line 2 of test.c"
+        );
+        let error = preprocess_with(&["A=1e"], "A").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "preprocess error: invalid floating constant 1e at line 0 location 2"
+        );
+    }
+
+    #[test]
+    fn error_and_warning_directives() {
+        let code = "#warning check  this\n#pragma once\n#pragma STDC FP_CONTRACT ON\n\
+                    #pragma pack(1)\n#line 10 \"main.c\"\n#error stop  here\n";
+        let mut preprocessor = Preprocessor::new(vec![]);
+        preprocessor.warn_unknown_pragmas(true);
+        let actual = preprocessor.preprocess(code, Path::new("test.c"));
+        let expect = PreprocessError {
+            kind: PreprocessErrorKind::ErrorDirective("stop  here".to_owned()),
+            stack: vec![(PathBuf::from("main.c"), 10)],
+        };
+        assert_eq!(actual, Err(expect));
+        let warnings = vec![
+            PreprocessWarning {
+                kind: PreprocessWarningKind::WarningDirective("check  this".to_owned()),
+                stack: vec![(PathBuf::from("test.c"), 1)],
+            },
+            PreprocessWarning {
+                kind: PreprocessWarningKind::UnknownPragma("pack(1)".to_owned()),
+                stack: vec![(PathBuf::from("test.c"), 4)],
+            },
+        ];
+        assert_eq!(preprocessor.warnings(), warnings.as_slice());
+        assert_eq!(
+            warnings[1].to_string(),
+            "preprocess warning: unknown pragma \"pack(1)\" is ignored at line 4 of test.c"
+        );
+    }
+
+    #[test]
+    fn pragma_once() {
+        let directory = files(
+            "once",
+            &[
+                ("main.c", "#include \"a.h\"\n#include \"./a.h\"\nint c;\n"),
+                ("a.h", "#pragma once\n#include \"b.h\"\nint a;\n"),
+                ("b.h", "#include \"a.h\"\nint b;\n"),
+            ],
+        );
+        let tokens = Preprocessor::new(vec![])
+            .preprocess_file(&directory.join("main.c"))
+            .unwrap();
+        let identifiers: Vec<Symbol> = tokens
+            .iter()
+            .filter_map(|token| token.get_token().get_identifier().ok())
+            .collect();
+        assert_eq!(identifiers, vec!["b", "a", "c"]);
+    }
+
+    #[test]
+    fn spans_in_files() {
+        let directory = files(
+            "spans",
+            &[
+                (
+                    "main.c",
+                    "#define N(x) x + 1\n#include \"a.h\"\n  int b = N(M);\n",
+                ),
+                ("a.h", "int a;\n"),
+            ],
+        );
+        let mut preprocessor = Preprocessor::new(vec![]);
+        preprocessor.define("M=2").unwrap();
+        let tokens = preprocessor
+            .preprocess_file(&directory.join("main.c"))
+            .unwrap();
+        let source_map = preprocessor.source_map();
+        let actual: Vec<(String, &str)> = tokens
+            .iter()
+            .map(|token| {
+                let span = token.get_span();
+                let name = Path::new(&source_map.file(span.file).name).file_name();
+                let name = name.unwrap().to_string_lossy().into_owned();
+                (name, source_map.snippet(span))
+            })
+            .collect();
+        let span = |name: &str, snippet| (name.to_owned(), snippet);
+        // tokens from macros are in the spans of their invocations
+        let expect = vec![
+            span("a.h", "int"),
+            span("a.h", "a"),
+            span("a.h", ";"),
+            span("main.c", "int"),
+            span("main.c", "b"),
+            span("main.c", "="),
+            span("main.c", "M"),
+            span("main.c", "N"),
+            span("main.c", "N"),
+            span("main.c", ";"),
+        ];
+        assert_eq!(actual, expect);
+        let (line, column) = source_map.position(tokens[3].get_span());
+        assert_eq!((line, column), (2, 2));
+    }
+}
@@ -0,0 +1,154 @@
+/// logical line of source code
+///
+/// a logical line continues over newlines spliced by backslashes or in block comments, so that a
+/// directive is always in a single logical line
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceLine<'a> {
+    pub text: &'a str,
+    pub line: u32,     // physical line where it starts
+    pub offset: usize, // byte offset in the code where it starts
+}
+impl<'a> SourceLine<'a> {
+    /// get the name and the rest of a directive (`# name rest`) if the line is a directive
+    pub fn directive(&self) -> Option<(&'a str, &'a str)> {
+        let text = self.text.trim_start_matches(is_blank);
+        let text = text.strip_prefix('#')?.trim_start_matches(is_blank);
+        let end = text
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(text.len());
+        Some((&text[..end], &text[end..]))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Code,
+    Literal(char), // quote of a string literal or a character constant
+    LineComment,
+    BlockComment,
+}
+
+/// split source code into logical lines
+///
+/// lines keep the spliced newlines and comments, which are handled by the tokenizer
+pub fn split_lines(code: &str) -> Vec<SourceLine<'_>> {
+    let mut lines = vec![];
+    let (mut start, mut line, mut newlines) = (0, 0, 0);
+    let mut state = State::Code;
+    let mut chars = code.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '\\' && is_splice(&code[i + 1..]) {
+            for (_, c) in chars.by_ref() {
+                if c == '\n' {
+                    break;
+                }
+            }
+            newlines += 1;
+            continue;
+        }
+        if c == '\n' {
+            if state == State::BlockComment {
+                newlines += 1;
+                continue;
+            }
+            lines.push(SourceLine {
+                text: &code[start..i],
+                line,
+                offset: start,
+            });
+            line += newlines + 1;
+            newlines = 0;
+            start = i + 1;
+            state = State::Code;
+            continue;
+        }
+        let next = chars.peek().map(|&(_, c)| c);
+        state = match state {
+            State::Code => match (c, next) {
+                ('"', _) | ('\'', _) => State::Literal(c),
+                ('/', Some('/')) => State::LineComment,
+                ('/', Some('*')) => {
+                    chars.next(); // consume * which cannot close the comment
+                    State::BlockComment
+                }
+                _ => State::Code,
+            },
+            State::Literal(quote) => {
+                if c == '\\' {
+                    chars.next(); // consume escaped char
+                }
+                if c == quote {
+                    State::Code
+                } else {
+                    State::Literal(quote)
+                }
+            }
+            State::LineComment => State::LineComment,
+            State::BlockComment if c == '*' && next == Some('/') => {
+                chars.next(); // consume /
+                State::Code
+            }
+            State::BlockComment => State::BlockComment,
+        };
+    }
+    if start < code.len() {
+        lines.push(SourceLine {
+            text: &code[start..],
+            line,
+            offset: start,
+        });
+    }
+    lines
+}
+
+/// check whether the text starts with a newline, which is spliced after a backslash
+fn is_splice(text: &str) -> bool {
+    text.starts_with('\n') || text.starts_with("\r\n")
+}
+
+/// white-space chars in a line
+fn is_blank(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\x0b' | '\x0c' | '\r')
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn spliced_lines_and_block_comments() {
+        let code = "#define A \\\n 1\nint a; /* a\n b */ int b;\n\"// not a comment\" // c\n";
+        let lines = split_lines(code);
+        let expect = vec![
+            SourceLine {
+                text: "#define A \\\n 1",
+                line: 0,
+                offset: 0,
+            },
+            SourceLine {
+                text: "int a; /* a\n b */ int b;",
+                line: 2,
+                offset: 15,
+            },
+            SourceLine {
+                text: "\"// not a comment\" // c",
+                line: 4,
+                offset: 40,
+            },
+        ];
+        assert_eq!(lines, expect);
+        assert_eq!(lines[0].directive(), Some(("define", " A \\\n 1")));
+        assert_eq!(lines[1].directive(), None);
+    }
+
+    #[test]
+    fn null_directive() {
+        let line = SourceLine {
+            text: "  #  ",
+            line: 0,
+            offset: 0,
+        };
+        assert_eq!(line.directive(), Some(("", "")));
+    }
+}
@@ -0,0 +1,653 @@
+use super::error::PreprocessErrorKind;
+use rcc_syntax::token::{Expansion, IntegerConstant, ManagedToken, Punct, Token};
+use rcc_syntax::Tokenizer;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::rc::Rc;
+
+/// macros which are expanded to the presumed file name and line of their tokens, and which cannot
+/// be defined or undefined
+const BUILTIN_MACROS: [&str; 2] = ["__FILE__", "__LINE__"];
+
+/// macro defined by `#define`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Macro {
+    pub name: ManagedToken,
+    pub parameters: Option<Vec<String>>, // None for an object-like macro
+    pub is_variadic: bool,               // the last parameter is `__VA_ARGS__`
+    pub replacement: Vec<ManagedToken>,
+}
+impl Macro {
+    /// parse a macro definition from the tokens after `#define`
+    ///
+    /// define := Token::Punct(Punct::Hash) Token::Identifier("define") Token::Identifier parameters? replacement
+    /// parameters :=
+    /// Token::Punct(Punct::LeftParen)
+    ///     (Token::Identifier (Token::Punct(Punct::Comma) Token::Identifier)* (Token::Punct(Punct::Comma) Token::Punct(Punct::Ellipsis))?
+    ///     | Token::Punct(Punct::Ellipsis))?
+    /// Token::Punct(Punct::RightParen)
+    ///
+    /// the parameters follow the name without white space, or the parenthesis is replacement
+    pub fn parse(tokens: Vec<ManagedToken>) -> Result<Macro, PreprocessErrorKind> {
+        let invalid = |cause: &str| PreprocessErrorKind::InvalidMacro(cause.to_owned());
+        let mut tokens = tokens.into_iter().peekable();
+        let name = match tokens.next() {
+            Some(name) => name,
+            None => return Err(invalid("no macro name")),
+        };
+        match name_of(name.get_token()) {
+            Some(identifier)
+                if identifier != "defined" && !BUILTIN_MACROS.contains(&identifier) => {}
+            _ => {
+                let cause = format!("macro name {}", name.get_token().spelling());
+                return Err(invalid(&cause));
+            }
+        }
+
+        let mut parameters = None;
+        let mut is_variadic = false;
+        match tokens.peek() {
+            Some(token)
+                if token.get_token().is_punct(Punct::LeftParen) && !is_separated(&name, token) =>
+            {
+                tokens.next(); // consume (
+                let mut names: Vec<String> = vec![];
+                loop {
+                    let token = tokens
+                        .next()
+                        .ok_or_else(|| invalid("unterminated parameters"))?;
+                    match token.get_token() {
+                        Token::Punct(Punct::RightParen) if names.is_empty() => break,
+                        Token::Punct(Punct::Ellipsis) => {
+                            is_variadic = true;
+                            names.push("__VA_ARGS__".to_owned());
+                        }
+                        token => match name_of(token) {
+                            Some(identifier)
+                                if identifier != "__VA_ARGS__"
+                                    && !names.iter().any(|name| name == identifier) =>
+                            {
+                                names.push(identifier.to_owned())
+                            }
+                            _ => return Err(invalid(&format!("parameter {}", token.spelling()))),
+                        },
+                    }
+                    match tokens.next() {
+                        Some(token) if token.get_token().is_punct(Punct::RightParen) => break,
+                        Some(token) if token.get_token().is_punct(Punct::Comma) && !is_variadic => {
+                        }
+                        _ => return Err(invalid("unterminated parameters")),
+                    }
+                }
+                parameters = Some(names);
+            }
+            _ => (),
+        }
+
+        let replacement: Vec<ManagedToken> = tokens.collect();
+        let definition = Macro {
+            name,
+            parameters,
+            is_variadic,
+            replacement,
+        };
+        definition.check_replacement()?;
+        Ok(definition)
+    }
+
+    /// `#` is followed by a parameter in a function-like macro, `##` is between operands and
+    /// `__VA_ARGS__` is only in a variadic macro
+    fn check_replacement(&self) -> Result<(), PreprocessErrorKind> {
+        let invalid = |cause: &str| PreprocessErrorKind::InvalidMacro(cause.to_owned());
+        let replacement = &self.replacement;
+        for (i, token) in replacement.iter().enumerate() {
+            match token.get_token() {
+                Token::Punct(Punct::Hash) if self.parameters.is_some() => {
+                    match replacement.get(i + 1) {
+                        Some(next) if self.parameter_index(next).is_some() => (),
+                        _ => return Err(invalid("# is not followed by a parameter")),
+                    }
+                }
+                Token::Punct(Punct::HashHash) if i == 0 || i + 1 == replacement.len() => {
+                    return Err(invalid("## at either end of a replacement list"))
+                }
+                Token::Identifier(identifier)
+                    if identifier == "__VA_ARGS__" && !self.is_variadic =>
+                {
+                    return Err(invalid("__VA_ARGS__ of a macro without variable arguments"))
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn parameter_index(&self, token: &ManagedToken) -> Option<usize> {
+        match (name_of(token.get_token()), &self.parameters) {
+            (Some(identifier), Some(parameters)) => parameters
+                .iter()
+                .position(|parameter| parameter == identifier),
+            _ => None,
+        }
+    }
+
+    /// check whether two definitions are the same, which allows a macro to be defined again
+    fn is_same(&self, other: &Macro) -> bool {
+        let spacing = |replacement: &[ManagedToken]| {
+            replacement
+                .windows(2)
+                .map(|pair| is_separated(&pair[0], &pair[1]))
+                .collect::<Vec<bool>>()
+        };
+        self.parameters == other.parameters
+            && self.is_variadic == other.is_variadic
+            && self.replacement.len() == other.replacement.len()
+            && self
+                .replacement
+                .iter()
+                .zip(other.replacement.iter())
+                .all(|(token, other)| token.get_token() == other.get_token())
+            && spacing(&self.replacement) == spacing(&other.replacement)
+    }
+}
+
+/// token being expanded with the names of the macros which are not expanded from it
+///
+/// a macro is not expanded again in its own replacement, even through other macros, since its
+/// name is in the hide sets of the replaced tokens ("blue paint")
+#[derive(Clone, Debug)]
+struct Item {
+    token: ManagedToken,
+    hide_set: Rc<HashSet<String>>,
+}
+impl Item {
+    fn new(token: ManagedToken) -> Item {
+        Item {
+            token,
+            hide_set: Rc::new(HashSet::new()),
+        }
+    }
+
+    fn hide(mut self, names: &HashSet<String>) -> Item {
+        if !names.is_subset(&self.hide_set) {
+            self.hide_set = Rc::new(self.hide_set.union(names).cloned().collect());
+        }
+        self
+    }
+}
+
+/// error with the line of the macro invocation
+type ExpansionResult<T> = Result<T, (PreprocessErrorKind, u32)>;
+
+/// macros defined in a translation unit
+#[derive(Debug, Default)]
+pub struct Macros {
+    macros: HashMap<String, Macro>,
+    generation: u64, // changes whenever a macro is defined or undefined
+    file: String,    // presumed name of the file being preprocessed (`__FILE__`)
+}
+impl Macros {
+    /// define a macro, which can be defined again only with the same definition
+    pub fn define(&mut self, definition: Macro) -> Result<(), PreprocessErrorKind> {
+        let name = definition.name.get_token().spelling();
+        match self.macros.get(&name) {
+            Some(defined) if !defined.is_same(&definition) => {
+                Err(PreprocessErrorKind::Redefinition(name))
+            }
+            _ => {
+                self.macros.insert(name, definition);
+                self.generation += 1;
+                Ok(())
+            }
+        }
+    }
+
+    pub fn undefine(&mut self, name: &str) -> Result<(), PreprocessErrorKind> {
+        if BUILTIN_MACROS.contains(&name) {
+            let cause = format!("macro name {}", name);
+            return Err(PreprocessErrorKind::InvalidMacro(cause));
+        }
+        self.macros.remove(name);
+        self.generation += 1;
+        Ok(())
+    }
+
+    pub fn set_file(&mut self, file: String) {
+        self.file = file;
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn is_defined(&self, name: &str) -> bool {
+        BUILTIN_MACROS.contains(&name) || self.macros.contains_key(name)
+    }
+
+    /// expand macros in tokens and rescan the replacements
+    pub fn expand(&self, tokens: Vec<ManagedToken>) -> ExpansionResult<Vec<ManagedToken>> {
+        let items = self.expand_items(tokens.into_iter().map(Item::new).collect())?;
+        Ok(items.into_iter().map(|item| item.token).collect())
+    }
+
+    fn expand_items(&self, items: Vec<Item>) -> ExpansionResult<Vec<Item>> {
+        let mut input: VecDeque<Item> = items.into();
+        let mut output = vec![];
+        while let Some(item) = input.pop_front() {
+            if let Some(token) = self.expand_builtin(&item.token) {
+                output.push(Item {
+                    token,
+                    hide_set: item.hide_set,
+                });
+                continue;
+            }
+            let definition = match name_of(item.token.get_token()) {
+                Some(name) if !item.hide_set.contains(name) => self.macros.get(name),
+                _ => None,
+            };
+            let definition = match definition {
+                Some(definition) => definition,
+                None => {
+                    output.push(item);
+                    continue;
+                }
+            };
+            let mut hide_set = (*item.hide_set).clone();
+            let mut arguments = vec![];
+            if definition.parameters.is_some() {
+                // the name of a function-like macro without arguments is not an invocation
+                match input.front() {
+                    Some(next) if next.token.get_token().is_punct(Punct::LeftParen) => (),
+                    _ => {
+                        output.push(item);
+                        continue;
+                    }
+                }
+                let (collected, close) = Macros::collect_arguments(definition, &item, &mut input)?;
+                arguments = collected;
+                hide_set.retain(|name| close.hide_set.contains(name));
+            }
+            hide_set.insert(item.token.get_token().spelling());
+            let replaced = self.substitute(definition, &arguments, &hide_set, &item.token)?;
+            for replaced_item in replaced.into_iter().rev() {
+                input.push_front(replaced_item);
+            }
+        }
+        Ok(output)
+    }
+
+    /// expand `__FILE__` or `__LINE__` (lines of tokens start with 0 and `__LINE__` with 1)
+    fn expand_builtin(&self, token: &ManagedToken) -> Option<ManagedToken> {
+        let value = match token.get_token() {
+            Token::Identifier(name) if name == "__FILE__" => {
+                Token::StringLiteral(self.file.clone())
+            }
+            Token::Identifier(name) if name == "__LINE__" => {
+                Token::Number(IntegerConstant::decimal(token.get_line() as u64 + 1))
+            }
+            _ => return None,
+        };
+        Some(replace_token(token, value))
+    }
+
+    /// collect the arguments of a function-like macro invocation
+    ///
+    /// returns the arguments and the closing parenthesis
+    fn collect_arguments(
+        definition: &Macro,
+        name: &Item,
+        input: &mut VecDeque<Item>,
+    ) -> ExpansionResult<(Vec<Vec<Item>>, Item)> {
+        let parameters = definition.parameters.as_ref().expect("function-like macro");
+        let error = |kind| (kind, name.token.get_line());
+        let macro_name = name.token.get_token().spelling();
+        input.pop_front(); // consume (
+        let mut arguments = vec![vec![]];
+        let mut depth = 0;
+        let close = loop {
+            let item = match input.pop_front() {
+                Some(item) => item,
+                None => {
+                    let kind = PreprocessErrorKind::UnterminatedInvocation(macro_name);
+                    return Err(error(kind));
+                }
+            };
+            match item.token.get_token() {
+                Token::Punct(Punct::LeftParen) => depth += 1,
+                Token::Punct(Punct::RightParen) => {
+                    if depth == 0 {
+                        break item;
+                    }
+                    depth -= 1;
+                }
+                // commas separate the variable arguments in __VA_ARGS__
+                Token::Punct(Punct::Comma)
+                    if depth == 0
+                        && !(definition.is_variadic && arguments.len() == parameters.len()) =>
+                {
+                    arguments.push(vec![]);
+                    continue;
+                }
+                _ => (),
+            }
+            arguments.last_mut().unwrap().push(item);
+        };
+        // `f()` has no arguments rather than an empty one, and variable arguments can be omitted
+        if parameters.is_empty() && arguments.len() == 1 && arguments[0].is_empty() {
+            arguments.clear();
+        }
+        if definition.is_variadic && arguments.len() + 1 == parameters.len() {
+            arguments.push(vec![]);
+        }
+        if arguments.len() != parameters.len() {
+            let kind = PreprocessErrorKind::MacroArguments(macro_name, arguments.len());
+            return Err(error(kind));
+        }
+        Ok((arguments, close))
+    }
+
+    /// replace a macro invocation with the replacement list of the macro
+    ///
+    /// parameters are replaced with the arguments, which are fully expanded unless they are
+    /// operands of `#` or `##`. the replacement is at the position of the invocation.
+    fn substitute(
+        &self,
+        definition: &Macro,
+        arguments: &[Vec<Item>],
+        hide_set: &HashSet<String>,
+        site: &ManagedToken,
+    ) -> ExpansionResult<Vec<Item>> {
+        let expansion = Rc::new(Expansion {
+            name: definition.name.get_token().spelling(),
+            definition: (definition.name.get_line(), definition.name.get_location()),
+            parent: site.get_expansion().cloned(),
+        });
+        let replace = |token: Token| {
+            Item::new(
+                ManagedToken::expanded(
+                    token,
+                    site.get_line(),
+                    site.get_location(),
+                    expansion.clone(),
+                )
+                .with_span(site.get_span()),
+            )
+        };
+        // an argument without tokens is a placemarker (None) as an operand of ##
+        let operand = |argument: &[Item]| -> Vec<Option<Item>> {
+            match argument {
+                [] => vec![None],
+                argument => argument.iter().cloned().map(Some).collect(),
+            }
+        };
+
+        let replacement = &definition.replacement;
+        let mut output: Vec<Option<Item>> = vec![];
+        let mut i = 0;
+        while i < replacement.len() {
+            let token = &replacement[i];
+            let is_pasted = matches!(replacement.get(i + 1), Some(next) if next.get_token() == &Token::Punct(Punct::HashHash));
+            match token.get_token() {
+                Token::Punct(Punct::Hash) if definition.parameters.is_some() => {
+                    let index = definition
+                        .parameter_index(&replacement[i + 1])
+                        .expect("# is followed by a parameter");
+                    let string = stringify(&arguments[index]);
+                    output.push(Some(replace(Token::StringLiteral(string))));
+                    i += 2;
+                }
+                Token::Punct(Punct::HashHash) => {
+                    let rhs = &replacement[i + 1];
+                    let mut rhs = match definition.parameter_index(rhs) {
+                        Some(index) => operand(&arguments[index]),
+                        None => vec![Some(replace(rhs.get_token().clone()))],
+                    };
+                    let lhs = output.pop().expect("## follows an operand");
+                    let pasted = match (lhs, rhs.remove(0)) {
+                        (None, rhs) => rhs,
+                        (lhs, None) => lhs,
+                        (Some(lhs), Some(rhs)) => {
+                            Some(paste(lhs, rhs).map_err(|kind| (kind, site.get_line()))?)
+                        }
+                    };
+                    output.push(pasted);
+                    output.append(&mut rhs);
+                    i += 2;
+                }
+                _ => {
+                    match definition.parameter_index(token) {
+                        Some(index) if is_pasted => output.append(&mut operand(&arguments[index])),
+                        Some(index) => {
+                            let expanded = self.expand_items(arguments[index].clone())?;
+                            output.extend(expanded.into_iter().map(Some));
+                        }
+                        None => output.push(Some(replace(token.get_token().clone()))),
+                    }
+                    i += 1;
+                }
+            }
+        }
+        Ok(output
+            .into_iter()
+            .flatten()
+            .map(|item| item.hide(hide_set))
+            .collect())
+    }
+}
+
+/// spell the tokens of an argument as a string literal (the operand of `#`)
+///
+/// white space between tokens becomes a space
+fn stringify(argument: &[Item]) -> String {
+    let mut string = "".to_owned();
+    for (i, item) in argument.iter().enumerate() {
+        if i > 0 && is_separated(&argument[i - 1].token, &item.token) {
+            string.push(' ');
+        }
+        string += &item.token.get_token().spelling();
+    }
+    string
+}
+
+/// concatenate two tokens into a token (the operands of `##`)
+fn paste(lhs: Item, rhs: Item) -> Result<Item, PreprocessErrorKind> {
+    let (lhs_spelling, rhs_spelling) = (
+        lhs.token.get_token().spelling(),
+        rhs.token.get_token().spelling(),
+    );
+    let mut tokens = match Tokenizer::tokenize(&format!("{}{}", lhs_spelling, rhs_spelling)) {
+        Ok(tokens) if tokens.len() == 1 => tokens,
+        _ => {
+            return Err(PreprocessErrorKind::InvalidPaste(
+                lhs_spelling,
+                rhs_spelling,
+            ))
+        }
+    };
+    let token = replace_token(&lhs.token, tokens.remove(0).get_token().clone());
+    Ok(Item {
+        token,
+        hide_set: lhs.hide_set,
+    }
+    .hide(&rhs.hide_set))
+}
+
+/// replace a token with another token at the same position from the same expansion
+fn replace_token(token: &ManagedToken, value: Token) -> ManagedToken {
+    let (line, location) = (token.get_line(), token.get_location());
+    let replaced = match token.get_expansion() {
+        Some(expansion) => ManagedToken::expanded(value, line, location, expansion.clone()),
+        None => ManagedToken::new(value, line, location),
+    };
+    replaced.with_span(token.get_span())
+}
+
+/// check whether there is white space between two tokens from their positions
+pub fn is_separated(token: &ManagedToken, next: &ManagedToken) -> bool {
+    let length = token.get_token().spelling().chars().count() as u32;
+    token.get_line() != next.get_line() || token.get_location() + length != next.get_location()
+}
+
+/// get the name of an identifier or a keyword, which are the same for the preprocessor (e.g.
+/// `#define inline`)
+pub fn name_of(token: &Token) -> Option<&str> {
+    match token {
+        Token::Identifier(identifier) => Some(identifier.as_str()),
+        Token::Keyword(keyword) => Some(keyword.spelling()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::error::PreprocessError;
+    use crate::Preprocessor;
+    use rcc_syntax::token::IntegerConstant;
+    use std::path::Path;
+
+    fn preprocess(code: &str) -> Result<Vec<ManagedToken>, PreprocessError> {
+        Preprocessor::new(vec![]).preprocess(code, Path::new("test.c"))
+    }
+
+    /// spell the preprocessed tokens separated by spaces
+    fn expand(code: &str) -> String {
+        let tokens = preprocess(code).unwrap();
+        let spellings: Vec<String> = tokens
+            .iter()
+            .map(|token| token.get_token().spelling())
+            .collect();
+        spellings.join(" ")
+    }
+
+    fn error(code: &str) -> PreprocessErrorKind {
+        preprocess(code).unwrap_err().kind
+    }
+
+    #[test]
+    fn object_like() {
+        let code = "#define N 10\n#define M (N + N)\nint a = M;\n#undef N\nint b = N;\n";
+        assert_eq!(expand(code), "int a = ( 10 + 10 ) ; int b = N ;");
+    }
+
+    #[test]
+    fn function_like() {
+        let code = r#"
+#define mul(a, b) ((a) * (b))
+#define f (1)
+#define g() 2
+int c = mul(f, g()) + mul((1, 2),
+    3);
+int mul = 0;
+"#;
+        let expect = "int c = ( ( ( 1 ) ) * ( 2 ) ) + ( ( ( 1 , 2 ) ) * ( 3 ) ) ; int mul = 0 ;";
+        assert_eq!(expand(code), expect);
+    }
+
+    #[test]
+    fn keywords_as_macro_names() {
+        let code = r#"
+#define inline
+#define unless(int) if (!(int))
+#ifdef inline
+static inline void f(void) { unless(x) return; }
+#endif
+#undef unless
+unless
+"#;
+        let expect = "static void f ( void ) { if ( ! ( x ) ) return ; } unless";
+        assert_eq!(expand(code), expect);
+    }
+
+    #[test]
+    fn stringify_and_paste() {
+        let code = r#"
+#define str(s) # s
+#define xstr(s) str(s)
+#define cat(a, b) a ## b
+#define N 4
+char *s = str(a  +"b\n") xstr(N);
+int cat(x, 1) = cat(, N) + cat(1, 2) cat(<, <) 1;
+"#;
+        let expect = r#"char * s = "a +\"b\\n\"" "4" ; int x1 = 4 + 12 << 1 ;"#;
+        assert_eq!(expand(code), expect);
+    }
+
+    #[test]
+    fn variadic() {
+        let code = r#"
+#define call(f, ...) f(__VA_ARGS__)
+#define show(...) #__VA_ARGS__
+call(g, 1, (2, 3)) call(h) show(a,b , c)
+"#;
+        assert_eq!(expand(code), r#"g ( 1 , ( 2 , 3 ) ) h ( ) "a,b , c""#);
+    }
+
+    #[test]
+    fn recursion_is_not_expanded() {
+        let code = r#"
+#define foo foo
+#define a b + 1
+#define b a * 2
+#define f(x) x f
+foo a b f(f)(1)
+"#;
+        assert_eq!(expand(code), "foo a * 2 + 1 b + 1 * 2 f f ( 1 )");
+    }
+
+    #[test]
+    fn expanded_tokens() {
+        let code = "#define ONE 1\n#define TWO ONE + ONE\nint a =\n  TWO;\n";
+        let tokens = preprocess(code).unwrap();
+        let one = &tokens[3];
+        assert_eq!(one.get_token(), &Token::Number(IntegerConstant::decimal(1)));
+        assert_eq!((one.get_line(), one.get_location()), (3, 2));
+        let expansion = one.get_expansion().unwrap();
+        assert_eq!(
+            expansion.to_string(),
+            "in expansion of macro ONE defined at line 0 location 8, \
+             in expansion of macro TWO defined at line 1 location 8"
+        );
+    }
+
+    #[test]
+    fn invalid_macros() {
+        let invalid = |cause: &str| PreprocessErrorKind::InvalidMacro(cause.to_owned());
+        assert_eq!(error("#define 1 2\n"), invalid("macro name 1"));
+        assert_eq!(error("#define f(a, a) a\n"), invalid("parameter a"));
+        assert_eq!(
+            error("#define f(a) #b\n"),
+            invalid("# is not followed by a parameter")
+        );
+        assert_eq!(
+            error("#define f ## a\n"),
+            invalid("## at either end of a replacement list")
+        );
+        // the amount of white space does not matter in redefinitions, but its presence does
+        assert_eq!(expand("#define A 1 + 2\n#define A 1  +  2\nA"), "1 + 2");
+        assert_eq!(
+            error("#define A 1 + 2\n#define A 1+2\n"),
+            PreprocessErrorKind::Redefinition("A".to_owned())
+        );
+        // # of an object-like macro is not stringification
+        assert_eq!(expand("#define h # x\nh"), "# x");
+    }
+
+    #[test]
+    fn invalid_invocations() {
+        let actual = preprocess("#define f(a, b) a\nint a;\nf(1)\n").unwrap_err();
+        assert_eq!(
+            actual.kind,
+            PreprocessErrorKind::MacroArguments("f".to_owned(), 1)
+        );
+        assert_eq!(actual.stack, vec![(Path::new("test.c").to_owned(), 3)]);
+        assert_eq!(
+            error("#define f(a) a\nf(1\n"),
+            PreprocessErrorKind::UnterminatedInvocation("f".to_owned())
+        );
+        assert_eq!(
+            error("#define cat(a, b) a ## b\ncat(+, /)\n"),
+            PreprocessErrorKind::InvalidPaste("+".to_owned(), "/".to_owned())
+        );
+    }
+}
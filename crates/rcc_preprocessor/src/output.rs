@@ -0,0 +1,171 @@
+use super::macros::is_separated;
+use rcc_syntax::token::{ManagedToken, Token};
+use rcc_syntax::Tokenizer;
+use std::path::{Path, PathBuf};
+
+/// lines which are written as newlines rather than a line marker
+const MAX_BLANK_LINES: u32 = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MarkerKind {
+    Start,  // the file being preprocessed
+    Enter,  // an included file (flag 1)
+    Return, // the including file after an include (flag 2)
+    Line,   // `#line`
+}
+
+/// change of the presumed file or line in the preprocessed tokens
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineMarker {
+    pub index: usize, // index of the first token after the marker
+    pub line: u32,    // presumed line of the next line (starting with 0)
+    pub file: PathBuf,
+    pub kind: MarkerKind,
+}
+
+/// write preprocessed tokens as text with GCC-style line markers (`# 12 "file.h" 1`), which is
+/// preprocessed again into the same tokens at the same lines
+///
+/// tokens are in their lines and columns, and a space separates tokens which are separated in the
+/// source or which would be tokenized differently without it
+pub fn write_preprocessed(tokens: &[ManagedToken], markers: &[LineMarker]) -> String {
+    let mut text = "".to_owned();
+    let mut file = PathBuf::new();
+    let mut line = 0;
+    let mut is_line_start = true;
+    let mut markers = markers.iter().peekable();
+    for (index, token) in tokens.iter().enumerate() {
+        while let Some(marker) = markers.next_if(|marker| marker.index <= index) {
+            file = marker.file.clone();
+            line = marker.line;
+            write_marker(&mut text, line, &file, marker.kind);
+            is_line_start = true;
+        }
+        if token.get_line() > line {
+            let lines = token.get_line() - line;
+            if lines <= MAX_BLANK_LINES {
+                text += &"\n".repeat(lines as usize);
+            } else {
+                write_marker(&mut text, token.get_line(), &file, MarkerKind::Line);
+            }
+            line = token.get_line();
+            is_line_start = true;
+        }
+        if is_line_start {
+            // the first token in a line is at its column
+            text += &" ".repeat(token.get_location() as usize);
+        } else if needs_space(&tokens[index - 1], token) {
+            text.push(' ');
+        }
+        text += &token.get_token().spelling();
+        is_line_start = false;
+    }
+    if !is_line_start {
+        text.push('\n');
+    }
+    text
+}
+
+/// write a line marker in a new line
+fn write_marker(text: &mut String, line: u32, file: &Path, kind: MarkerKind) {
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    let name = Token::StringLiteral(file.display().to_string()).spelling();
+    let flag = match kind {
+        MarkerKind::Enter => " 1",
+        MarkerKind::Return => " 2",
+        MarkerKind::Start | MarkerKind::Line => "",
+    };
+    // markers are followed by the line which they give
+    *text += &format!("# {} {}{}\n", line + 1, name, flag);
+}
+
+/// check whether two tokens need a space between them
+fn needs_space(token: &ManagedToken, next: &ManagedToken) -> bool {
+    if is_separated(token, next) {
+        return true;
+    }
+    let (spelling, next_spelling) = (token.get_token().spelling(), next.get_token().spelling());
+    // a comment would start
+    if spelling.ends_with('/') && (next_spelling.starts_with('/') || next_spelling.starts_with('*'))
+    {
+        return true;
+    }
+    // joined tokens which are invalid (e.g. `1` and `e`) are also separated
+    match Tokenizer::tokenize(&format!("{}{}", spelling, next_spelling)) {
+        Ok(joined) => {
+            let joined: Vec<&Token> = joined.iter().map(|token| token.get_token()).collect();
+            joined != vec![token.get_token(), next.get_token()]
+        }
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::Preprocessor;
+    use rcc_syntax::symbol::Symbol;
+    use rcc_syntax::token::Punct;
+    use std::fs;
+
+    #[test]
+    fn spaces_between_tokens() {
+        let token = |token: Token, location| ManagedToken::new(token, 0, location);
+        let operator = |operator: &str, location| {
+            token(
+                Token::Punct(Punct::from_spelling(operator).unwrap()),
+                location,
+            )
+        };
+        let identifier = |identifier: &str, location| {
+            token(Token::Identifier(Symbol::intern(identifier)), location)
+        };
+        assert!(needs_space(&operator("/", 0), &operator("/", 1)));
+        assert!(needs_space(&operator("/", 0), &operator("*", 1)));
+        assert!(needs_space(&operator("<", 0), &operator("<", 1)));
+        assert!(needs_space(&identifier("a", 0), &identifier("b", 1)));
+        assert!(needs_space(&identifier("a", 0), &operator("+", 2)));
+        assert!(!needs_space(&identifier("a", 0), &operator("+", 1)));
+        assert!(!needs_space(&operator("+", 0), &operator("-", 1)));
+    }
+
+    #[test]
+    fn preprocessed_code_is_preprocessed_again() {
+        let directory = std::env::temp_dir().join("rcc_preprocessor_output");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("a.h"), "#define LT <\nint a = 1 LT LT 2;\n").unwrap();
+        let main = format!(
+            "#include \"a.h\"\n#define SHIFT(a, b) a<<b\nint c = SHIFT(1, 2);\n{}int d;\n\
+             #line 40 \"other.c\"\nint e;\n",
+            "\n".repeat(10)
+        );
+        fs::write(directory.join("main.c"), &main).unwrap();
+
+        let mut preprocessor = Preprocessor::new(vec![]);
+        let tokens = preprocessor
+            .preprocess_file(&directory.join("main.c"))
+            .unwrap();
+        let text = write_preprocessed(&tokens, preprocessor.line_markers());
+        let (main, header) = (directory.join("main.c"), directory.join("a.h"));
+        let expect = format!(
+            "# 1 \"{main}\"\n# 1 \"{header}\" 1\n\nint a = 1 < < 2;\n# 2 \"{main}\" 2\n\n\
+             int c = 1 << 2 ;\n# 14 \"{main}\"\nint d;\n# 40 \"other.c\"\nint e;\n",
+            main = main.display(),
+            header = header.display()
+        );
+        assert_eq!(text, expect);
+
+        let lines = |tokens: &[ManagedToken]| -> Vec<(Token, u32)> {
+            tokens
+                .iter()
+                .map(|token| (token.get_token().clone(), token.get_line()))
+                .collect()
+        };
+        let reparsed = Preprocessor::new(vec![]).preprocess(&text, &main).unwrap();
+        assert_eq!(lines(&reparsed), lines(&tokens));
+    }
+}
@@ -0,0 +1,106 @@
+use std::env;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// macros defined before preprocessing as `-D` definitions
+///
+/// the target is x86_64 Linux (LP64), and `__FILE__` and `__LINE__` are expanded by `Macros`
+pub fn definitions() -> Vec<String> {
+    let (date, time) = date_time(timestamp());
+    let mut definitions = vec![
+        format!("__DATE__=\"{}\"", date),
+        format!("__TIME__=\"{}\"", time),
+    ];
+    let constants = [
+        ("__STDC__", "1"),
+        ("__STDC_VERSION__", "201112L"),
+        ("__STDC_HOSTED__", "1"),
+        ("__x86_64__", "1"),
+        ("__x86_64", "1"),
+        ("__amd64__", "1"),
+        ("__amd64", "1"),
+        ("__LP64__", "1"),
+        ("_LP64", "1"),
+        ("__linux__", "1"),
+        ("__linux", "1"),
+        ("__unix__", "1"),
+        ("__unix", "1"),
+        ("__CHAR_BIT__", "8"),
+        ("__SIZEOF_SHORT__", "2"),
+        ("__SIZEOF_INT__", "4"),
+        ("__SIZEOF_LONG__", "8"),
+        ("__SIZEOF_LONG_LONG__", "8"),
+        ("__SIZEOF_POINTER__", "8"),
+        ("__SIZEOF_FLOAT__", "4"),
+        ("__SIZEOF_DOUBLE__", "8"),
+    ];
+    definitions.extend(
+        constants
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value)),
+    );
+    definitions
+}
+
+/// seconds since the Unix epoch, which is fixed by `SOURCE_DATE_EPOCH` for reproducible builds
+fn timestamp() -> u64 {
+    match env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse().ok())
+    {
+        Some(epoch) => epoch,
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0),
+    }
+}
+
+/// format a timestamp (UTC) as `__DATE__` ("Mmm dd yyyy") and `__TIME__` ("hh:mm:ss")
+fn date_time(timestamp: u64) -> (String, String) {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let (days, seconds) = (timestamp / 86400, timestamp % 86400);
+    let time = format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+
+    // civil date from days since 1970-01-01 in eras of 400 years from 0000-03-01
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = (month_from_march + 2) % 12;
+    let year = year_of_era + era * 400 + if month < 2 { 1 } else { 0 };
+    let date = format!("{} {:2} {}", MONTHS[month as usize], day, year);
+    (date, time)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn dates_and_times() {
+        assert_eq!(
+            date_time(0),
+            ("Jan  1 1970".to_owned(), "00:00:00".to_owned())
+        );
+        assert_eq!(
+            date_time(951_782_400 + 3_723),
+            ("Feb 29 2000".to_owned(), "01:02:03".to_owned())
+        );
+        assert_eq!(
+            date_time(1_798_761_599),
+            ("Dec 31 2026".to_owned(), "23:59:59".to_owned())
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use super::span::Span;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LexErrorKind {
+    UnexpectedChar(char),
+    UnterminatedComment,
+    UnterminatedString,
+    UnterminatedCharacter,
+    InvalidCharacter(String), // empty or of more than one char
+    InvalidEscape(String),
+    InvalidFloating(String),
+    FloatingOverflow(String),
+}
+
+impl fmt::Display for LexErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LexErrorKind::UnexpectedChar(c) => write!(f, "unexpected char {:?}", c),
+            LexErrorKind::UnterminatedComment => write!(f, "unterminated comment"),
+            LexErrorKind::UnterminatedString => write!(f, "unterminated string literal"),
+            LexErrorKind::UnterminatedCharacter => write!(f, "unterminated character constant"),
+            LexErrorKind::InvalidCharacter(chars) => {
+                write!(f, "invalid character constant '{}'", chars)
+            }
+            LexErrorKind::InvalidEscape(escape) => write!(f, "invalid escape sequence {}", escape),
+            LexErrorKind::InvalidFloating(number) => {
+                write!(f, "invalid floating constant {}", number)
+            }
+            LexErrorKind::FloatingOverflow(number) => {
+                write!(f, "floating constant {} is too large", number)
+            }
+        }
+    }
+}
+
+/// lexical error at the position where the invalid chars start
+#[derive(Clone, Debug, PartialEq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub line: u32,
+    pub location: u32,
+    pub span: Span, // the invalid chars
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {} location {}",
+            self.kind, self.line, self.location
+        )
+    }
+}
+
+impl Error for LexError {
+    fn description(&self) -> &str {
+        match self.kind {
+            LexErrorKind::UnexpectedChar(_) => "lex error: unexpected char",
+            LexErrorKind::UnterminatedComment => "lex error: unterminated comment",
+            LexErrorKind::UnterminatedString => "lex error: unterminated string literal",
+            LexErrorKind::UnterminatedCharacter => "lex error: unterminated character constant",
+            LexErrorKind::InvalidCharacter(_) => "lex error: invalid character constant",
+            LexErrorKind::InvalidEscape(_) => "lex error: invalid escape sequence",
+            LexErrorKind::InvalidFloating(_) => "lex error: invalid floating constant",
+            LexErrorKind::FloatingOverflow(_) => "lex error: floating constant overflow",
+        }
+    }
+}
@@ -1,85 +1,376 @@
+pub mod error;
+mod source;
+pub mod span;
+pub mod symbol;
 pub mod token;
 pub mod tokens;
 
-use self::token::{ManagedToken, Token};
+use self::error::{LexError, LexErrorKind};
+use self::source::SourceChars;
+use self::span::{FileId, Span};
+use self::symbol::Symbol;
+use self::token::{IntegerConstant, Keyword, ManagedToken, Punct, Token};
 use std::iter::Peekable;
-use std::str::Chars;
 
+/// tokenizer of source code
+///
+/// the cursor is the physical position of the next char, so tokens keep their positions in the
+/// source even when lines are spliced
 pub struct Tokenizer<'a> {
-    chars: Peekable<Chars<'a>>,
+    chars: Peekable<SourceChars<'a>>,
     cursor_line: u32,
     cursor_location: u32,
+    cursor_offset: u32,
+    end_offset: u32, // offset after the last consumed char
 }
 impl<'a> Tokenizer<'a> {
     pub fn new(code: &'a String) -> Tokenizer {
-        Tokenizer {
-            chars: code.chars().peekable(),
+        let mut tokenizer = Tokenizer {
+            chars: SourceChars::new(code).peekable(),
             cursor_line: 0,
             cursor_location: 0,
-        }
+            cursor_offset: 0,
+            end_offset: 0,
+        };
+        tokenizer.move_cursor();
+        tokenizer
     }
 
-    pub fn tokenize(code: &'a String) -> Vec<ManagedToken> {
+    /// tokenize code into tokens whose spans are byte offsets in the code (in the default file)
+    ///
+    /// tokenizing continues after an invalid char or constant, so that all the lexical errors in
+    /// the code are returned
+    pub fn tokenize(code: &'a String) -> Result<Vec<ManagedToken>, Vec<LexError>> {
         let mut tokenizer = Tokenizer::new(code);
 
         let mut tokens: Vec<ManagedToken> = Vec::new();
-        while let Some(c) = tokenizer.peek() {
-            if c.is_ascii_digit() {
-                tokens.push(tokenizer.consume_number());
-            } else if c == &'+' || c == &'*' || c == &'=' {
-                tokens.push(tokenizer.consume_operator());
-            } else if c == &'{' || c == &'}' {
-                tokens.push(tokenizer.consume_bracket());
-            } else if c == &'[' || c == &']' {
-                tokens.push(tokenizer.consume_sbracket());
-            } else if c == &'(' || c == &')' {
-                tokens.push(tokenizer.consume_parenthesis());
-            } else if c == &' ' || c == &'\n' {
+        let mut errors: Vec<LexError> = Vec::new();
+        while let Some(&c) = tokenizer.peek() {
+            let (line, location) = (tokenizer.cursor_line, tokenizer.cursor_location);
+            let start = tokenizer.cursor_offset;
+            let token = if c.is_ascii_digit() || (c == '.' && tokenizer.is_next_fraction()) {
+                tokenizer.consume_number()
+            } else if c == '"' {
+                tokenizer.consume_string_literal()
+            } else if c == '\'' {
+                tokenizer.consume_character_constant()
+            } else if c == '/' && tokenizer.is_next_comment() {
+                match tokenizer.skip_comment() {
+                    Ok(()) => continue,
+                    Err(kind) => Err(kind),
+                }
+            } else if is_whitespace(c) {
                 tokenizer.next();
-            } else if c == &';' {
-                tokens.push(tokenizer.consume_semicolon());
-            } else if c == &',' {
-                tokens.push(tokenizer.consume_comma());
-            } else if c.is_ascii_alphabetic() {
-                tokens.push(tokenizer.consume_identifier());
+                continue;
+            } else if c.is_ascii_alphabetic() || c == '_' {
+                Ok(tokenizer.consume_identifier())
+            } else if let Some(token) = tokenizer.consume_punct() {
+                Ok(token)
             } else {
-                panic!("unexpected char {:?}", c);
+                tokenizer.next();
+                Err(LexErrorKind::UnexpectedChar(c))
+            };
+            let span = Span::new(FileId::default(), start, tokenizer.end_offset);
+            match token {
+                Ok(token) => tokens.push(token.with_span(span)),
+                Err(kind) => errors.push(LexError {
+                    kind,
+                    line,
+                    location,
+                    span,
+                }),
             }
         }
-        tokens
+        if errors.is_empty() {
+            Ok(tokens)
+        } else {
+            Err(errors)
+        }
     }
 
     fn peek(&mut self) -> Option<&char> {
-        self.chars.peek()
+        self.chars.peek().map(|(c, _, _, _)| c)
     }
 
-    fn next(&mut self) -> Option<char> {
-        if let Some(c) = self.peek() {
-            if c == &'\n' {
-                self.cursor_line += 1;
-                self.cursor_location = 0;
-            } else {
-                self.cursor_location += 1;
+    /// get the next chars without consuming them
+    fn lookahead(&self) -> impl Iterator<Item = char> + 'a {
+        self.chars.clone().map(|(c, _, _, _)| c)
+    }
+
+    /// check whether the next chars are a fraction without integer part (e.g. `.5`)
+    fn is_next_fraction(&self) -> bool {
+        let mut chars = self.lookahead();
+        chars.next() == Some('.') && matches!(chars.next(), Some(c) if c.is_ascii_digit())
+    }
+
+    /// check whether the next chars start a comment (`//` or `/*`)
+    fn is_next_comment(&self) -> bool {
+        let mut chars = self.lookahead();
+        chars.next() == Some('/') && matches!(chars.next(), Some('/') | Some('*'))
+    }
+
+    /// skip a line comment to the end of the line or a block comment to `*/`
+    fn skip_comment(&mut self) -> Result<(), LexErrorKind> {
+        self.next(); // consume /
+        if self.next() == Some('/') {
+            while let Some(&c) = self.peek() {
+                if c == '\n' {
+                    break;
+                }
+                self.next();
+            }
+            return Ok(());
+        }
+        let mut previous = None;
+        while let Some(c) = self.next() {
+            if previous == Some('*') && c == '/' {
+                return Ok(());
             }
+            previous = Some(c);
         }
-        self.chars.next()
+        Err(LexErrorKind::UnterminatedComment)
     }
 
-    pub fn consume_number(&mut self) -> ManagedToken {
+    /// consume a string literal and get its chars after escape sequences are replaced
+    ///
+    /// an unterminated string literal is consumed to the end of the line
+    fn consume_string_literal(&mut self) -> Result<ManagedToken, LexErrorKind> {
         let line = self.cursor_line;
         let location = self.cursor_location;
+        let s = self.consume_quoted('"', LexErrorKind::UnterminatedString)?;
+        Ok(ManagedToken::new(Token::StringLiteral(s), line, location))
+    }
 
+    /// consume a character constant of a char or an escape sequence (e.g. `'a'`, `'\n'`, `'\x41'`)
+    fn consume_character_constant(&mut self) -> Result<ManagedToken, LexErrorKind> {
+        let line = self.cursor_line;
+        let location = self.cursor_location;
+        let s = self.consume_quoted('\'', LexErrorKind::UnterminatedCharacter)?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(ManagedToken::new(Token::Character(c), line, location)),
+            _ => Err(LexErrorKind::InvalidCharacter(s)),
+        }
+    }
+
+    /// consume chars in quotes and replace escape sequences
+    ///
+    /// an octal escape sequence has up to three octal digits and a hexadecimal one has any number of
+    /// hexadecimal digits after `\x`. the chars are consumed to the closing quote even if an escape
+    /// sequence is invalid (no hexadecimal digits or a value which does not fit in a char).
+    fn consume_quoted(
+        &mut self,
+        quote: char,
+        unterminated: LexErrorKind,
+    ) -> Result<String, LexErrorKind> {
+        // quoted chars end in the line where they start
+        let next_char = |tokenizer: &mut Tokenizer| match tokenizer.next() {
+            Some(c) if c != '\n' => Ok(c),
+            _ => Err(unterminated.clone()),
+        };
+
+        self.next(); // consume the opening quote
+        let mut s = String::new();
+        let mut invalid_escape = None;
+        loop {
+            let c = match next_char(self)? {
+                c if c == quote => break,
+                '\\' => match next_char(self)? {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    'v' => '\x0b',
+                    'f' => '\x0c',
+                    'a' => '\x07',
+                    'b' => '\x08',
+                    c @ '0'..='7' => {
+                        let mut digits = c.to_string();
+                        while digits.len() < 3 {
+                            match self.peek() {
+                                Some(&c) if c.is_digit(8) => digits.push(c),
+                                _ => break,
+                            }
+                            self.next();
+                        }
+                        match escaped_char(&digits, 8) {
+                            Some(c) => c,
+                            None => {
+                                invalid_escape.get_or_insert(format!("\\{}", digits));
+                                continue;
+                            }
+                        }
+                    }
+                    'x' => {
+                        let mut digits = String::new();
+                        while let Some(&c) = self.peek() {
+                            if !c.is_ascii_hexdigit() {
+                                break;
+                            }
+                            digits.push(c);
+                            self.next();
+                        }
+                        match escaped_char(&digits, 16) {
+                            Some(c) => c,
+                            None => {
+                                invalid_escape.get_or_insert(format!("\\x{}", digits));
+                                continue;
+                            }
+                        }
+                    }
+                    // \\, \', \" and \?
+                    c => c,
+                },
+                c => c,
+            };
+            s.push(c);
+        }
+        match invalid_escape {
+            Some(escape) => Err(LexErrorKind::InvalidEscape(escape)),
+            None => Ok(s),
+        }
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let (c, _, _, offset) = self.chars.next()?;
+        self.end_offset = offset + c.len_utf8() as u32;
+        self.move_cursor();
+        Some(c)
+    }
+
+    /// move the cursor to the position of the next char
+    fn move_cursor(&mut self) {
+        if let Some(&(_, line, location, offset)) = self.chars.peek() {
+            self.cursor_line = line;
+            self.cursor_location = location;
+            self.cursor_offset = offset;
+        }
+    }
+
+    /// consume an integer or a floating constant
+    ///
+    /// integer constants are decimal, octal with a leading `0`, hexadecimal with `0x` or binary with `0b`
+    /// (e.g. `10`, `017`, `0x1F`, `0b101`) and can have the suffixes `u`, `l` and `ll` in either order
+    ///
+    /// floating constants are decimal with a fraction part and/or an exponent part (e.g. `1.5`, `2e10`)
+    /// and the suffix `f` makes them float instead of double
+    pub fn consume_number(&mut self) -> Result<ManagedToken, LexErrorKind> {
+        let line = self.cursor_line;
+        let location = self.cursor_location;
+
+        if let Some(radix) = self.consume_radix_prefix() {
+            let digits = self.consume_digits_of(radix);
+            let constant = self.consume_integer_suffix(digits, radix);
+            return Ok(ManagedToken::new(Token::Number(constant), line, location));
+        }
+        let mut s = self.consume_digits();
+        let mut is_floating = false;
+        if self.peek() == Some(&'.') {
+            is_floating = true;
+            s.push(self.next().unwrap());
+            s += &self.consume_digits();
+        }
+        if let Some('e') | Some('E') = self.peek() {
+            is_floating = true;
+            s.push(self.next().unwrap());
+            if let Some('+') | Some('-') = self.peek() {
+                s.push(self.next().unwrap());
+            }
+            s += &self.consume_digits();
+        }
+        if !is_floating {
+            // invalid digits of an octal constant (e.g. `09`) are reported by the parser
+            let radix = if s.len() > 1 && s.starts_with('0') {
+                8
+            } else {
+                10
+            };
+            let constant = self.consume_integer_suffix(s, radix);
+            return Ok(ManagedToken::new(Token::Number(constant), line, location));
+        }
+        // an exponent part without digits (e.g. `1e+`)
+        let num: f64 = s
+            .parse()
+            .map_err(|_| LexErrorKind::InvalidFloating(s.clone()))?;
+        let is_float = matches!(self.peek(), Some('f') | Some('F'));
+        if is_float {
+            self.next();
+        }
+        if num.is_infinite() || (is_float && (num as f32).is_infinite()) {
+            return Err(LexErrorKind::FloatingOverflow(s));
+        }
+        if is_float {
+            Ok(ManagedToken::new(Token::Float(num), line, location))
+        } else {
+            Ok(ManagedToken::new(Token::Double(num), line, location))
+        }
+    }
+    /// consume `0x` or `0b` followed by a digit of the radix
+    fn consume_radix_prefix(&mut self) -> Option<u32> {
+        let mut chars = self.lookahead();
+        if chars.next() != Some('0') {
+            return None;
+        }
+        let radix = match chars.next() {
+            Some('x') | Some('X') => 16,
+            Some('b') | Some('B') => 2,
+            _ => return None,
+        };
+        if !matches!(chars.next(), Some(c) if c.is_digit(radix)) {
+            return None;
+        }
+        self.next(); // consume 0
+        self.next(); // consume x or b
+        Some(radix)
+    }
+    /// consume the suffix of an integer constant
+    fn consume_integer_suffix(&mut self, digits: String, radix: u32) -> IntegerConstant {
+        let mut constant = IntegerConstant {
+            digits,
+            radix,
+            is_unsigned: false,
+            long: 0,
+        };
+        constant.is_unsigned = self.consume_unsigned_suffix();
+        constant.long = self.consume_long_suffix();
+        if !constant.is_unsigned && constant.long > 0 {
+            constant.is_unsigned = self.consume_unsigned_suffix();
+        }
+        constant
+    }
+    fn consume_unsigned_suffix(&mut self) -> bool {
+        if let Some('u') | Some('U') = self.peek() {
+            self.next();
+            return true;
+        }
+        false
+    }
+    /// consume `l` or `ll` (`lL` is not a suffix)
+    fn consume_long_suffix(&mut self) -> u8 {
+        let l = match self.peek() {
+            Some(&c) if c == 'l' || c == 'L' => c,
+            _ => return 0,
+        };
+        self.next();
+        if self.peek() == Some(&l) {
+            self.next();
+            return 2;
+        }
+        1
+    }
+    fn consume_digits(&mut self) -> String {
+        self.consume_digits_of(10)
+    }
+    fn consume_digits_of(&mut self, radix: u32) -> String {
         let mut s = String::from("");
         while let Some(c) = self.peek() {
-            if c.is_ascii_digit() {
+            if c.is_digit(radix) {
                 s += &c.to_string();
             } else {
                 break;
             }
             self.next();
         }
-        let num: u64 = s.parse().unwrap_or_else(|err| panic!(err));
-        ManagedToken::new(Token::Number(num), line, location)
+        s
     }
     fn consume_identifier(&mut self) -> ManagedToken {
         let line = self.cursor_line;
@@ -87,62 +378,48 @@ impl<'a> Tokenizer<'a> {
 
         let mut s = String::from("");
         while let Some(c) = self.peek() {
-            if c.is_alphabetic() || c.is_ascii_digit() {
+            if c.is_alphabetic() || c.is_ascii_digit() || c == &'_' {
                 s += &c.to_string();
             } else {
                 break;
             }
             self.next();
         }
-        // treat as reserved keyword
-        if s == "return" {
-            ManagedToken::new(Token::Return, line, location)
-        } else if s == "int" {
-            ManagedToken::new(Token::Type(s), line, location)
-        } else {
-            ManagedToken::new(Token::Identifier(s), line, location)
+        match Keyword::from_spelling(&s) {
+            Some(keyword) => ManagedToken::new(Token::Keyword(keyword), line, location),
+            None => ManagedToken::new(Token::Identifier(Symbol::intern(&s)), line, location),
         }
     }
-    fn consume_operator(&mut self) -> ManagedToken {
+    /// consume the longest punctuator which the next chars start with (e.g. `a+++b` is
+    /// `a ++ + b`)
+    fn consume_punct(&mut self) -> Option<ManagedToken> {
         let line = self.cursor_line;
         let location = self.cursor_location;
 
-        let c = self.next().unwrap();
-        ManagedToken::new(Token::Operator(c.to_string()), line, location)
-    }
-    fn consume_bracket(&mut self) -> ManagedToken {
-        let line = self.cursor_line;
-        let location = self.cursor_location;
-
-        let c = self.next().unwrap();
-        ManagedToken::new(Token::Bracket(c.to_string()), line, location)
+        // the longest punctuator is `%:%:`
+        let chars: Vec<char> = self.lookahead().take(4).collect();
+        let (punct, length) = (1..=chars.len()).rev().find_map(|length| {
+            let s: String = chars[..length].iter().collect();
+            Punct::from_spelling(&s).map(|punct| (punct, length))
+        })?;
+        for _ in 0..length {
+            self.next();
+        }
+        Some(ManagedToken::new(Token::Punct(punct), line, location))
     }
-    fn consume_sbracket(&mut self) -> ManagedToken {
-        let line = self.cursor_line;
-        let location = self.cursor_location;
+}
 
-        let c = self.next().unwrap();
-        ManagedToken::new(Token::SBracket(c.to_string()), line, location)
+/// white-space chars of C (space, horizontal tab, newline, vertical tab, form feed and carriage return)
+/// get the char of the digits of an octal or a hexadecimal escape sequence if it fits in a char
+fn escaped_char(digits: &str, radix: u32) -> Option<char> {
+    match u8::from_str_radix(digits, radix) {
+        Ok(byte) => Some(char::from(byte)),
+        Err(_) => None,
     }
-    fn consume_parenthesis(&mut self) -> ManagedToken {
-        let line = self.cursor_line;
-        let location = self.cursor_location;
+}
 
-        let c = self.next().unwrap();
-        ManagedToken::new(Token::Parenthesis(c.to_string()), line, location)
-    }
-    fn consume_semicolon(&mut self) -> ManagedToken {
-        let line = self.cursor_line;
-        let location = self.cursor_location;
-        self.next();
-        ManagedToken::new(Token::Semicolon, line, location)
-    }
-    fn consume_comma(&mut self) -> ManagedToken {
-        let line = self.cursor_line;
-        let location = self.cursor_location;
-        self.next();
-        ManagedToken::new(Token::Comma, line, location)
-    }
+fn is_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\x0b' | '\x0c' | '\r')
 }
 
 #[cfg(test)]
@@ -150,6 +427,14 @@ mod tests {
 
     use super::*;
 
+    fn number(value: u64) -> Token {
+        Token::Number(IntegerConstant::decimal(value))
+    }
+
+    fn span(start: u32, end: u32) -> Span {
+        Span::new(FileId::default(), start, end)
+    }
+
     #[cfg(test)]
     mod consumer {
 
@@ -157,23 +442,54 @@ mod tests {
 
         fn run(code: String) -> Token {
             let mut tokenizer = Tokenizer::new(&code);
-            From::from(tokenizer.consume_number())
+            From::from(tokenizer.consume_number().unwrap())
         }
 
         #[test]
         fn sigle_number() {
-            assert_eq!(run("10".to_string()), Token::Number(10));
+            assert_eq!(run("10".to_string()), number(10));
         }
 
         #[test]
         fn single_number_with_a_separated() {
-            assert_eq!(run("77a99".to_string()), Token::Number(77));
+            assert_eq!(run("77a99".to_string()), number(77));
+        }
+
+        #[test]
+        fn integer_radixes_and_suffixes() {
+            let constant = |digits: &str, radix, is_unsigned, long| {
+                Token::Number(IntegerConstant {
+                    digits: digits.to_owned(),
+                    radix,
+                    is_unsigned,
+                    long,
+                })
+            };
+            assert_eq!(run("0x1F".to_string()), constant("1F", 16, false, 0));
+            assert_eq!(run("017".to_string()), constant("017", 8, false, 0));
+            assert_eq!(run("0b101".to_string()), constant("101", 2, false, 0));
+            assert_eq!(run("10u".to_string()), constant("10", 10, true, 0));
+            assert_eq!(run("10LLU".to_string()), constant("10", 10, true, 2));
+            assert_eq!(run("0XffuL".to_string()), constant("ff", 16, true, 1));
+            // lL is not a suffix
+            assert_eq!(run("1lL".to_string()), constant("1", 10, false, 1));
+            assert_eq!(run("0".to_string()), number(0));
+        }
+
+        #[test]
+        fn floating_numbers() {
+            assert_eq!(run("1.5".to_string()), Token::Double(1.5));
+            assert_eq!(run("2e10".to_string()), Token::Double(2e10));
+            assert_eq!(run("3.0f".to_string()), Token::Float(3.0));
+            assert_eq!(run("1.25E-2F".to_string()), Token::Float(0.0125));
+            assert_eq!(run("4.".to_string()), Token::Double(4.0));
         }
     }
 
     // return Vec<Token> (not Vec<ManagedToken> for test readability)
     fn get_only_tokenized_tokens(code: &str) -> Vec<Token> {
         Tokenizer::tokenize(&code.to_string())
+            .unwrap()
             .into_iter()
             .map(|mtoken| From::from(mtoken))
             .collect::<Vec<Token>>()
@@ -181,18 +497,14 @@ mod tests {
 
     #[test]
     fn sigle_number() {
-        assert_eq!(get_only_tokenized_tokens("10"), vec![Token::Number(10),])
+        assert_eq!(get_only_tokenized_tokens("10"), vec![number(10),])
     }
 
     #[test]
     fn binary_add() {
         assert_eq!(
             get_only_tokenized_tokens("10+20"),
-            vec![
-                Token::Number(10),
-                Token::Operator("+".to_string()),
-                Token::Number(20),
-            ]
+            vec![number(10), Token::Punct(Punct::Plus), number(20),]
         )
     }
 
@@ -200,11 +512,7 @@ mod tests {
     fn binary_mul() {
         assert_eq!(
             get_only_tokenized_tokens("10*20"),
-            vec![
-                Token::Number(10),
-                Token::Operator("*".to_string()),
-                Token::Number(20),
-            ]
+            vec![number(10), Token::Punct(Punct::Star), number(20),]
         )
     }
 
@@ -213,11 +521,11 @@ mod tests {
         assert_eq!(
             get_only_tokenized_tokens("1 + 2 * 3"),
             vec![
-                Token::Number(1),
-                Token::Operator("+".to_string()),
-                Token::Number(2),
-                Token::Operator("*".to_string()),
-                Token::Number(3)
+                number(1),
+                Token::Punct(Punct::Plus),
+                number(2),
+                Token::Punct(Punct::Star),
+                number(3)
             ]
         );
     }
@@ -227,11 +535,11 @@ mod tests {
         assert_eq!(
             get_only_tokenized_tokens("1\n+\n2\n*\n3"),
             vec![
-                Token::Number(1),
-                Token::Operator("+".to_string()),
-                Token::Number(2),
-                Token::Operator("*".to_string()),
-                Token::Number(3)
+                number(1),
+                Token::Punct(Punct::Plus),
+                number(2),
+                Token::Punct(Punct::Star),
+                number(3)
             ]
         );
     }
@@ -241,20 +549,485 @@ mod tests {
         // should panic...
         assert_eq!(
             get_only_tokenized_tokens("1a0"),
-            vec![Token::Number(1), Token::Identifier("a0".to_string()),]
+            vec![number(1), Token::Identifier(Symbol::intern("a0")),]
+        );
+    }
+
+    #[test]
+    fn enum_keyword() {
+        assert_eq!(
+            get_only_tokenized_tokens("enum color"),
+            vec![
+                Token::Keyword(Keyword::Enum),
+                Token::Identifier(Symbol::intern("color"))
+            ]
+        );
+    }
+
+    #[test]
+    fn typedef_keyword() {
+        assert_eq!(
+            get_only_tokenized_tokens("typedef int foo;"),
+            vec![
+                Token::Keyword(Keyword::Typedef),
+                Token::Keyword(Keyword::Int),
+                Token::Identifier(Symbol::intern("foo")),
+                Token::Punct(Punct::Semicolon),
+            ]
+        );
+    }
+
+    #[test]
+    fn storage_class_keywords() {
+        assert_eq!(
+            get_only_tokenized_tokens("static inline extern"),
+            vec![
+                Token::Keyword(Keyword::Static),
+                Token::Keyword(Keyword::Inline),
+                Token::Keyword(Keyword::Extern)
+            ]
+        );
+    }
+
+    #[test]
+    fn type_qualifier_keywords() {
+        assert_eq!(
+            get_only_tokenized_tokens("const volatile constant"),
+            vec![
+                Token::Keyword(Keyword::Const),
+                Token::Keyword(Keyword::Volatile),
+                Token::Identifier(Symbol::intern("constant"))
+            ]
+        );
+    }
+
+    #[test]
+    fn integer_type_keywords() {
+        assert_eq!(
+            get_only_tokenized_tokens("unsigned long long _Bool _b1"),
+            vec![
+                Token::Keyword(Keyword::Unsigned),
+                Token::Keyword(Keyword::Long),
+                Token::Keyword(Keyword::Long),
+                Token::Keyword(Keyword::Bool),
+                Token::Identifier(Symbol::intern("_b1")),
+            ]
+        );
+    }
+
+    #[test]
+    fn arithmetic_operators() {
+        assert_eq!(
+            get_only_tokenized_tokens("a-b/c%d<<e>>f"),
+            vec![
+                Token::Identifier(Symbol::intern("a")),
+                Token::Punct(Punct::Minus),
+                Token::Identifier(Symbol::intern("b")),
+                Token::Punct(Punct::Slash),
+                Token::Identifier(Symbol::intern("c")),
+                Token::Punct(Punct::Percent),
+                Token::Identifier(Symbol::intern("d")),
+                Token::Punct(Punct::LeftShift),
+                Token::Identifier(Symbol::intern("e")),
+                Token::Punct(Punct::RightShift),
+                Token::Identifier(Symbol::intern("f")),
+            ]
         );
     }
 
+    #[test]
+    fn logical_and_relational_operators() {
+        let operator = |operator: &str| Token::Punct(Punct::from_spelling(operator).unwrap());
+        assert_eq!(
+            get_only_tokenized_tokens("!a||~b&&c|d^e==f!=g<=h>=i<j?k:l"),
+            vec![
+                operator("!"),
+                Token::Identifier(Symbol::intern("a")),
+                operator("||"),
+                operator("~"),
+                Token::Identifier(Symbol::intern("b")),
+                operator("&&"),
+                Token::Identifier(Symbol::intern("c")),
+                operator("|"),
+                Token::Identifier(Symbol::intern("d")),
+                operator("^"),
+                Token::Identifier(Symbol::intern("e")),
+                operator("=="),
+                Token::Identifier(Symbol::intern("f")),
+                operator("!="),
+                Token::Identifier(Symbol::intern("g")),
+                operator("<="),
+                Token::Identifier(Symbol::intern("h")),
+                operator(">="),
+                Token::Identifier(Symbol::intern("i")),
+                operator("<"),
+                Token::Identifier(Symbol::intern("j")),
+                operator("?"),
+                Token::Identifier(Symbol::intern("k")),
+                Token::Punct(Punct::Colon),
+                Token::Identifier(Symbol::intern("l")),
+            ]
+        );
+    }
+
+    #[test]
+    fn address_operator() {
+        assert_eq!(
+            get_only_tokenized_tokens("(*op)(&f)"),
+            vec![
+                Token::Punct(Punct::LeftParen),
+                Token::Punct(Punct::Star),
+                Token::Identifier(Symbol::intern("op")),
+                Token::Punct(Punct::RightParen),
+                Token::Punct(Punct::LeftParen),
+                Token::Punct(Punct::Ampersand),
+                Token::Identifier(Symbol::intern("f")),
+                Token::Punct(Punct::RightParen),
+            ]
+        );
+    }
+
+    #[test]
+    fn floating_types() {
+        assert_eq!(
+            get_only_tokenized_tokens("double d = .5 * 2.f;"),
+            vec![
+                Token::Keyword(Keyword::Double),
+                Token::Identifier(Symbol::intern("d")),
+                Token::Punct(Punct::Assign),
+                Token::Double(0.5),
+                Token::Punct(Punct::Star),
+                Token::Float(2.0),
+                Token::Punct(Punct::Semicolon),
+            ]
+        );
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(
+            get_only_tokenized_tokens("1 // 2 */\n/* 3 /* // */ 4 /**/ / 5"),
+            vec![number(1), number(4), Token::Punct(Punct::Slash), number(5)]
+        );
+    }
+
+    #[test]
+    fn location_after_comments() {
+        let actual = Tokenizer::tokenize(&"/* a\n * b */ 10 // c\n20".to_string()).unwrap();
+        let expect = vec![
+            ManagedToken::new(number(10), 1, 8).with_span(span(13, 15)),
+            ManagedToken::new(number(20), 2, 0).with_span(span(21, 23)),
+        ];
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn unterminated_comment() {
+        let errors = Tokenizer::tokenize(&"1\n2 /* 3 *".to_string()).unwrap_err();
+        let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        assert_eq!(messages, vec!["unterminated comment at line 1 location 2"]);
+    }
+
+    #[test]
+    fn struct_member_access() {
+        assert_eq!(
+            get_only_tokenized_tokens("struct flags { unsigned mode : 3; }; f.mode"),
+            vec![
+                Token::Keyword(Keyword::Struct),
+                Token::Identifier(Symbol::intern("flags")),
+                Token::Punct(Punct::LeftBrace),
+                Token::Keyword(Keyword::Unsigned),
+                Token::Identifier(Symbol::intern("mode")),
+                Token::Punct(Punct::Colon),
+                number(3),
+                Token::Punct(Punct::Semicolon),
+                Token::Punct(Punct::RightBrace),
+                Token::Punct(Punct::Semicolon),
+                Token::Identifier(Symbol::intern("f")),
+                Token::Punct(Punct::Dot),
+                Token::Identifier(Symbol::intern("mode")),
+            ]
+        );
+    }
+
+    #[test]
+    fn macro_operators() {
+        assert_eq!(
+            get_only_tokenized_tokens("#define CAT(a, b) # a ## b"),
+            vec![
+                Token::Punct(Punct::Hash),
+                Token::Identifier(Symbol::intern("define")),
+                Token::Identifier(Symbol::intern("CAT")),
+                Token::Punct(Punct::LeftParen),
+                Token::Identifier(Symbol::intern("a")),
+                Token::Punct(Punct::Comma),
+                Token::Identifier(Symbol::intern("b")),
+                Token::Punct(Punct::RightParen),
+                Token::Punct(Punct::Hash),
+                Token::Identifier(Symbol::intern("a")),
+                Token::Punct(Punct::HashHash),
+                Token::Identifier(Symbol::intern("b")),
+            ]
+        );
+    }
+
+    #[test]
+    fn spelling_is_tokenized_again() {
+        let code = r#"0x1Fu 017 2e10 1.5f "a\"\n" "\0""1\x7" '\'' '"' _Alignof ... ## <<"#;
+        let tokens = get_only_tokenized_tokens(code);
+        let spelling = tokens
+            .iter()
+            .map(|token| token.spelling())
+            .collect::<Vec<String>>()
+            .join(" ");
+        assert_eq!(
+            spelling,
+            r#"0x1Fu 017 20000000000.0 1.5f "a\"\n" "\000" "1\a" '\'' '"' _Alignof ... ## <<"#
+        );
+        assert_eq!(get_only_tokenized_tokens(&spelling), tokens);
+    }
+
+    #[test]
+    fn maximal_munch() {
+        let punct = |punct: &str| Token::Punct(Punct::from_spelling(punct).unwrap());
+        assert_eq!(
+            get_only_tokenized_tokens("a+++b->c<<=d>>=e..f%:%:g"),
+            vec![
+                Token::Identifier(Symbol::intern("a")),
+                punct("++"),
+                punct("+"),
+                Token::Identifier(Symbol::intern("b")),
+                punct("->"),
+                Token::Identifier(Symbol::intern("c")),
+                punct("<<="),
+                Token::Identifier(Symbol::intern("d")),
+                punct(">>="),
+                Token::Identifier(Symbol::intern("e")),
+                punct("."),
+                punct("."),
+                Token::Identifier(Symbol::intern("f")),
+                punct("##"),
+                Token::Identifier(Symbol::intern("g")),
+            ]
+        );
+    }
+
+    #[test]
+    fn digraphs() {
+        assert_eq!(
+            get_only_tokenized_tokens("<% a<:1:> %> %: <::"),
+            get_only_tokenized_tokens("{ a[1] } # [:")
+        );
+    }
+
+    #[test]
+    fn all_keywords() {
+        let code = "auto break case char const continue default do double else enum extern float \
+                    for goto if inline int long register restrict return short signed sizeof \
+                    static struct switch typedef union unsigned void volatile while _Alignas \
+                    _Alignof _Atomic _Bool _Complex _Generic _Imaginary _Noreturn \
+                    _Static_assert _Thread_local";
+        let tokens = get_only_tokenized_tokens(code);
+        assert_eq!(tokens.len(), 44);
+        for (token, spelling) in tokens.iter().zip(code.split_whitespace()) {
+            assert_eq!(
+                token,
+                &Token::Keyword(Keyword::from_spelling(spelling).unwrap())
+            );
+            assert_eq!(token.spelling(), spelling);
+        }
+    }
+
+    #[test]
+    fn alignment_keywords() {
+        assert_eq!(
+            get_only_tokenized_tokens("_Alignas(64) char buffer[_Alignof(long)];"),
+            vec![
+                Token::Keyword(Keyword::Alignas),
+                Token::Punct(Punct::LeftParen),
+                number(64),
+                Token::Punct(Punct::RightParen),
+                Token::Keyword(Keyword::Char),
+                Token::Identifier(Symbol::intern("buffer")),
+                Token::Punct(Punct::LeftBracket),
+                Token::Keyword(Keyword::Alignof),
+                Token::Punct(Punct::LeftParen),
+                Token::Keyword(Keyword::Long),
+                Token::Punct(Punct::RightParen),
+                Token::Punct(Punct::RightBracket),
+                Token::Punct(Punct::Semicolon),
+            ]
+        );
+    }
+
+    #[test]
+    fn static_assert_with_string_literal() {
+        assert_eq!(
+            get_only_tokenized_tokens(
+                r#"_Static_assert(sizeof(int) / 4, "int is \"4\" bytes\n");"#
+            ),
+            vec![
+                Token::Keyword(Keyword::StaticAssert),
+                Token::Punct(Punct::LeftParen),
+                Token::Keyword(Keyword::Sizeof),
+                Token::Punct(Punct::LeftParen),
+                Token::Keyword(Keyword::Int),
+                Token::Punct(Punct::RightParen),
+                Token::Punct(Punct::Slash),
+                number(4),
+                Token::Punct(Punct::Comma),
+                Token::StringLiteral("int is \"4\" bytes\n".to_string()),
+                Token::Punct(Punct::RightParen),
+                Token::Punct(Punct::Semicolon),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_string_literal() {
+        let errors = Tokenizer::tokenize(&"1 + \"abc\n\"".to_string()).unwrap_err();
+        let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        // the next line is tokenized after the error
+        assert_eq!(
+            messages,
+            vec![
+                "unterminated string literal at line 0 location 4",
+                "unterminated string literal at line 1 location 0",
+            ]
+        );
+    }
+
+    #[test]
+    fn character_constants() {
+        assert_eq!(
+            get_only_tokenized_tokens(r#"'a' '\n' '\\' '"'"#),
+            vec![
+                Token::Character('a'),
+                Token::Character('\n'),
+                Token::Character('\\'),
+                Token::Character('"'),
+            ]
+        );
+        assert_eq!(
+            get_only_tokenized_tokens(r#"'\0' '\012' '\123' '\x41' '\xff' "\x4a\101""#),
+            vec![
+                Token::Character('\0'),
+                Token::Character('\n'),
+                Token::Character('S'),
+                Token::Character('A'),
+                Token::Character('\u{ff}'),
+                Token::StringLiteral("JA".to_owned()),
+            ]
+        );
+        let errors = Tokenizer::tokenize(&"'ab' ''\n'a".to_string()).unwrap_err();
+        let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "invalid character constant 'ab' at line 0 location 0",
+                "invalid character constant '' at line 0 location 5",
+                "unterminated character constant at line 1 location 0",
+            ]
+        );
+        let errors = Tokenizer::tokenize(&r"'\x' '\777' '\1234'".to_string()).unwrap_err();
+        let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "invalid escape sequence \\x at line 0 location 0",
+                "invalid escape sequence \\777 at line 0 location 5",
+                "invalid character constant 'S4' at line 0 location 12",
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_are_collected() {
+        let errors = Tokenizer::tokenize(&"a @ 1e+ $\n1e999 2e39f 1.5f".to_string()).unwrap_err();
+        let error = |kind, line, location, start, end| LexError {
+            kind,
+            line,
+            location,
+            span: span(start, end),
+        };
+        assert_eq!(
+            errors,
+            vec![
+                error(LexErrorKind::UnexpectedChar('@'), 0, 2, 2, 3),
+                error(LexErrorKind::InvalidFloating("1e+".to_owned()), 0, 4, 4, 7),
+                error(LexErrorKind::UnexpectedChar('$'), 0, 8, 8, 9),
+                error(
+                    LexErrorKind::FloatingOverflow("1e999".to_owned()),
+                    1,
+                    0,
+                    10,
+                    15
+                ),
+                error(
+                    LexErrorKind::FloatingOverflow("2e39".to_owned()),
+                    1,
+                    6,
+                    16,
+                    21
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn ellipsis() {
+        assert_eq!(
+            get_only_tokenized_tokens("int f(int n, ...);"),
+            vec![
+                Token::Keyword(Keyword::Int),
+                Token::Identifier(Symbol::intern("f")),
+                Token::Punct(Punct::LeftParen),
+                Token::Keyword(Keyword::Int),
+                Token::Identifier(Symbol::intern("n")),
+                Token::Punct(Punct::Comma),
+                Token::Punct(Punct::Ellipsis),
+                Token::Punct(Punct::RightParen),
+                Token::Punct(Punct::Semicolon),
+            ]
+        );
+    }
+
+    #[test]
+    fn all_whitespace() {
+        assert_eq!(
+            get_only_tokenized_tokens("1\t+\x0c2\r\n*\x0b3\r"),
+            vec![
+                number(1),
+                Token::Punct(Punct::Plus),
+                number(2),
+                Token::Punct(Punct::Star),
+                number(3)
+            ]
+        );
+    }
+
+    #[test]
+    fn location_with_line_splicing() {
+        let actual = Tokenizer::tokenize(&"ab\\\ncd 1\\\r\n0\r\n+".to_string()).unwrap();
+        let expect = vec![
+            ManagedToken::new(Token::Identifier(Symbol::intern("abcd")), 0, 0)
+                .with_span(span(0, 6)),
+            ManagedToken::new(number(10), 1, 3).with_span(span(7, 12)),
+            ManagedToken::new(Token::Punct(Punct::Plus), 3, 0).with_span(span(14, 15)),
+        ];
+        assert_eq!(actual, expect);
+    }
+
     #[test]
     fn check_management_info() {
-        let actual = Tokenizer::tokenize(&"10 + 20\n30 * 40".to_string());
+        let actual = Tokenizer::tokenize(&"10 + 20\n30 * 40".to_string()).unwrap();
         let expect = vec![
-            ManagedToken::new(Token::Number(10), 0, 0),
-            ManagedToken::new(Token::Operator("+".to_string()), 0, 3),
-            ManagedToken::new(Token::Number(20), 0, 5),
-            ManagedToken::new(Token::Number(30), 1, 0),
-            ManagedToken::new(Token::Operator("*".to_string()), 1, 3),
-            ManagedToken::new(Token::Number(40), 1, 5),
+            ManagedToken::new(number(10), 0, 0).with_span(span(0, 2)),
+            ManagedToken::new(Token::Punct(Punct::Plus), 0, 3).with_span(span(3, 4)),
+            ManagedToken::new(number(20), 0, 5).with_span(span(5, 7)),
+            ManagedToken::new(number(30), 1, 0).with_span(span(8, 10)),
+            ManagedToken::new(Token::Punct(Punct::Star), 1, 3).with_span(span(11, 12)),
+            ManagedToken::new(number(40), 1, 5).with_span(span(13, 15)),
         ];
         assert_eq!(actual, expect);
     }
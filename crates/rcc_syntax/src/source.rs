@@ -0,0 +1,63 @@
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+/// chars of source code after line splicing (translation phase 2) with their physical positions
+/// and byte offsets
+///
+/// a backslash followed by a newline is removed and `\r\n` is read as a single `\n`
+#[derive(Clone)]
+pub struct SourceChars<'a> {
+    chars: Peekable<CharIndices<'a>>,
+    line: u32,
+    location: u32,
+}
+impl<'a> SourceChars<'a> {
+    pub fn new(code: &'a str) -> SourceChars<'a> {
+        SourceChars {
+            chars: code.char_indices().peekable(),
+            line: 0,
+            location: 0,
+        }
+    }
+
+    /// consume a physical char and move the position
+    fn consume(&mut self) -> Option<(char, usize)> {
+        let (offset, mut c) = self.chars.next()?;
+        if c == '\r' && matches!(self.chars.peek(), Some((_, '\n'))) {
+            c = self.chars.next()?.1;
+        }
+        if c == '\n' {
+            self.line += 1;
+            self.location = 0;
+        } else {
+            self.location += 1;
+        }
+        Some((c, offset))
+    }
+
+    /// check whether the next chars are a backslash and a newline
+    fn is_next_splice(&self) -> bool {
+        let mut chars = self.chars.clone().map(|(_, c)| c);
+        if chars.next() != Some('\\') {
+            return false;
+        }
+        match chars.next() {
+            Some('\n') => true,
+            Some('\r') => chars.next() == Some('\n'),
+            _ => false,
+        }
+    }
+}
+impl<'a> Iterator for SourceChars<'a> {
+    type Item = (char, u32, u32, u32); // (char, line, location, offset)
+
+    fn next(&mut self) -> Option<(char, u32, u32, u32)> {
+        while self.is_next_splice() {
+            self.consume(); // consume backslash
+            self.consume(); // consume newline
+        }
+        let (line, location) = (self.line, self.location);
+        self.consume()
+            .map(|(c, offset)| (c, line, location, offset as u32))
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt;
+
+/// index of a file in a `SourceMap`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FileId(pub u32);
+
+/// range of bytes in a file from `start` to `end` (exclusive)
+///
+/// spans of tokens are in the physical source, so a token spliced over lines covers the
+/// backslashes and newlines in it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub file: FileId,
+    pub start: u32,
+    pub end: u32,
+}
+impl Span {
+    pub fn new(file: FileId, start: u32, end: u32) -> Span {
+        Span { file, start, end }
+    }
+
+    /// span from the start of this span to the end of another span in the same file
+    pub fn to(self, other: Span) -> Span {
+        debug_assert_eq!(self.file, other.file);
+        Span {
+            file: self.file,
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    /// move a span which is relative to a part of a file into the file
+    pub fn shift(self, file: FileId, offset: u32) -> Span {
+        Span {
+            file,
+            start: self.start + offset,
+            end: self.end + offset,
+        }
+    }
+
+    pub fn len(&self) -> u32 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// source file with the offsets where its lines start
+#[derive(Clone, Debug)]
+pub struct SourceFile {
+    pub name: String,
+    pub code: String,
+    line_starts: Vec<u32>,
+}
+impl SourceFile {
+    pub fn new(name: String, code: String) -> SourceFile {
+        let line_starts = std::iter::once(0)
+            .chain(code.match_indices('\n').map(|(i, _)| i as u32 + 1))
+            .collect();
+        SourceFile {
+            name,
+            code,
+            line_starts,
+        }
+    }
+
+    /// physical line and column (chars from the start of the line) of a byte offset, both
+    /// starting with 0
+    pub fn position(&self, offset: u32) -> (u32, u32) {
+        let offset = offset.min(self.code.len() as u32);
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        };
+        let start = self.line_starts[line] as usize;
+        let column = self.code[start..offset as usize].chars().count();
+        (line as u32, column as u32)
+    }
+}
+
+/// files which spans point to
+///
+/// the preprocessor adds each file which it reads, so that diagnostics after preprocessing can
+/// show where tokens are in their files
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+impl SourceMap {
+    pub fn add_file(&mut self, name: String, code: String) -> FileId {
+        self.files.push(SourceFile::new(name, code));
+        FileId(self.files.len() as u32 - 1)
+    }
+
+    pub fn file(&self, file: FileId) -> &SourceFile {
+        &self.files[file.0 as usize]
+    }
+
+    /// physical line and column where a span starts
+    pub fn position(&self, span: Span) -> (u32, u32) {
+        self.file(span.file).position(span.start)
+    }
+
+    /// source code in a span
+    pub fn snippet(&self, span: Span) -> &str {
+        &self.file(span.file).code[span.start as usize..span.end as usize]
+    }
+
+    /// describe where a span starts as `name:line:column` (lines and columns from 1)
+    pub fn locate(&self, span: Span) -> Location {
+        let (line, column) = self.position(span);
+        Location {
+            name: self.file(span.file).name.clone(),
+            line: line + 1,
+            column: column + 1,
+        }
+    }
+}
+
+/// position for diagnostics as `name:line:column`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location {
+    pub name: String,
+    pub line: u32,
+    pub column: u32,
+}
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.name, self.line, self.column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn positions_of_offsets() {
+        let mut source_map = SourceMap::default();
+        source_map.add_file("a.c".to_owned(), "int a;\n".to_owned());
+        let file = source_map.add_file("b.c".to_owned(), "int\n  é = 1;\n\nx".to_owned());
+        assert_eq!(file, FileId(1));
+
+        let span = |start, end| Span::new(file, start, end);
+        assert_eq!(source_map.position(span(0, 3)), (0, 0));
+        assert_eq!(source_map.position(span(3, 4)), (0, 3));
+        assert_eq!(source_map.position(span(4, 6)), (1, 0));
+        // columns count chars rather than bytes
+        assert_eq!(source_map.position(span(9, 10)), (1, 4));
+        assert_eq!(source_map.position(span(15, 16)), (3, 0));
+        assert_eq!(source_map.snippet(span(6, 8)), "é");
+        assert_eq!(source_map.snippet(span(6, 8).to(span(11, 12))), "é = 1");
+        assert_eq!(source_map.locate(span(9, 10)).to_string(), "b.c:2:5");
+        assert_eq!(source_map.locate(Span::default()).to_string(), "a.c:1:1");
+    }
+}
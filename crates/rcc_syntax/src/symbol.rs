@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+/// interned identifier
+///
+/// identifiers are interned once by the tokenizer, so the parser and the code generator copy,
+/// compare and hash a `u32` instead of a `String`. the interner is shared by the threads of the
+/// process and never frees its strings, which is fine for the identifiers of a compilation
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Symbol(u32);
+impl Symbol {
+    pub fn intern(string: &str) -> Symbol {
+        interner().intern(string)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        interner().strings[self.0 as usize]
+    }
+
+    /// `true` for the default symbol, the empty identifier of abstract declarators
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Symbol({:?})", self.as_str())
+    }
+}
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+impl From<&str> for Symbol {
+    fn from(string: &str) -> Symbol {
+        Symbol::intern(string)
+    }
+}
+
+struct Interner {
+    symbols: HashMap<&'static str, Symbol>,
+    strings: Vec<&'static str>,
+}
+impl Interner {
+    fn new() -> Interner {
+        let mut interner = Interner {
+            symbols: HashMap::new(),
+            strings: vec![],
+        };
+        interner.intern(""); // Symbol::default()
+        interner
+    }
+
+    fn intern(&mut self, string: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(string) {
+            return symbol;
+        }
+        let symbol = Symbol(self.strings.len() as u32);
+        let string: &'static str = Box::leak(string.to_owned().into_boxed_str());
+        self.symbols.insert(string, symbol);
+        self.strings.push(string);
+        symbol
+    }
+}
+
+fn interner() -> MutexGuard<'static, Interner> {
+    static INTERNER: OnceLock<Mutex<Interner>> = OnceLock::new();
+    INTERNER
+        .get_or_init(|| Mutex::new(Interner::new()))
+        .lock()
+        .expect("interner is poisoned")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intern() {
+        let a = Symbol::intern("a");
+        assert_eq!(a, Symbol::intern("a"));
+        assert_ne!(a, Symbol::intern("b"));
+        assert_eq!(a.as_str(), "a");
+        assert_eq!(a, "a");
+        assert_eq!(format!("{}", a), "a");
+        assert_eq!(format!("{:?}", a), "Symbol(\"a\")");
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(Symbol::intern(""), Symbol::default());
+        assert!(Symbol::default().is_empty());
+        assert!(!Symbol::intern("a").is_empty());
+    }
+
+    #[test]
+    fn shared_by_threads() {
+        let a = std::thread::spawn(|| Symbol::intern("shared"))
+            .join()
+            .unwrap();
+        assert_eq!(a, Symbol::intern("shared"));
+        assert_eq!(a.as_str(), "shared");
+        let b = Symbol::intern("main");
+        assert_eq!(
+            std::thread::spawn(move || b.as_str()).join().unwrap(),
+            "main"
+        );
+    }
+}
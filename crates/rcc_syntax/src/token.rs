@@ -1,11 +1,45 @@
+use super::span::Span;
+use super::symbol::Symbol;
+use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt;
+use std::num::ParseIntError;
+use std::rc::Rc;
 
+/// macro expansion which a token comes from
+///
+/// a token from the replacement list of a macro is at the position where the macro is expanded
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expansion {
+    pub name: String,
+    pub definition: (u32, u32), // (line, location) of the macro name in its definition
+    pub parent: Option<Rc<Expansion>>, // expansion which the macro invocation comes from
+}
+impl fmt::Display for Expansion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "in expansion of macro {} defined at line {} location {}",
+            self.name, self.definition.0, self.definition.1
+        )?;
+        match &self.parent {
+            Some(parent) => write!(f, ", {}", parent),
+            None => Ok(()),
+        }
+    }
+}
+
+/// token with its position
+///
+/// `line` and `location` are the presumed position for diagnostics (changed by `#line`) and
+/// `span` is the physical range in a file of the `SourceMap`
 #[derive(Clone, PartialEq)]
 pub struct ManagedToken {
     token: Token,
     line: u32,
     location: u32,
+    span: Span,
+    expansion: Option<Rc<Expansion>>,
 }
 
 impl ManagedToken {
@@ -14,11 +48,43 @@ impl ManagedToken {
             token,
             line,
             location,
+            span: Span::default(),
+            expansion: None,
         }
     }
+    pub fn expanded(
+        token: Token,
+        line: u32,
+        location: u32,
+        expansion: Rc<Expansion>,
+    ) -> ManagedToken {
+        ManagedToken {
+            token,
+            line,
+            location,
+            span: Span::default(),
+            expansion: Some(expansion),
+        }
+    }
+    pub fn with_span(mut self, span: Span) -> ManagedToken {
+        self.span = span;
+        self
+    }
     pub fn get_token(&self) -> &Token {
         &self.token
     }
+    pub fn get_line(&self) -> u32 {
+        self.line
+    }
+    pub fn get_location(&self) -> u32 {
+        self.location
+    }
+    pub fn get_span(&self) -> Span {
+        self.span
+    }
+    pub fn get_expansion(&self) -> Option<&Rc<Expansion>> {
+        self.expansion.as_ref()
+    }
 }
 impl From<ManagedToken> for Token {
     fn from(managed_token: ManagedToken) -> Token {
@@ -36,64 +102,496 @@ impl fmt::Debug for ManagedToken {
     }
 }
 
+/// integer constant as written in the source (its value and type are checked by the parser)
+#[derive(PartialEq, Debug, Clone)]
+pub struct IntegerConstant {
+    pub digits: String,
+    pub radix: u32,
+    pub is_unsigned: bool,
+    pub long: u8, // number of `l` in the suffix
+}
+impl IntegerConstant {
+    /// unsuffixed decimal constant
+    pub fn decimal(value: u64) -> IntegerConstant {
+        IntegerConstant {
+            digits: value.to_string(),
+            radix: 10,
+            is_unsigned: false,
+            long: 0,
+        }
+    }
+
+    pub fn value(&self) -> Result<u64, ParseIntError> {
+        u64::from_str_radix(&self.digits, self.radix)
+    }
+}
+impl fmt::Display for IntegerConstant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // octal digits keep their leading 0
+        let prefix = match self.radix {
+            16 => "0x",
+            2 => "0b",
+            _ => "",
+        };
+        let unsigned = if self.is_unsigned { "u" } else { "" };
+        let long = "l".repeat(self.long as usize);
+        write!(f, "{}{}{}{}", prefix, self.digits, unsigned, long)
+    }
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub enum Token {
-    Number(u64),
-    Type(String),
-    Identifier(String),
-    Operator(String),
-    Bracket(String),
-    SBracket(String),
-    Parenthesis(String),
-    Return,
-    Comma,
-    Semicolon,
+    Number(IntegerConstant),
+    Float(f64),
+    Double(f64),
+    StringLiteral(String),
+    Character(char), // character constant, which has type int
+    Identifier(Symbol),
+    Keyword(Keyword),
+    Punct(Punct),
 }
 
 impl Token {
-    pub fn get_identifier(&self) -> Result<String, TokenError> {
+    pub fn get_identifier(&self) -> Result<Symbol, TokenError> {
         if let Token::Identifier(identifier) = self {
-            return Ok(identifier.to_owned());
+            return Ok(*identifier);
         }
         Err(TokenError::UnexpectedType(self.clone()))
     }
 
-    pub fn get_type(&self) -> Result<String, TokenError> {
-        if let Token::Type(ty) = self {
-            return Ok(ty.to_owned());
+    pub fn get_string_literal(&self) -> Result<String, TokenError> {
+        if let Token::StringLiteral(string) = self {
+            return Ok(string.to_owned());
         }
         Err(TokenError::UnexpectedType(self.clone()))
     }
 
-    pub fn get_number(&self) -> Result<u64, TokenError> {
-        if let Token::Number(num) = self {
-            return Ok(*num);
+    /// get the keyword of a type specifier (e.g. `int`)
+    pub fn get_type(&self) -> Result<Keyword, TokenError> {
+        match self {
+            Token::Keyword(keyword) if keyword.is_type_specifier() => Ok(*keyword),
+            _ => Err(TokenError::UnexpectedType(self.clone())),
         }
-        Err(TokenError::UnexpectedType(self.clone()))
     }
 
-    pub fn get_operator(&self) -> Result<String, TokenError> {
-        if let Token::Operator(op) = self.clone() {
-            return Ok(op);
+    pub fn get_number(&self) -> Result<IntegerConstant, TokenError> {
+        if let Token::Number(constant) = self {
+            return Ok(constant.clone());
         }
         Err(TokenError::UnexpectedType(self.clone()))
     }
+
+    pub fn is_keyword(&self, keyword: Keyword) -> bool {
+        self == &Token::Keyword(keyword)
+    }
+
+    pub fn is_punct(&self, punct: Punct) -> bool {
+        self == &Token::Punct(punct)
+    }
+
+    /// get the token as it is written in source code
+    pub fn spelling(&self) -> String {
+        match self {
+            Token::Number(number) => number.to_string(),
+            // debug format keeps the decimal point of an integral value (e.g. `2.0`)
+            Token::Float(number) => format!("{:?}f", number),
+            Token::Double(number) => format!("{:?}", number),
+            Token::StringLiteral(string) => {
+                let mut s = "\"".to_owned();
+                for c in string.chars() {
+                    push_escaped(&mut s, c, '"');
+                }
+                s + "\""
+            }
+            Token::Character(c) => {
+                let mut s = "'".to_owned();
+                push_escaped(&mut s, *c, '\'');
+                s + "'"
+            }
+            Token::Identifier(identifier) => identifier.as_str().to_owned(),
+            Token::Keyword(keyword) => keyword.spelling().to_owned(),
+            Token::Punct(punct) => punct.spelling().to_owned(),
+        }
+    }
 }
 impl fmt::Display for Token {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let string = match self {
-            Token::Number(number) => format!("number: {}", number),
-            Token::Type(ty) => format!("type: {}", ty),
-            Token::Identifier(identifier) => format!("identifier: {}", identifier),
-            Token::Operator(operator) => format!("operator: {}", operator),
-            Token::Bracket(bracket) => format!("bracket: {}", bracket),
-            Token::SBracket(bracket) => format!("sbracket: {}", bracket),
-            Token::Parenthesis(parenthesis) => format!("parenthesis: {}", parenthesis),
-            Token::Return => format!("return"),
-            Token::Comma => format!("comma"),
-            Token::Semicolon => format!("semicolon"),
+        match self {
+            Token::Number(number) => write!(f, "number: {}", number),
+            Token::Float(number) => write!(f, "float: {}", number),
+            Token::Double(number) => write!(f, "double: {}", number),
+            Token::StringLiteral(string) => write!(f, "string: {:?}", string),
+            Token::Character(c) => write!(f, "character: {:?}", c),
+            Token::Identifier(identifier) => write!(f, "identifier: {}", identifier),
+            Token::Keyword(keyword) => write!(f, "keyword: {}", keyword.spelling()),
+            Token::Punct(punct) => write!(f, "punctuator: {}", punct.spelling()),
+        }
+    }
+}
+
+/// value of a character constant, whose char is signed as on x86-64 (e.g. `'\xff'` is -1)
+pub fn character_value(c: char) -> i64 {
+    match u8::try_from(c) {
+        Ok(byte) => byte as i8 as i64,
+        Err(_) => c as i64,
+    }
+}
+
+/// push a char of a string literal or a character constant, which is escaped if needed
+fn push_escaped(s: &mut String, c: char, quote: char) {
+    match c {
+        '\\' => s.push_str("\\\\"),
+        '\n' => s.push_str("\\n"),
+        '\t' => s.push_str("\\t"),
+        '\r' => s.push_str("\\r"),
+        '\x0b' => s.push_str("\\v"),
+        '\x0c' => s.push_str("\\f"),
+        '\x07' => s.push_str("\\a"),
+        '\x08' => s.push_str("\\b"),
+        // three digits so that a following digit is not a part of the escape sequence
+        c if c.is_ascii_control() => s.push_str(&format!("\\{:03o}", c as u32)),
+        c if c == quote => {
+            s.push('\\');
+            s.push(c);
+        }
+        c => s.push(c),
+    }
+}
+
+/// keyword of C11
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Keyword {
+    Auto,
+    Break,
+    Case,
+    Char,
+    Const,
+    Continue,
+    Default,
+    Do,
+    Double,
+    Else,
+    Enum,
+    Extern,
+    Float,
+    For,
+    Goto,
+    If,
+    Inline,
+    Int,
+    Long,
+    Register,
+    Restrict,
+    Return,
+    Short,
+    Signed,
+    Sizeof,
+    Static,
+    Struct,
+    Switch,
+    Typedef,
+    Union,
+    Unsigned,
+    Void,
+    Volatile,
+    While,
+    Alignas,
+    Alignof,
+    Atomic,
+    Bool,
+    Complex,
+    Generic,
+    Imaginary,
+    Noreturn,
+    StaticAssert,
+    ThreadLocal,
+}
+impl Keyword {
+    pub fn spelling(self) -> &'static str {
+        match self {
+            Keyword::Auto => "auto",
+            Keyword::Break => "break",
+            Keyword::Case => "case",
+            Keyword::Char => "char",
+            Keyword::Const => "const",
+            Keyword::Continue => "continue",
+            Keyword::Default => "default",
+            Keyword::Do => "do",
+            Keyword::Double => "double",
+            Keyword::Else => "else",
+            Keyword::Enum => "enum",
+            Keyword::Extern => "extern",
+            Keyword::Float => "float",
+            Keyword::For => "for",
+            Keyword::Goto => "goto",
+            Keyword::If => "if",
+            Keyword::Inline => "inline",
+            Keyword::Int => "int",
+            Keyword::Long => "long",
+            Keyword::Register => "register",
+            Keyword::Restrict => "restrict",
+            Keyword::Return => "return",
+            Keyword::Short => "short",
+            Keyword::Signed => "signed",
+            Keyword::Sizeof => "sizeof",
+            Keyword::Static => "static",
+            Keyword::Struct => "struct",
+            Keyword::Switch => "switch",
+            Keyword::Typedef => "typedef",
+            Keyword::Union => "union",
+            Keyword::Unsigned => "unsigned",
+            Keyword::Void => "void",
+            Keyword::Volatile => "volatile",
+            Keyword::While => "while",
+            Keyword::Alignas => "_Alignas",
+            Keyword::Alignof => "_Alignof",
+            Keyword::Atomic => "_Atomic",
+            Keyword::Bool => "_Bool",
+            Keyword::Complex => "_Complex",
+            Keyword::Generic => "_Generic",
+            Keyword::Imaginary => "_Imaginary",
+            Keyword::Noreturn => "_Noreturn",
+            Keyword::StaticAssert => "_Static_assert",
+            Keyword::ThreadLocal => "_Thread_local",
+        }
+    }
+
+    /// get the keyword spelled by an identifier
+    pub fn from_spelling(s: &str) -> Option<Keyword> {
+        let keyword = match s {
+            "auto" => Keyword::Auto,
+            "break" => Keyword::Break,
+            "case" => Keyword::Case,
+            "char" => Keyword::Char,
+            "const" => Keyword::Const,
+            "continue" => Keyword::Continue,
+            "default" => Keyword::Default,
+            "do" => Keyword::Do,
+            "double" => Keyword::Double,
+            "else" => Keyword::Else,
+            "enum" => Keyword::Enum,
+            "extern" => Keyword::Extern,
+            "float" => Keyword::Float,
+            "for" => Keyword::For,
+            "goto" => Keyword::Goto,
+            "if" => Keyword::If,
+            "inline" => Keyword::Inline,
+            "int" => Keyword::Int,
+            "long" => Keyword::Long,
+            "register" => Keyword::Register,
+            "restrict" => Keyword::Restrict,
+            "return" => Keyword::Return,
+            "short" => Keyword::Short,
+            "signed" => Keyword::Signed,
+            "sizeof" => Keyword::Sizeof,
+            "static" => Keyword::Static,
+            "struct" => Keyword::Struct,
+            "switch" => Keyword::Switch,
+            "typedef" => Keyword::Typedef,
+            "union" => Keyword::Union,
+            "unsigned" => Keyword::Unsigned,
+            "void" => Keyword::Void,
+            "volatile" => Keyword::Volatile,
+            "while" => Keyword::While,
+            "_Alignas" => Keyword::Alignas,
+            "_Alignof" => Keyword::Alignof,
+            "_Atomic" => Keyword::Atomic,
+            "_Bool" => Keyword::Bool,
+            "_Complex" => Keyword::Complex,
+            "_Generic" => Keyword::Generic,
+            "_Imaginary" => Keyword::Imaginary,
+            "_Noreturn" => Keyword::Noreturn,
+            "_Static_assert" => Keyword::StaticAssert,
+            "_Thread_local" => Keyword::ThreadLocal,
+            _ => return None,
+        };
+        Some(keyword)
+    }
+
+    /// check whether the keyword is a type specifier which the parser supports
+    pub fn is_type_specifier(self) -> bool {
+        matches!(
+            self,
+            Keyword::Char
+                | Keyword::Short
+                | Keyword::Int
+                | Keyword::Long
+                | Keyword::Signed
+                | Keyword::Unsigned
+                | Keyword::Bool
+                | Keyword::Float
+                | Keyword::Double
+        )
+    }
+}
+
+/// punctuator of C11
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Punct {
+    LeftBracket,
+    RightBracket,
+    LeftParen,
+    RightParen,
+    LeftBrace,
+    RightBrace,
+    Dot,
+    Arrow,
+    Increment,
+    Decrement,
+    Ampersand,
+    Star,
+    Plus,
+    Minus,
+    Tilde,
+    Exclamation,
+    Slash,
+    Percent,
+    LeftShift,
+    RightShift,
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    Caret,
+    Pipe,
+    AndAnd,
+    OrOr,
+    Question,
+    Colon,
+    Semicolon,
+    Ellipsis,
+    Assign,
+    MulAssign,
+    DivAssign,
+    ModAssign,
+    AddAssign,
+    SubAssign,
+    LeftShiftAssign,
+    RightShiftAssign,
+    AndAssign,
+    XorAssign,
+    OrAssign,
+    Comma,
+    Hash,
+    HashHash,
+}
+impl Punct {
+    pub fn spelling(self) -> &'static str {
+        match self {
+            Punct::LeftBracket => "[",
+            Punct::RightBracket => "]",
+            Punct::LeftParen => "(",
+            Punct::RightParen => ")",
+            Punct::LeftBrace => "{",
+            Punct::RightBrace => "}",
+            Punct::Dot => ".",
+            Punct::Arrow => "->",
+            Punct::Increment => "++",
+            Punct::Decrement => "--",
+            Punct::Ampersand => "&",
+            Punct::Star => "*",
+            Punct::Plus => "+",
+            Punct::Minus => "-",
+            Punct::Tilde => "~",
+            Punct::Exclamation => "!",
+            Punct::Slash => "/",
+            Punct::Percent => "%",
+            Punct::LeftShift => "<<",
+            Punct::RightShift => ">>",
+            Punct::Less => "<",
+            Punct::Greater => ">",
+            Punct::LessEqual => "<=",
+            Punct::GreaterEqual => ">=",
+            Punct::Equal => "==",
+            Punct::NotEqual => "!=",
+            Punct::Caret => "^",
+            Punct::Pipe => "|",
+            Punct::AndAnd => "&&",
+            Punct::OrOr => "||",
+            Punct::Question => "?",
+            Punct::Colon => ":",
+            Punct::Semicolon => ";",
+            Punct::Ellipsis => "...",
+            Punct::Assign => "=",
+            Punct::MulAssign => "*=",
+            Punct::DivAssign => "/=",
+            Punct::ModAssign => "%=",
+            Punct::AddAssign => "+=",
+            Punct::SubAssign => "-=",
+            Punct::LeftShiftAssign => "<<=",
+            Punct::RightShiftAssign => ">>=",
+            Punct::AndAssign => "&=",
+            Punct::XorAssign => "^=",
+            Punct::OrAssign => "|=",
+            Punct::Comma => ",",
+            Punct::Hash => "#",
+            Punct::HashHash => "##",
+        }
+    }
+
+    /// get the punctuator spelled by chars (digraphs are the same as their punctuators)
+    pub fn from_spelling(s: &str) -> Option<Punct> {
+        let punct = match s {
+            "[" => Punct::LeftBracket,
+            "]" => Punct::RightBracket,
+            "(" => Punct::LeftParen,
+            ")" => Punct::RightParen,
+            "{" => Punct::LeftBrace,
+            "}" => Punct::RightBrace,
+            "." => Punct::Dot,
+            "->" => Punct::Arrow,
+            "++" => Punct::Increment,
+            "--" => Punct::Decrement,
+            "&" => Punct::Ampersand,
+            "*" => Punct::Star,
+            "+" => Punct::Plus,
+            "-" => Punct::Minus,
+            "~" => Punct::Tilde,
+            "!" => Punct::Exclamation,
+            "/" => Punct::Slash,
+            "%" => Punct::Percent,
+            "<<" => Punct::LeftShift,
+            ">>" => Punct::RightShift,
+            "<" => Punct::Less,
+            ">" => Punct::Greater,
+            "<=" => Punct::LessEqual,
+            ">=" => Punct::GreaterEqual,
+            "==" => Punct::Equal,
+            "!=" => Punct::NotEqual,
+            "^" => Punct::Caret,
+            "|" => Punct::Pipe,
+            "&&" => Punct::AndAnd,
+            "||" => Punct::OrOr,
+            "?" => Punct::Question,
+            ":" => Punct::Colon,
+            ";" => Punct::Semicolon,
+            "..." => Punct::Ellipsis,
+            "=" => Punct::Assign,
+            "*=" => Punct::MulAssign,
+            "/=" => Punct::DivAssign,
+            "%=" => Punct::ModAssign,
+            "+=" => Punct::AddAssign,
+            "-=" => Punct::SubAssign,
+            "<<=" => Punct::LeftShiftAssign,
+            ">>=" => Punct::RightShiftAssign,
+            "&=" => Punct::AndAssign,
+            "^=" => Punct::XorAssign,
+            "|=" => Punct::OrAssign,
+            "," => Punct::Comma,
+            "#" => Punct::Hash,
+            "##" => Punct::HashHash,
+            // digraphs
+            "<:" => Punct::LeftBracket,
+            ":>" => Punct::RightBracket,
+            "<%" => Punct::LeftBrace,
+            "%>" => Punct::RightBrace,
+            "%:" => Punct::Hash,
+            "%:%:" => Punct::HashHash,
+            _ => return None,
         };
-        write!(f, "{}", string)
+        Some(punct)
     }
 }
 
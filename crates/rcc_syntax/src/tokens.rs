@@ -1,59 +1,101 @@
-use super::token::{ManagedToken, Token};
+use super::span::Span;
+use super::token::{Keyword, ManagedToken, Punct, Token};
 use std::error::Error;
 use std::fmt;
-use std::iter::Peekable;
-use std::vec::IntoIter;
 
 type Result<T> = std::result::Result<T, ConsumeError>;
 
+/// position of the cursor of `Tokens` to rewind to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Checkpoint(usize);
+
+/// cursor over tokens
+///
+/// the tokens are kept after they are consumed, so a parser can look ahead any number of tokens
+/// and rewind to a checkpoint after parsing speculatively
 #[derive(Debug, Clone)]
 pub struct Tokens {
-    tokens: Peekable<IntoIter<ManagedToken>>,
+    tokens: Vec<ManagedToken>,
+    position: usize,
 }
 impl Tokens {
     pub fn new(tokens: Vec<ManagedToken>) -> Tokens {
         Tokens {
-            tokens: tokens.into_iter().peekable(),
+            tokens,
+            position: 0,
         }
     }
 
-    pub fn peek(&self) -> Option<ManagedToken> {
-        let mut tokens = self.clone();
-        tokens.next()
+    pub fn peek(&self) -> Option<&ManagedToken> {
+        self.peek_nth(0)
     }
 
-    pub fn peek2(&self) -> Option<ManagedToken> {
-        let mut tokens = self.clone();
-        tokens.next();
-        tokens.next()
+    /// get the token `n` tokens after the next one without consuming tokens
+    pub fn peek_nth(&self, n: usize) -> Option<&ManagedToken> {
+        self.tokens.get(self.position + n)
     }
 
     pub fn next(&mut self) -> Option<ManagedToken> {
-        self.tokens.next()
+        let token = self.tokens.get(self.position)?.clone();
+        self.position += 1;
+        Some(token)
     }
 
+    /// number of the tokens which are not consumed
     pub fn len(&self) -> usize {
-        self.tokens.len()
+        self.tokens.len() - self.position
     }
 
-    pub fn check_next_operator(&mut self, target_operator: &str) -> Option<ManagedToken> {
-        if let Some(token) = self.peek() {
-            if let Token::Operator(operator) = token.get_token() {
-                if operator == target_operator {
-                    return Some(token);
-                }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.position)
+    }
+
+    /// move the cursor back to a checkpoint, so that the tokens after it are consumed again
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.position = checkpoint.0;
+    }
+
+    /// span from the first to the last token consumed since a checkpoint
+    ///
+    /// the span is the one of the first token if the tokens are in different files (e.g. a
+    /// construct ending in an included file)
+    pub fn span_since(&self, checkpoint: Checkpoint) -> Span {
+        let consumed = &self.tokens[checkpoint.0..self.position];
+        match (consumed.first(), consumed.last()) {
+            (Some(first), Some(last)) if first.get_span().file == last.get_span().file => {
+                first.get_span().to(last.get_span())
             }
+            (Some(first), _) => first.get_span(),
+            _ => Span::default(),
         }
-        None
+    }
+
+    pub fn check_next_punct(&self, punct: Punct) -> Option<&ManagedToken> {
+        self.peek()
+            .filter(|token| token.get_token().is_punct(punct))
     }
 
     pub fn check_next_is_expression_node(&self) -> bool {
         if let Some(token) = self.peek() {
-            if let Token::Identifier(_) = token.get_token() {
-                return true;
-            }
-            if let Token::Number(_) = token.get_token() {
-                return true;
+            match token.get_token() {
+                Token::Identifier(_)
+                | Token::Number(_)
+                | Token::Character(_)
+                | Token::Float(_)
+                | Token::Double(_) => return true,
+                // parenthesized expression or unary operator
+                Token::Punct(Punct::LeftParen)
+                | Token::Punct(Punct::Ampersand)
+                | Token::Punct(Punct::Star)
+                | Token::Punct(Punct::Plus)
+                | Token::Punct(Punct::Minus)
+                | Token::Keyword(Keyword::Sizeof)
+                | Token::Keyword(Keyword::Alignof) => return true,
+                _ => (),
             }
         }
         false
@@ -61,10 +103,10 @@ impl Tokens {
 
     pub fn consume_expression(&mut self) -> Result<Vec<ManagedToken>> {
         let mut target_tokens: Vec<ManagedToken> = Vec::new();
-        while let Some(token) = self.tokens.peek() {
+        while let Some(token) = self.peek() {
             match token.get_token() {
-                Token::Number(_) | Token::Operator(_) => {
-                    target_tokens.push(self.tokens.next().unwrap());
+                Token::Number(_) | Token::Punct(_) => {
+                    target_tokens.push(self.next().unwrap());
                 }
                 _ => {
                     break;
@@ -74,16 +116,14 @@ impl Tokens {
         Ok(target_tokens)
     }
 
-    pub fn consume_to_binary_operator(&mut self, operator: String) -> Vec<ManagedToken> {
+    pub fn consume_to_binary_operator(&mut self, operator: Punct) -> Vec<ManagedToken> {
         let mut target_tokens: Vec<ManagedToken> = Vec::new();
         while let Some(token) = self.peek() {
-            if let Token::Operator(op) = token.get_token() {
-                if op == &operator {
-                    self.next();
-                    break;
-                }
+            if token.get_token().is_punct(operator) {
+                self.next();
+                break;
             }
-            if let Token::Semicolon = token.get_token() {
+            if token.get_token().is_punct(Punct::Semicolon) {
                 break;
             }
             target_tokens.push(self.next().unwrap());
@@ -91,82 +131,41 @@ impl Tokens {
         target_tokens
     }
 
-    pub fn consume_type(&mut self) -> Result<ManagedToken> {
-        match self.tokens.peek() {
-            Some(token) => match token.get_token() {
-                Token::Type(_) => Ok(self.tokens.next().unwrap()),
-                _ => Err(ConsumeError::Consume(Some(token.clone()))),
-            },
+    /// consume the next token if it satisfies a condition
+    fn consume_if(&mut self, condition: impl Fn(&Token) -> bool) -> Result<ManagedToken> {
+        match self.peek() {
+            Some(token) if condition(token.get_token()) => Ok(self.next().unwrap()),
+            Some(token) => Err(ConsumeError::Consume(Some(token.clone()))),
             None => Err(ConsumeError::Consume(None)),
         }
     }
 
-    pub fn consume_identifier(&mut self) -> Result<ManagedToken> {
-        match self.tokens.peek() {
-            Some(token) => match token.get_token() {
-                Token::Identifier(_) => Ok(self.tokens.next().unwrap()),
-                _ => Err(ConsumeError::Consume(Some(token.clone()))),
-            },
-            None => Err(ConsumeError::Consume(None)),
-        }
-    }
-
-    pub fn consume_parenthesis(&mut self) -> Result<ManagedToken> {
-        match self.tokens.peek() {
-            Some(token) => match token.get_token() {
-                Token::Parenthesis(_) => Ok(self.tokens.next().unwrap()),
-                _ => Err(ConsumeError::Consume(Some(token.clone()))),
-            },
-            None => Err(ConsumeError::Consume(None)),
-        }
+    /// consume a keyword of a type specifier (e.g. `int`)
+    pub fn consume_type(&mut self) -> Result<ManagedToken> {
+        self.consume_if(|token| token.get_type().is_ok())
     }
 
-    pub fn consume_bracket(&mut self) -> Result<ManagedToken> {
-        match self.tokens.peek() {
-            Some(token) => match token.get_token() {
-                Token::Bracket(_) => Ok(self.tokens.next().unwrap()),
-                _ => Err(ConsumeError::Consume(Some(token.clone()))),
-            },
-            None => Err(ConsumeError::Consume(None)),
-        }
+    pub fn consume_identifier(&mut self) -> Result<ManagedToken> {
+        self.consume_if(|token| matches!(token, Token::Identifier(_)))
     }
 
-    pub fn consume_return(&mut self) -> Result<ManagedToken> {
-        match self.tokens.peek() {
-            Some(token) => match token.get_token() {
-                Token::Return => Ok(self.tokens.next().unwrap()),
-                _ => Err(ConsumeError::Consume(Some(token.clone()))),
-            },
-            None => Err(ConsumeError::Consume(None)),
-        }
+    pub fn consume_string_literal(&mut self) -> Result<ManagedToken> {
+        self.consume_if(|token| matches!(token, Token::StringLiteral(_)))
     }
 
-    pub fn consume_comma(&mut self) -> Result<ManagedToken> {
-        match self.tokens.peek() {
-            Some(token) => match token.get_token() {
-                Token::Comma => Ok(self.tokens.next().unwrap()),
-                _ => Err(ConsumeError::Consume(Some(token.clone()))),
-            },
-            None => Err(ConsumeError::Consume(None)),
-        }
+    pub fn consume_keyword(&mut self, keyword: Keyword) -> Result<ManagedToken> {
+        self.consume_if(|token| token.is_keyword(keyword))
     }
 
-    pub fn consume_semicolon(&mut self) -> Result<ManagedToken> {
-        match self.tokens.peek() {
-            Some(token) => match token.get_token() {
-                Token::Semicolon => Ok(self.tokens.next().unwrap()),
-                _ => Err(ConsumeError::Consume(Some(token.clone()))),
-            },
-            None => Err(ConsumeError::Consume(None)),
-        }
+    pub fn consume_punct(&mut self, punct: Punct) -> Result<ManagedToken> {
+        self.consume_if(|token| token.is_punct(punct))
     }
 }
 
 impl fmt::Display for Tokens {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "tokens =========================\n")?;
-        let mut tokens = self.clone();
-        while let Some(mtoken) = tokens.next() {
+        for mtoken in &self.tokens[self.position..] {
             write!(f, "{:?}\n", mtoken.get_token())?;
         }
         write!(f, "================================\n")
@@ -201,6 +200,52 @@ impl Error for ConsumeError {
 mod tests {
 
     use super::*;
+    use crate::token::IntegerConstant;
+
+    fn number(value: u64) -> Token {
+        Token::Number(IntegerConstant::decimal(value))
+    }
+
+    #[test]
+    fn lookahead_and_rewind() {
+        let mut tokens = Tokens::new(
+            (1..=4)
+                .map(|value| ManagedToken::new(number(value), 0, 0))
+                .collect(),
+        );
+        let token = |token: Option<&ManagedToken>| token.map(|token| token.get_token().clone());
+        assert_eq!(token(tokens.peek_nth(3)), Some(number(4)));
+        assert_eq!(token(tokens.peek_nth(4)), None);
+
+        tokens.next();
+        let checkpoint = tokens.checkpoint();
+        tokens.next();
+        tokens.next();
+        assert_eq!(token(tokens.peek()), Some(number(4)));
+        assert_eq!(tokens.len(), 1);
+        tokens.rewind(checkpoint);
+        assert_eq!(token(tokens.peek()), Some(number(2)));
+        assert_eq!(tokens.len(), 3);
+    }
+
+    #[test]
+    fn span_since_checkpoint() {
+        use crate::span::FileId;
+        let span = |file, start, end| Span::new(FileId(file), start, end);
+        let mut tokens = Tokens::new(vec![
+            ManagedToken::new(number(1), 0, 0).with_span(span(0, 0, 1)),
+            ManagedToken::new(number(2), 0, 2).with_span(span(0, 2, 4)),
+            ManagedToken::new(number(3), 0, 0).with_span(span(1, 0, 1)),
+        ]);
+        let checkpoint = tokens.checkpoint();
+        assert_eq!(tokens.span_since(checkpoint), Span::default());
+        tokens.next();
+        tokens.next();
+        assert_eq!(tokens.span_since(checkpoint), span(0, 0, 4));
+        tokens.next();
+        // the last token is in another file
+        assert_eq!(tokens.span_since(checkpoint), span(0, 0, 1));
+    }
 
     #[cfg(test)]
     mod consumer {
@@ -210,37 +255,33 @@ mod tests {
         #[test]
         fn consume_to_binary_operator_add() {
             let mut tokens = Tokens::new(
-                vec![
-                    Token::Number(10),
-                    Token::Number(20),
-                    Token::Operator("+".to_owned()),
-                ]
-                .into_iter()
-                .map(|token| ManagedToken::new(token, 0, 0))
-                .collect(),
+                vec![number(10), number(20), Token::Punct(Punct::Plus)]
+                    .into_iter()
+                    .map(|token| ManagedToken::new(token, 0, 0))
+                    .collect(),
             );
             let actual: Vec<Token> = tokens
-                .consume_to_binary_operator("+".to_string())
+                .consume_to_binary_operator(Punct::Plus)
                 .into_iter()
                 .map(|mtoken| From::from(mtoken))
                 .collect();
-            assert_eq!(actual, vec![Token::Number(10), Token::Number(20)]);
+            assert_eq!(actual, vec![number(10), number(20)]);
         }
 
         #[test]
         fn consume_to_binary_operator_eol() {
             let mut tokens = Tokens::new(
-                vec![Token::Number(10), Token::Number(20)]
+                vec![number(10), number(20)]
                     .into_iter()
                     .map(|token| ManagedToken::new(token, 0, 0))
                     .collect(),
             );
             let actual: Vec<Token> = tokens
-                .consume_to_binary_operator("+".to_string())
+                .consume_to_binary_operator(Punct::Plus)
                 .into_iter()
                 .map(|mtoken| From::from(mtoken))
                 .collect();
-            assert_eq!(actual, vec![Token::Number(10), Token::Number(20)]);
+            assert_eq!(actual, vec![number(10), number(20)]);
         }
     }
 }